@@ -0,0 +1,642 @@
+//! Renders an AST to text styled with ANSI escape sequences
+//!
+//! [`ansi`](crate::ansi) module provides [`ToAnsi`] trait, which renders a markdown component for terminals.
+//!
+//! | Element | Style |
+//! |---|---|
+//! | [`ItalicsStar`](crate::ast::ItalicsStar), [`ItalicsUnderscore`](crate::ast::ItalicsUnderscore) | italics (`ESC[3m`) |
+//! | [`Bold`](crate::ast::Bold) | bold (`ESC[1m`) |
+//! | [`Underline`](crate::ast::Underline) | underline (`ESC[4m`) |
+//! | [`Strikethrough`](crate::ast::Strikethrough) | strikethrough (`ESC[9m`) |
+//! | [`Spoiler`](crate::ast::Spoiler) | reverse video (`ESC[7m`), or masked with [`SpoilerStyle::Hidden`] |
+//! | [`OneLineCode`](crate::ast::OneLineCode), [`MultiLineCode`](crate::ast::MultiLineCode) | dim (`ESC[2m`) |
+//! | [`BlockQuote`](crate::ast::BlockQuote) | `│ ` at the start of each line |
+//!
+//! Every styled line ends with a reset sequence, so the output can be printed line by line.
+//! Control characters in the text are replaced with visible symbols, so that a message cannot control the terminal.
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::ansi::{AnsiOption, ToAnsi};
+//! use discord_md::parse;
+//!
+//! let ast = parse("**bold** and `code`");
+//!
+//! assert_eq!(
+//!     ast.to_ansi(&AnsiOption::new()),
+//!     "\x1b[1mbold\x1b[0m and \x1b[2mcode\x1b[0m"
+//! );
+//! assert_eq!(ast.to_ansi(&AnsiOption::new().color(false)), "bold and code");
+//! ```
+
+use crate::ast::{MarkdownDocument, MarkdownElement, MarkdownElementCollection};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Struct that allows to alter [`to_ansi()`](ToAnsi::to_ansi())'s behaviour.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::ansi::{AnsiOption, SpoilerStyle, ToAnsi};
+/// use discord_md::parse;
+///
+/// let ast = parse("the answer is ||42||");
+///
+/// assert_eq!(
+///     ast.to_ansi(&AnsiOption::new().color(false).spoiler(SpoilerStyle::Hidden)),
+///     "the answer is ██"
+/// );
+/// assert_eq!(
+///     ast.to_ansi(&AnsiOption::new().color(false).width(Some(10))),
+///     "the answer\nis 42"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct AnsiOption {
+    /// Emit escape sequences. If disabled, the output is plain text
+    pub color: bool,
+
+    /// How spoilers are rendered
+    pub spoiler: SpoilerStyle,
+
+    /// Wrap lines at word boundaries so that each line is at most this many columns, or `None` not to wrap.
+    /// Wide characters like CJK and emoji take two columns
+    pub width: Option<usize>,
+}
+
+impl Default for AnsiOption {
+    fn default() -> Self {
+        Self {
+            color: true,
+            spoiler: SpoilerStyle::default(),
+            width: None,
+        }
+    }
+}
+
+impl AnsiOption {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn color(mut self, value: bool) -> Self {
+        self.color = value;
+        self
+    }
+
+    pub fn spoiler(mut self, value: SpoilerStyle) -> Self {
+        self.spoiler = value;
+        self
+    }
+
+    pub fn width(mut self, value: Option<usize>) -> Self {
+        self.width = value;
+        self
+    }
+}
+
+/// How spoilers are rendered by [`to_ansi()`](ToAnsi::to_ansi()).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum SpoilerStyle {
+    /// Render in reverse video. The text is shown as it is if [`AnsiOption::color`] is disabled.
+    #[default]
+    Reverse,
+
+    /// Replace every character of the content except whitespace with `█`.
+    Hidden,
+}
+
+/// A trait for rendering a markdown component to ANSI styled text.
+pub trait ToAnsi {
+    /// Returns the component as text styled with ANSI escape sequences.
+    fn to_ansi(&self, option: &AnsiOption) -> String;
+}
+
+impl ToAnsi for MarkdownDocument {
+    fn to_ansi(&self, option: &AnsiOption) -> String {
+        self.content().to_ansi(option)
+    }
+}
+
+impl ToAnsi for MarkdownElementCollection {
+    fn to_ansi(&self, option: &AnsiOption) -> String {
+        let mut renderer = Renderer::new(option);
+        renderer.elements(self.get(), Style::default());
+        renderer.finish()
+    }
+}
+
+impl ToAnsi for MarkdownElement {
+    fn to_ansi(&self, option: &AnsiOption) -> String {
+        let mut renderer = Renderer::new(option);
+        renderer.element(self, Style::default());
+        renderer.finish()
+    }
+}
+
+/// Text attributes of a character.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+struct Style {
+    bold: bool,
+    dim: bool,
+    italics: bool,
+    underline: bool,
+    reverse: bool,
+    strikethrough: bool,
+}
+
+impl Style {
+    /// Returns the escape sequence that sets the style, or an empty string for the default style.
+    fn sequence(&self) -> String {
+        let codes = [
+            (self.bold, "1"),
+            (self.dim, "2"),
+            (self.italics, "3"),
+            (self.underline, "4"),
+            (self.reverse, "7"),
+            (self.strikethrough, "9"),
+        ]
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, code)| *code)
+        .collect::<Vec<_>>();
+
+        if codes.is_empty() {
+            String::new()
+        } else {
+            format!("\x1b[{}m", codes.join(";"))
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+const QUOTE_PREFIX: &str = "│ ";
+
+/// A line of styled characters, before wrapping.
+#[derive(Debug, Default)]
+struct Line {
+    /// Number of block quotes the line is in.
+    quotes: usize,
+    cells: Vec<(char, Style)>,
+}
+
+/// Renders elements into lines of styled characters.
+struct Renderer<'a> {
+    option: &'a AnsiOption,
+    lines: Vec<Line>,
+    quotes: usize,
+    /// A block element ended, so the next character starts a new line.
+    pending_break: bool,
+    /// The style of a hidden spoiler being rendered, whose characters are masked.
+    masked: Option<Style>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(option: &'a AnsiOption) -> Self {
+        Self {
+            option,
+            lines: vec![Line::default()],
+            quotes: 0,
+            pending_break: false,
+            masked: None,
+        }
+    }
+
+    fn elements(&mut self, elements: &[MarkdownElement], style: Style) {
+        for element in elements {
+            self.element(element, style);
+        }
+    }
+
+    fn element(&mut self, element: &MarkdownElement, style: Style) {
+        match element {
+            MarkdownElement::Plain(x) => self.text(x.content(), style),
+            MarkdownElement::ItalicsStar(x) => self.elements(
+                x.content().get(),
+                Style {
+                    italics: true,
+                    ..style
+                },
+            ),
+            MarkdownElement::ItalicsUnderscore(x) => self.elements(
+                x.content().get(),
+                Style {
+                    italics: true,
+                    ..style
+                },
+            ),
+            MarkdownElement::Bold(x) => self.elements(
+                x.content().get(),
+                Style {
+                    bold: true,
+                    ..style
+                },
+            ),
+            MarkdownElement::Underline(x) => self.elements(
+                x.content().get(),
+                Style {
+                    underline: true,
+                    ..style
+                },
+            ),
+            MarkdownElement::Strikethrough(x) => self.elements(
+                x.content().get(),
+                Style {
+                    strikethrough: true,
+                    ..style
+                },
+            ),
+            MarkdownElement::Spoiler(x) => match self.option.spoiler {
+                SpoilerStyle::Reverse => self.elements(
+                    x.content().get(),
+                    Style {
+                        reverse: true,
+                        ..style
+                    },
+                ),
+                SpoilerStyle::Hidden => {
+                    let outer = self.masked;
+                    self.masked = Some(outer.unwrap_or(style));
+                    self.elements(x.content().get(), style);
+                    self.masked = outer;
+                }
+            },
+            MarkdownElement::OneLineCode(x) => self.text(x.content(), Style { dim: true, ..style }),
+            MarkdownElement::MultiLineCode(x) => {
+                // The line breaks around the content are not displayed, as in Discord.
+                let content = x.content();
+                let content = content
+                    .strip_prefix("\r\n")
+                    .or_else(|| content.strip_prefix('\n'))
+                    .unwrap_or(content);
+                let content = content
+                    .strip_suffix("\r\n")
+                    .or_else(|| content.strip_suffix('\n'))
+                    .unwrap_or(content);
+
+                self.start_block();
+                self.text(content, Style { dim: true, ..style });
+                self.pending_break = true;
+            }
+            MarkdownElement::BlockQuote(x) => {
+                self.start_block();
+                self.quotes += 1;
+                self.elements(x.content().get(), style);
+                self.quotes -= 1;
+                self.pending_break = true;
+            }
+        }
+    }
+
+    /// Starts a new line unless the current line is empty.
+    fn start_block(&mut self) {
+        if !self.current().cells.is_empty() {
+            self.new_line();
+        }
+        self.pending_break = false;
+    }
+
+    fn text(&mut self, text: &str, style: Style) {
+        for c in text.chars() {
+            if std::mem::take(&mut self.pending_break) && c != '\n' {
+                self.new_line();
+            }
+
+            match c {
+                '\n' => self.new_line(),
+                '\r' => {}
+                c => {
+                    let quotes = self.quotes;
+                    let masked = self.masked;
+                    let line = self.current();
+                    if line.cells.is_empty() {
+                        line.quotes = quotes;
+                    }
+                    let cell = match masked {
+                        Some(mask) if !c.is_whitespace() => ('█', mask),
+                        Some(mask) => (visible(c), mask),
+                        None => (visible(c), style),
+                    };
+                    line.cells.push(cell);
+                }
+            }
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.lines.push(Line {
+            quotes: self.quotes,
+            cells: Vec::new(),
+        });
+    }
+
+    fn current(&mut self) -> &mut Line {
+        self.lines.last_mut().expect("there is always a line")
+    }
+
+    /// Wraps the lines and returns the styled text.
+    fn finish(self) -> String {
+        let mut output = Vec::new();
+
+        for line in &self.lines {
+            let prefix = QUOTE_PREFIX.repeat(line.quotes);
+            let rows = match self.option.width {
+                Some(width) => {
+                    let width = width.saturating_sub(prefix.width()).max(1);
+                    wrap(&line.cells, width)
+                }
+                None => vec![line.cells.as_slice()],
+            };
+
+            for row in rows {
+                let mut text = String::new();
+                if !prefix.is_empty() {
+                    text.push_str(&self.styled(
+                        &prefix,
+                        Style {
+                            dim: true,
+                            ..Style::default()
+                        },
+                    ));
+                }
+                text.push_str(&self.styled_cells(row));
+                output.push(text);
+            }
+        }
+
+        output.join("\n")
+    }
+
+    fn styled(&self, text: &str, style: Style) -> String {
+        let cells = text.chars().map(|c| (c, style)).collect::<Vec<_>>();
+        self.styled_cells(&cells)
+    }
+
+    /// Converts styled characters into text, resetting the style at the end.
+    fn styled_cells(&self, cells: &[(char, Style)]) -> String {
+        if !self.option.color {
+            return cells.iter().map(|(c, _)| c).collect();
+        }
+
+        let mut text = String::new();
+        let mut current = Style::default();
+        for &(c, style) in cells {
+            if style != current {
+                if current != Style::default() {
+                    text.push_str(RESET);
+                }
+                text.push_str(&style.sequence());
+                current = style;
+            }
+            text.push(c);
+        }
+        if current != Style::default() {
+            text.push_str(RESET);
+        }
+        text
+    }
+}
+
+/// Replaces a control character except tab with a visible symbol,
+/// so that text cannot inject its own escape sequences into the terminal.
+///
+/// C0 controls and DEL are replaced with their Control Pictures (like `␛` for ESC), and C1 controls with `�`.
+fn visible(c: char) -> char {
+    match c {
+        '\t' => c,
+        '\0'..='\x1f' => char::from_u32(0x2400 + c as u32).unwrap_or(char::REPLACEMENT_CHARACTER),
+        '\x7f' => '\u{2421}',
+        c if c.is_control() => char::REPLACEMENT_CHARACTER,
+        c => c,
+    }
+}
+
+/// Wraps styled characters at word boundaries into rows of at most `width` columns.
+///
+/// Whitespace at a wrap is removed, and words wider than `width` are broken.
+fn wrap(cells: &[(char, Style)], width: usize) -> Vec<&[(char, Style)]> {
+    let columns = |cells: &[(char, Style)]| -> usize {
+        cells.iter().map(|(c, _)| c.width().unwrap_or(0)).sum()
+    };
+
+    let mut rows = Vec::new();
+    // Range of the current row, excluding whitespace at its end, and the width of the row including the whitespace.
+    let mut row_start = 0;
+    let mut row_end = 0;
+    let mut row_width = 0;
+    let mut i = 0;
+
+    while i < cells.len() {
+        let is_space = cells[i].0.is_whitespace();
+        let token_end = cells[i..]
+            .iter()
+            .position(|(c, _)| c.is_whitespace() != is_space)
+            .map_or(cells.len(), |len| i + len);
+        let token_width = columns(&cells[i..token_end]);
+
+        if row_width + token_width <= width {
+            // The token fits in the current row.
+            row_width += token_width;
+            if !is_space {
+                row_end = token_end;
+            }
+        } else if is_space {
+            // Whitespace at the end of a row is removed.
+            rows.push(&cells[row_start..row_end]);
+            row_start = token_end;
+            row_end = token_end;
+            row_width = 0;
+        } else {
+            if row_end > row_start {
+                rows.push(&cells[row_start..row_end]);
+            }
+            // Break a word wider than the width, keeping at least one character in each row.
+            row_start = i;
+            row_width = 0;
+            for (j, (c, _)) in cells.iter().enumerate().take(token_end).skip(i) {
+                let char_width = c.width().unwrap_or(0);
+                if row_width + char_width > width && j > row_start {
+                    rows.push(&cells[row_start..j]);
+                    row_start = j;
+                    row_width = 0;
+                }
+                row_width += char_width;
+            }
+            row_end = token_end;
+        }
+
+        i = token_end;
+    }
+
+    if row_end > row_start || rows.is_empty() {
+        rows.push(&cells[row_start..row_end]);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::parse;
+
+    fn option_plain() -> AnsiOption {
+        AnsiOption::new().color(false)
+    }
+
+    #[test]
+    fn test_styles_to_ansi() {
+        let option = AnsiOption::new();
+
+        assert_eq!(italics_star("a").to_ansi(&option), "\x1b[3ma\x1b[0m");
+        assert_eq!(italics_underscore("a").to_ansi(&option), "\x1b[3ma\x1b[0m");
+        assert_eq!(bold("a").to_ansi(&option), "\x1b[1ma\x1b[0m");
+        assert_eq!(underline("a").to_ansi(&option), "\x1b[4ma\x1b[0m");
+        assert_eq!(strikethrough("a").to_ansi(&option), "\x1b[9ma\x1b[0m");
+        assert_eq!(spoiler("a").to_ansi(&option), "\x1b[7ma\x1b[0m");
+        assert_eq!(one_line_code("a").to_ansi(&option), "\x1b[2ma\x1b[0m");
+        assert_eq!(plain("a").to_ansi(&option), "a");
+    }
+
+    #[test]
+    fn test_nested_to_ansi() {
+        let ast = parse("__a **b**__ c");
+
+        assert_eq!(
+            ast.to_ansi(&AnsiOption::new()),
+            "\x1b[4ma \x1b[0m\x1b[1;4mb\x1b[0m c"
+        );
+        assert_eq!(ast.to_ansi(&option_plain()), "a b c");
+    }
+
+    #[test]
+    fn test_spoiler_to_ansi() {
+        let ast = parse("||secret **text**||");
+        let hidden = AnsiOption::new().spoiler(SpoilerStyle::Hidden);
+
+        assert_eq!(ast.to_ansi(&hidden), "██████ ████");
+        assert_eq!(parse("||`code` text||").to_ansi(&hidden), "████ ████");
+        assert_eq!(ast.to_ansi(&option_plain()), "secret text");
+        assert_eq!(
+            ast.to_ansi(&AnsiOption::new()),
+            "\x1b[7msecret \x1b[0m\x1b[1;7mtext\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_multi_line_code_to_ansi() {
+        let ast = parse("see ```rs\nfn main() {}\n```\nafter");
+
+        assert_eq!(
+            ast.to_ansi(&AnsiOption::new()),
+            "see \n\x1b[2mfn main() {}\x1b[0m\nafter"
+        );
+        assert_eq!(parse("```a``` b").to_ansi(&option_plain()), "a\n b");
+    }
+
+    #[test]
+    fn test_control_characters_to_ansi() {
+        let ast = MarkdownDocument::new(vec![
+            plain("a\x1b[2J\tb\x07"),
+            one_line_code("\x1b]0;title\x07"),
+            multi_line_code("\n\x1b[H\u{9b}\x7f\n", None),
+        ]);
+        let rendered = ast.to_ansi(&option_plain());
+
+        assert_eq!(rendered, "a␛[2J\tb␇␛]0;title␇\n␛[H�␡");
+        assert!(!ast.to_ansi(&AnsiOption::new()).contains("\x1b[2J"));
+
+        // Whitespace kept in a hidden spoiler is made visible as well.
+        let spoiler = MarkdownDocument::new(vec![spoiler("a\x0b\x0c\u{85} b")]);
+        assert_eq!(
+            spoiler.to_ansi(&option_plain().spoiler(SpoilerStyle::Hidden)),
+            "█␋␌� █"
+        );
+    }
+
+    #[test]
+    fn test_block_quote_to_ansi() {
+        let ast = MarkdownDocument::new(vec![
+            plain("said:"),
+            block_quote(vec![plain("line 1\n"), bold("line 2")]),
+            plain("reply"),
+        ]);
+
+        assert_eq!(
+            ast.to_ansi(&option_plain()),
+            "said:\n│ line 1\n│ line 2\nreply"
+        );
+        assert_eq!(
+            ast.to_ansi(&AnsiOption::new()),
+            "said:\n\x1b[2m│ \x1b[0mline 1\n\x1b[2m│ \x1b[0m\x1b[1mline 2\x1b[0m\nreply"
+        );
+    }
+
+    #[test]
+    fn test_wrap_to_ansi() {
+        let ast = parse("**bold text** wraps at word boundaries");
+        let option = AnsiOption::new().width(Some(10));
+
+        assert_eq!(
+            ast.to_ansi(&option.clone().color(false)),
+            "bold text\nwraps at\nword\nboundaries"
+        );
+        assert_eq!(
+            ast.to_ansi(&option),
+            "\x1b[1mbold text\x1b[0m\nwraps at\nword\nboundaries"
+        );
+    }
+
+    #[test]
+    fn test_wrap_block_quote_to_ansi() {
+        let ast = MarkdownDocument::new(vec![block_quote("aaa bbb ccc")]);
+
+        assert_eq!(
+            ast.to_ansi(&option_plain().width(Some(9))),
+            "│ aaa bbb\n│ ccc"
+        );
+    }
+
+    #[test]
+    fn test_wrap() {
+        let cells = |text: &str| {
+            text.chars()
+                .map(|c| (c, Style::default()))
+                .collect::<Vec<_>>()
+        };
+        let rows = |text: &str, width| {
+            wrap(&cells(text), width)
+                .iter()
+                .map(|row| row.iter().map(|(c, _)| c).collect::<String>())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(rows("", 5), vec![""]);
+        assert_eq!(rows("abc def", 7), vec!["abc def"]);
+        assert_eq!(rows("abc def", 5), vec!["abc", "def"]);
+        assert_eq!(rows("abc   def", 4), vec!["abc", "def"]);
+        assert_eq!(rows("  indented", 10), vec!["  indented"]);
+        assert_eq!(rows("abcdefgh ij", 3), vec!["abc", "def", "gh", "ij"]);
+        assert_eq!(rows("a b c d", 3), vec!["a b", "c d"]);
+        assert_eq!(rows("日本語 テキスト", 8), vec!["日本語", "テキスト"]);
+        assert_eq!(rows("日本語です", 5), vec!["日本", "語で", "す"]);
+        assert_eq!(rows("👍👍 ab", 5), vec!["👍👍", "ab"]);
+        assert_eq!(rows("日本", 1), vec!["日", "本"]);
+    }
+
+    #[test]
+    fn test_style_sequence() {
+        assert_eq!(Style::default().sequence(), "");
+        assert_eq!(
+            Style {
+                bold: true,
+                strikethrough: true,
+                ..Style::default()
+            }
+            .sequence(),
+            "\x1b[1;9m"
+        );
+    }
+}
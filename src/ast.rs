@@ -28,9 +28,53 @@
 //!
 //! assert_eq!(ast.to_string(), "**bold** text");
 //! ```
+//!
+//! # Walking through the AST
+//!
+//! [`Visitor`] and [`VisitorMut`] walk through every node of the AST, and [`Fold`] rebuilds a new AST from it.
+//! Override only the methods for the nodes you care about.
+//!
+//! # Serialization
+//!
+//! With the `serde` feature enabled, every type in this module implements `Serialize` and `Deserialize`.
+//! The representation is stable and versioned by [`SERDE_FORMAT_VERSION`].
+//!
+//! - [`MarkdownDocument`] is an object with `version` and `content`.
+//!   Deserializing a document with a newer `version` fails, so that older readers do not misinterpret new node types.
+//! - [`MarkdownElementCollection`] is an array of elements.
+//! - [`MarkdownElement`] is an object tagged with `type`, which is the name of its [`ElementKind`].
+//!   Plain text and codes have a string `content`, and other elements have an array `content`.
+//!   Multiline code blocks have an optional string `language`.
+//! - Unknown fields are ignored when deserializing.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "content": [
+//!     { "type": "bold", "content": [{ "type": "plain", "content": "bold" }] },
+//!     { "type": "multi_line_code", "content": "\ncode\n", "language": "rust" }
+//!   ]
+//! }
+//! ```
 
+use crate::delimiter::auto_close;
+use crate::diff::{diff, Change};
+use crate::error::SelectorError;
 use crate::generate::{ToMarkdownString, ToMarkdownStringOption};
+use crate::iter::Iter;
+use crate::length::{char_count, visible_len};
+use crate::normalize::normalize;
+use crate::select::Selector;
+use crate::semantic::Semantic;
+use crate::split::{split, SplitOption};
+use crate::truncate::{truncate, TruncateOption};
 use derive_more::{Display, From, Into, IntoIterator};
+use std::hash::{Hash, Hasher};
+
+/// Version of the serialized representation of [`MarkdownDocument`].
+///
+/// Incremented whenever the representation changes, e.g. when a new node type is added.
+pub const SERDE_FORMAT_VERSION: u32 = 1;
 
 /// A markdown document. The root of AST.
 ///
@@ -49,7 +93,7 @@ use derive_more::{Display, From, Into, IntoIterator};
 ///
 /// assert_eq!(ast.to_string(), "**bold text**");
 /// ```
-#[derive(Debug, Eq, PartialEq, Hash, Default, Display)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default, Display)]
 #[display(fmt = "{}", "self.to_markdown_string(&ToMarkdownStringOption::new())")]
 pub struct MarkdownDocument {
     content: MarkdownElementCollection,
@@ -67,11 +111,256 @@ impl MarkdownDocument {
     pub fn content(&self) -> &MarkdownElementCollection {
         &self.content
     }
+
+    /// Returns a mutable reference to the content of the markdown document.
+    pub fn content_mut(&mut self) -> &mut MarkdownElementCollection {
+        &mut self.content
+    }
+
+    /// Consumes the markdown document and returns its content.
+    pub fn into_content(self) -> MarkdownElementCollection {
+        self.content
+    }
+
+    /// Rewrites the AST into the normal form, which is the form [`parse`](crate::parse) returns.
+    ///
+    /// - Adjacent plain text is merged, and empty plain text is removed.
+    /// - Styled text and codes without content are removed.
+    /// - Styled text directly inside styled text of the same kind, like `Bold(Bold(x))`, is flattened.
+    ///   Nested block quotes are kept, since they are rendered differently.
+    /// - Nesting whose closing delimiter would be parsed early is rewritten into nesting rendered the same,
+    ///   e.g. `Bold(ItalicsStar(x))`, generated as `***x***`, becomes `Bold(ItalicsUnderscore(x))`.
+    ///
+    /// Normalization is idempotent, and the result of [`parse`](crate::parse) is always normalized.
+    /// A normalized document survives a round trip, that is, `parse(&doc.to_string())` returns the same document,
+    /// including documents built by hand, unless plain text or code contains markdown delimiters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use discord_md::ast::MarkdownDocument;
+    /// use discord_md::builder::*;
+    /// use discord_md::parse;
+    ///
+    /// let mut ast = MarkdownDocument::new(vec![
+    ///     plain("a"),
+    ///     plain("b"),
+    ///     bold(vec![]),
+    ///     spoiler(vec![spoiler("c")]),
+    /// ]);
+    /// ast.normalize();
+    ///
+    /// assert_eq!(ast, MarkdownDocument::new(vec![plain("ab"), spoiler("c")]));
+    /// assert_eq!(parse(&ast.to_string()), ast);
+    /// ```
+    pub fn normalize(&mut self) {
+        *self = normalize(std::mem::take(self));
+    }
+
+    /// Returns `true` if both documents are rendered identically in Discord.
+    ///
+    /// Unlike `==`, italics text wrapped in `*` and `_` are considered equal, and so are split and merged plain text.
+    /// See [`Semantic`] for the exact rules, and to use documents as keys of a hash map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use discord_md::parse;
+    ///
+    /// assert!(parse("*a* b").semantically_eq(&parse("_a_ b")));
+    /// assert!(!parse("*a* b").semantically_eq(&parse("**a** b")));
+    /// ```
+    pub fn semantically_eq(&self, other: &MarkdownDocument) -> bool {
+        Semantic::new(self.clone()) == Semantic::new(other.clone())
+    }
+
+    /// Feeds the document into the hasher, consistently with [`semantically_eq()`](MarkdownDocument::semantically_eq()).
+    ///
+    /// Semantically equal documents produce the same hash.
+    pub fn semantic_hash<H: Hasher>(&self, state: &mut H) {
+        Semantic::new(self.clone()).hash(state)
+    }
+
+    /// Returns a depth-first iterator over every element in document order, together with its ancestors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use discord_md::ast::MarkdownElement;
+    /// use discord_md::parse;
+    ///
+    /// let ast = parse("__*nested* styles__");
+    ///
+    /// assert_eq!(
+    ///     ast.iter().map(|node| node.depth()).collect::<Vec<_>>(),
+    ///     vec![0, 1, 2, 1]
+    /// );
+    /// ```
+    pub fn iter(&self) -> Iter<'_> {
+        Iter::new(self)
+    }
+
+    /// Returns every node of the given type in document order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use discord_md::ast::OneLineCode;
+    /// use discord_md::parse;
+    ///
+    /// let ast = parse("`a` and **`b`**");
+    ///
+    /// assert_eq!(
+    ///     ast.find_all::<OneLineCode>(),
+    ///     vec![&OneLineCode::new("a"), &OneLineCode::new("b")]
+    /// );
+    /// ```
+    pub fn find_all<T: FromElement>(&self) -> Vec<&T> {
+        self.iter()
+            .filter_map(|node| T::from_element(node.element()))
+            .collect()
+    }
+
+    /// Returns every element matching the selector in document order.
+    ///
+    /// See [`Selector`] for the syntax of selectors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use discord_md::builder::*;
+    /// use discord_md::parse;
+    ///
+    /// let ast = parse("||**a** __**b**__|| **c**");
+    ///
+    /// assert_eq!(ast.select("spoiler > bold").unwrap(), vec![&bold("a")]);
+    /// assert_eq!(ast.select("spoiler bold").unwrap(), vec![&bold("a"), &bold("b")]);
+    /// assert!(ast.select("sp0iler").is_err());
+    /// ```
+    pub fn select(&self, selector: &str) -> Result<Vec<&MarkdownElement>, SelectorError> {
+        let selector = selector.parse::<Selector>()?;
+        Ok(self
+            .iter()
+            .filter(|node| selector.matches(node))
+            .map(|node| node.element())
+            .collect())
+    }
+
+    /// Computes the changes from this document to `new` in document order.
+    ///
+    /// See [`diff`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use discord_md::builder::*;
+    /// use discord_md::diff::Change;
+    /// use discord_md::parse;
+    ///
+    /// let old = parse("**bold** text");
+    /// let new = parse("**bold**");
+    ///
+    /// assert_eq!(
+    ///     old.diff(&new),
+    ///     vec![Change::Deleted { path: vec![1], element: &plain(" text") }]
+    /// );
+    /// ```
+    pub fn diff<'a>(&'a self, new: &'a MarkdownDocument) -> Vec<Change<'a>> {
+        diff(self, new)
+    }
+
+    /// Returns the length of the generated markdown text, counted the way Discord does.
+    ///
+    /// This is the length checked against [`MESSAGE_LIMIT`](crate::length::MESSAGE_LIMIT) when sending a message.
+    /// See [`length`](crate::length) module for how characters are counted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use discord_md::parse;
+    ///
+    /// assert_eq!(parse("**bold** 👍").markdown_len(), 10);
+    /// ```
+    pub fn markdown_len(&self) -> usize {
+        char_count(&self.to_markdown_string(&ToMarkdownStringOption::new()))
+    }
+
+    /// Returns the length of the text displayed in Discord, without markdown delimiters.
+    ///
+    /// A custom emoji or a mention in plain text counts as one character,
+    /// while the content of code is counted as it is.
+    /// The language of a code block is not counted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use discord_md::parse;
+    ///
+    /// assert_eq!(parse("**bold** <@123>").visible_len(), 6);
+    /// ```
+    pub fn visible_len(&self) -> usize {
+        visible_len(self)
+    }
+
+    /// Splits the document into markdown texts that fit in a message, keeping the formatting of each chunk.
+    ///
+    /// See [`split`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use discord_md::parse;
+    /// use discord_md::split::SplitOption;
+    ///
+    /// let ast = parse("**bold text**");
+    ///
+    /// assert_eq!(ast.split(&SplitOption::new().limit(10)), vec!["**bold**", "**text**"]);
+    /// ```
+    pub fn split(&self, option: &SplitOption) -> Vec<String> {
+        split(self, option)
+    }
+
+    /// Returns a document truncated to at most `max_len` visible characters, with an ellipsis at the cut.
+    ///
+    /// See [`truncate`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use discord_md::parse;
+    /// use discord_md::truncate::TruncateOption;
+    ///
+    /// let ast = parse("**bold text** and more");
+    ///
+    /// assert_eq!(ast.truncate(6, &TruncateOption::new()).to_string(), "**bold …**");
+    /// ```
+    pub fn truncate(&self, max_len: usize, option: &TruncateOption) -> MarkdownDocument {
+        truncate(self, max_len, option)
+    }
+
+    /// Generates markdown text from the AST and closes delimiters left unterminated in plain text.
+    ///
+    /// See [`auto_close`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use discord_md::parse;
+    ///
+    /// let truncated = parse("**bold** and ||spoil");
+    ///
+    /// assert_eq!(truncated.auto_close(), "**bold** and ||spoil||");
+    /// ```
+    pub fn auto_close(&self) -> String {
+        auto_close(&self.to_markdown_string(&ToMarkdownStringOption::new()))
+    }
 }
 
 /// A collection of [`MarkdownElement`].
-#[derive(Debug, Eq, PartialEq, Hash, Default, From, Into, IntoIterator, Display)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default, From, Into, IntoIterator, Display)]
 #[display(fmt = "{}", "self.to_markdown_string(&ToMarkdownStringOption::new())")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct MarkdownElementCollection(Vec<MarkdownElement>);
 
 impl MarkdownElementCollection {
@@ -84,6 +373,16 @@ impl MarkdownElementCollection {
     pub fn get(&self) -> &Vec<MarkdownElement> {
         &self.0
     }
+
+    /// Returns a mutable reference to the collection of markdown element in [`Vec`].
+    pub fn get_mut(&mut self) -> &mut Vec<MarkdownElement> {
+        &mut self.0
+    }
+
+    /// Consumes the collection and returns the markdown elements in [`Vec`].
+    pub fn into_inner(self) -> Vec<MarkdownElement> {
+        self.0
+    }
 }
 
 impl From<MarkdownElement> for MarkdownElementCollection {
@@ -111,7 +410,9 @@ impl From<&String> for MarkdownElementCollection {
 }
 
 /// A markdown element.
-#[derive(Debug, Eq, PartialEq, Hash, Display)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
 pub enum MarkdownElement {
     /// Plain text.
     Plain(Box<Plain>),
@@ -144,13 +445,133 @@ pub enum MarkdownElement {
     BlockQuote(Box<BlockQuote>),
 }
 
+impl MarkdownElement {
+    /// Returns the kind of the element.
+    pub fn kind(&self) -> ElementKind {
+        match self {
+            MarkdownElement::Plain(_) => ElementKind::Plain,
+            MarkdownElement::ItalicsStar(_) => ElementKind::ItalicsStar,
+            MarkdownElement::ItalicsUnderscore(_) => ElementKind::ItalicsUnderscore,
+            MarkdownElement::Bold(_) => ElementKind::Bold,
+            MarkdownElement::Underline(_) => ElementKind::Underline,
+            MarkdownElement::Strikethrough(_) => ElementKind::Strikethrough,
+            MarkdownElement::Spoiler(_) => ElementKind::Spoiler,
+            MarkdownElement::OneLineCode(_) => ElementKind::OneLineCode,
+            MarkdownElement::MultiLineCode(_) => ElementKind::MultiLineCode,
+            MarkdownElement::BlockQuote(_) => ElementKind::BlockQuote,
+        }
+    }
+
+    /// Returns the content of the element if it can contain other elements, or `None` otherwise.
+    ///
+    /// Plain text and codes do not contain other elements.
+    pub fn children(&self) -> Option<&MarkdownElementCollection> {
+        match self {
+            MarkdownElement::Plain(_) => None,
+            MarkdownElement::ItalicsStar(x) => Some(x.content()),
+            MarkdownElement::ItalicsUnderscore(x) => Some(x.content()),
+            MarkdownElement::Bold(x) => Some(x.content()),
+            MarkdownElement::Underline(x) => Some(x.content()),
+            MarkdownElement::Strikethrough(x) => Some(x.content()),
+            MarkdownElement::Spoiler(x) => Some(x.content()),
+            MarkdownElement::OneLineCode(_) => None,
+            MarkdownElement::MultiLineCode(_) => None,
+            MarkdownElement::BlockQuote(x) => Some(x.content()),
+        }
+    }
+}
+
+/// The kind of [`MarkdownElement`], without its content.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ElementKind {
+    /// Plain text.
+    #[display(fmt = "plain")]
+    Plain,
+
+    /// Italics text, wrapped in `*`.
+    #[display(fmt = "italics_star")]
+    ItalicsStar,
+
+    /// Italics text, wrapped in `_`.
+    #[display(fmt = "italics_underscore")]
+    ItalicsUnderscore,
+
+    /// Bold text, wrapped in `**`.
+    #[display(fmt = "bold")]
+    Bold,
+
+    /// Underline text, wrapped in `__`.
+    #[display(fmt = "underline")]
+    Underline,
+
+    /// Strikethrough text, wrapped in `~~`.
+    #[display(fmt = "strikethrough")]
+    Strikethrough,
+
+    /// Spoiler text, wrapped in `||`.
+    #[display(fmt = "spoiler")]
+    Spoiler,
+
+    /// Inline code, wrapped in `` ` ``.
+    #[display(fmt = "one_line_code")]
+    OneLineCode,
+
+    /// Multiline code block, wrapped in ```` ``` ````.
+    #[display(fmt = "multi_line_code")]
+    MultiLineCode,
+
+    /// Block quote, preceded by `> `.
+    #[display(fmt = "block_quote")]
+    BlockQuote,
+}
+
+impl ElementKind {
+    /// Every kind of element, in the order of the variants of [`MarkdownElement`].
+    pub const ALL: [ElementKind; 10] = [
+        ElementKind::Plain,
+        ElementKind::ItalicsStar,
+        ElementKind::ItalicsUnderscore,
+        ElementKind::Bold,
+        ElementKind::Underline,
+        ElementKind::Strikethrough,
+        ElementKind::Spoiler,
+        ElementKind::OneLineCode,
+        ElementKind::MultiLineCode,
+        ElementKind::BlockQuote,
+    ];
+
+    /// Returns the name of the kind in snake case, such as `one_line_code`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ElementKind::Plain => "plain",
+            ElementKind::ItalicsStar => "italics_star",
+            ElementKind::ItalicsUnderscore => "italics_underscore",
+            ElementKind::Bold => "bold",
+            ElementKind::Underline => "underline",
+            ElementKind::Strikethrough => "strikethrough",
+            ElementKind::Spoiler => "spoiler",
+            ElementKind::OneLineCode => "one_line_code",
+            ElementKind::MultiLineCode => "multi_line_code",
+            ElementKind::BlockQuote => "block_quote",
+        }
+    }
+
+    /// Returns the kind with the given name in snake case, or `None` if there is no such kind.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
 /// Plain text.
 ///
 /// # Example markdown text
 ///
 /// `plain text` (plain text)
-#[derive(Debug, Eq, PartialEq, Hash, Display)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Display)]
 #[display(fmt = "{}", "self.to_markdown_string(&ToMarkdownStringOption::new())")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Plain {
     content: String,
 }
@@ -167,6 +588,16 @@ impl Plain {
     pub fn content(&self) -> &str {
         &self.content
     }
+
+    /// Returns a mutable reference to the content of plain text.
+    pub fn content_mut(&mut self) -> &mut String {
+        &mut self.content
+    }
+
+    /// Consumes plain text and returns its content.
+    pub fn into_content(self) -> String {
+        self.content
+    }
 }
 
 /// Italics text, wrapped in `*`.
@@ -174,8 +605,9 @@ impl Plain {
 /// # Example markdown text
 ///
 /// `*italics text*` (*italics text*)
-#[derive(Debug, Eq, PartialEq, Hash, Display)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Display)]
 #[display(fmt = "{}", "self.to_markdown_string(&ToMarkdownStringOption::new())")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ItalicsStar {
     content: MarkdownElementCollection,
 }
@@ -192,6 +624,16 @@ impl ItalicsStar {
     pub fn content(&self) -> &MarkdownElementCollection {
         &self.content
     }
+
+    /// Returns a mutable reference to the content of italics text.
+    pub fn content_mut(&mut self) -> &mut MarkdownElementCollection {
+        &mut self.content
+    }
+
+    /// Consumes italics text and returns its content.
+    pub fn into_content(self) -> MarkdownElementCollection {
+        self.content
+    }
 }
 
 /// Italics text, wrapped in `_`.
@@ -199,8 +641,9 @@ impl ItalicsStar {
 /// # Example markdown text
 ///
 /// `_italics text_` (_italics text_)
-#[derive(Debug, Eq, PartialEq, Hash, Display)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Display)]
 #[display(fmt = "{}", "self.to_markdown_string(&ToMarkdownStringOption::new())")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ItalicsUnderscore {
     content: MarkdownElementCollection,
 }
@@ -217,6 +660,16 @@ impl ItalicsUnderscore {
     pub fn content(&self) -> &MarkdownElementCollection {
         &self.content
     }
+
+    /// Returns a mutable reference to the content of italics text.
+    pub fn content_mut(&mut self) -> &mut MarkdownElementCollection {
+        &mut self.content
+    }
+
+    /// Consumes italics text and returns its content.
+    pub fn into_content(self) -> MarkdownElementCollection {
+        self.content
+    }
 }
 
 /// Bold text, wrapped in `**`.
@@ -224,8 +677,9 @@ impl ItalicsUnderscore {
 /// # Example markdown text
 ///
 /// `**bold text**` (**bold text**)
-#[derive(Debug, Eq, PartialEq, Hash, Display)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Display)]
 #[display(fmt = "{}", "self.to_markdown_string(&ToMarkdownStringOption::new())")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bold {
     content: MarkdownElementCollection,
 }
@@ -242,6 +696,16 @@ impl Bold {
     pub fn content(&self) -> &MarkdownElementCollection {
         &self.content
     }
+
+    /// Returns a mutable reference to the content of bold text.
+    pub fn content_mut(&mut self) -> &mut MarkdownElementCollection {
+        &mut self.content
+    }
+
+    /// Consumes bold text and returns its content.
+    pub fn into_content(self) -> MarkdownElementCollection {
+        self.content
+    }
 }
 
 /// Underline text, wrapped in `__`.
@@ -249,8 +713,9 @@ impl Bold {
 /// # Example markdown text
 ///
 /// `__underline text__`
-#[derive(Debug, Eq, PartialEq, Hash, Display)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Display)]
 #[display(fmt = "{}", "self.to_markdown_string(&ToMarkdownStringOption::new())")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Underline {
     content: MarkdownElementCollection,
 }
@@ -267,6 +732,16 @@ impl Underline {
     pub fn content(&self) -> &MarkdownElementCollection {
         &self.content
     }
+
+    /// Returns a mutable reference to the content of underline text.
+    pub fn content_mut(&mut self) -> &mut MarkdownElementCollection {
+        &mut self.content
+    }
+
+    /// Consumes underline text and returns its content.
+    pub fn into_content(self) -> MarkdownElementCollection {
+        self.content
+    }
 }
 
 /// Strikethrough text, wrapped in `~~`.
@@ -274,8 +749,9 @@ impl Underline {
 /// # Example markdown text
 ///
 /// `~~strikethrough text~~` (~~strikethrough text~~)
-#[derive(Debug, Eq, PartialEq, Hash, Display)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Display)]
 #[display(fmt = "{}", "self.to_markdown_string(&ToMarkdownStringOption::new())")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Strikethrough {
     content: MarkdownElementCollection,
 }
@@ -292,6 +768,16 @@ impl Strikethrough {
     pub fn content(&self) -> &MarkdownElementCollection {
         &self.content
     }
+
+    /// Returns a mutable reference to the content of strikethrough text.
+    pub fn content_mut(&mut self) -> &mut MarkdownElementCollection {
+        &mut self.content
+    }
+
+    /// Consumes strikethrough text and returns its content.
+    pub fn into_content(self) -> MarkdownElementCollection {
+        self.content
+    }
 }
 
 /// Spoiler text, wrapped in `||`.
@@ -299,8 +785,9 @@ impl Strikethrough {
 /// # Example markdown text
 ///
 /// `||spoiler text||`
-#[derive(Debug, Eq, PartialEq, Hash, Display)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Display)]
 #[display(fmt = "{}", "self.to_markdown_string(&ToMarkdownStringOption::new())")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Spoiler {
     content: MarkdownElementCollection,
 }
@@ -317,6 +804,16 @@ impl Spoiler {
     pub fn content(&self) -> &MarkdownElementCollection {
         &self.content
     }
+
+    /// Returns a mutable reference to the content of spoiler text.
+    pub fn content_mut(&mut self) -> &mut MarkdownElementCollection {
+        &mut self.content
+    }
+
+    /// Consumes spoiler text and returns its content.
+    pub fn into_content(self) -> MarkdownElementCollection {
+        self.content
+    }
 }
 
 /// Inline code, wrapped in `` ` ``.
@@ -324,8 +821,9 @@ impl Spoiler {
 /// # Example markdown text
 ///
 /// `` `let foo = "bar";` `` (`let foo = "bar";`)
-#[derive(Debug, Eq, PartialEq, Hash, Display)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Display)]
 #[display(fmt = "{}", "self.to_markdown_string(&ToMarkdownStringOption::new())")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OneLineCode {
     content: String,
 }
@@ -338,10 +836,20 @@ impl OneLineCode {
         }
     }
 
-    /// Returns the content of the code block.
+    /// Returns the content of the inline code.
     pub fn content(&self) -> &str {
         &self.content
     }
+
+    /// Returns a mutable reference to the content of the inline code.
+    pub fn content_mut(&mut self) -> &mut String {
+        &mut self.content
+    }
+
+    /// Consumes the inline code and returns its content.
+    pub fn into_content(self) -> String {
+        self.content
+    }
 }
 
 /// Multiline code block, wrapped in ```` ``` ````.
@@ -355,10 +863,15 @@ impl OneLineCode {
 /// </p>
 /// ```
 /// ````
-#[derive(Debug, Eq, PartialEq, Hash, Display)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Display)]
 #[display(fmt = "{}", "self.to_markdown_string(&ToMarkdownStringOption::new())")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MultiLineCode {
     content: String,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     language: Option<String>,
 }
 
@@ -379,10 +892,46 @@ impl MultiLineCode {
         &self.content
     }
 
+    /// Returns a mutable reference to the content of the code block.
+    pub fn content_mut(&mut self) -> &mut String {
+        &mut self.content
+    }
+
+    /// Consumes the code block and returns its content, dropping the language.
+    ///
+    /// Use [`into_inner()`](Self::into_inner) to keep the language as well.
+    pub fn into_content(self) -> String {
+        self.content
+    }
+
     /// Returns the language of the code block.
     pub fn language(&self) -> Option<&str> {
         self.language.as_deref()
     }
+
+    /// Sets the language of the code block.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use discord_md::ast::MultiLineCode;
+    ///
+    /// let mut code = MultiLineCode::new("\nfn main() {}\n", None);
+    /// code.set_language(Some("rust".to_string()));
+    ///
+    /// assert_eq!(code.to_string(), "```rust\nfn main() {}\n```");
+    /// ```
+    pub fn set_language(&mut self, language: Option<String>) {
+        self.language = language;
+    }
+
+    /// Consumes the code block and returns its content and language.
+    ///
+    /// Unlike [`into_content()`](Self::into_content), which returns only the content,
+    /// this keeps every field of the code block.
+    pub fn into_inner(self) -> (String, Option<String>) {
+        (self.content, self.language)
+    }
 }
 
 /// Block quote, preceded by `> `.
@@ -393,8 +942,9 @@ impl MultiLineCode {
 /// > this is
 /// > block quote
 /// ```
-#[derive(Debug, Eq, PartialEq, Hash, Display)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Display)]
 #[display(fmt = "{}", "self.to_markdown_string(&ToMarkdownStringOption::new())")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockQuote {
     content: MarkdownElementCollection,
 }
@@ -411,6 +961,114 @@ impl BlockQuote {
     pub fn content(&self) -> &MarkdownElementCollection {
         &self.content
     }
+
+    /// Returns a mutable reference to the content of the block quote text.
+    pub fn content_mut(&mut self) -> &mut MarkdownElementCollection {
+        &mut self.content
+    }
+
+    /// Consumes the block quote text and returns its content.
+    pub fn into_content(self) -> MarkdownElementCollection {
+        self.content
+    }
+}
+
+/// A node type that can be borrowed from a [`MarkdownElement`] of the same kind.
+///
+/// Used by [`MarkdownDocument::find_all()`] to find nodes of a specific type.
+pub trait FromElement {
+    /// Returns the node if the element is of this type, or `None` otherwise.
+    fn from_element(element: &MarkdownElement) -> Option<&Self>;
+}
+
+impl FromElement for Plain {
+    fn from_element(element: &MarkdownElement) -> Option<&Self> {
+        match element {
+            MarkdownElement::Plain(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl FromElement for ItalicsStar {
+    fn from_element(element: &MarkdownElement) -> Option<&Self> {
+        match element {
+            MarkdownElement::ItalicsStar(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl FromElement for ItalicsUnderscore {
+    fn from_element(element: &MarkdownElement) -> Option<&Self> {
+        match element {
+            MarkdownElement::ItalicsUnderscore(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl FromElement for Bold {
+    fn from_element(element: &MarkdownElement) -> Option<&Self> {
+        match element {
+            MarkdownElement::Bold(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl FromElement for Underline {
+    fn from_element(element: &MarkdownElement) -> Option<&Self> {
+        match element {
+            MarkdownElement::Underline(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl FromElement for Strikethrough {
+    fn from_element(element: &MarkdownElement) -> Option<&Self> {
+        match element {
+            MarkdownElement::Strikethrough(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl FromElement for Spoiler {
+    fn from_element(element: &MarkdownElement) -> Option<&Self> {
+        match element {
+            MarkdownElement::Spoiler(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl FromElement for OneLineCode {
+    fn from_element(element: &MarkdownElement) -> Option<&Self> {
+        match element {
+            MarkdownElement::OneLineCode(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl FromElement for MultiLineCode {
+    fn from_element(element: &MarkdownElement) -> Option<&Self> {
+        match element {
+            MarkdownElement::MultiLineCode(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl FromElement for BlockQuote {
+    fn from_element(element: &MarkdownElement) -> Option<&Self> {
+        match element {
+            MarkdownElement::BlockQuote(x) => Some(x),
+            _ => None,
+        }
+    }
 }
 
 impl From<Plain> for MarkdownElement {
@@ -473,141 +1131,956 @@ impl From<BlockQuote> for MarkdownElement {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    fn example_text() -> MarkdownElementCollection {
-        MarkdownElementCollection::new(vec![MarkdownElement::Plain(Box::new(Plain::new("text")))])
+/// A visitor that walks through an AST.
+///
+/// Every method has a default implementation that walks into the children of the node,
+/// so implementors only need to override the methods for the nodes they care about.
+/// An overriding method can call the corresponding `walk_*` function to keep walking into the children.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::ast::*;
+/// use discord_md::parse;
+///
+/// #[derive(Default)]
+/// struct SpoilerCounter(usize);
+///
+/// impl Visitor for SpoilerCounter {
+///     fn visit_spoiler(&mut self, node: &Spoiler) {
+///         self.0 += 1;
+///         walk_spoiler(self, node);
+///     }
+/// }
+///
+/// let mut counter = SpoilerCounter::default();
+/// counter.visit_document(&parse("||a|| **||b||** `||c||`"));
+///
+/// assert_eq!(counter.0, 2);
+/// ```
+pub trait Visitor {
+    /// Visits a markdown document.
+    fn visit_document(&mut self, node: &MarkdownDocument) {
+        walk_document(self, node);
     }
 
-    #[test]
-    fn test_document_content() {
-        assert_eq!(
-            MarkdownDocument::new(example_text()).content(),
-            &example_text()
-        );
+    /// Visits a collection of markdown element.
+    fn visit_element_collection(&mut self, node: &MarkdownElementCollection) {
+        walk_element_collection(self, node);
     }
 
-    #[test]
-    fn test_element_collection_get() {
-        let test_case = || vec![MarkdownElement::Plain(Box::new(Plain::new("plain")))];
-        assert_eq!(
-            MarkdownElementCollection::new(test_case()).get(),
-            &test_case()
-        );
+    /// Visits a markdown element.
+    fn visit_element(&mut self, node: &MarkdownElement) {
+        walk_element(self, node);
     }
 
-    #[test]
-    fn test_element_collection_from_element() {
-        assert_eq!(
-            MarkdownElementCollection::from(MarkdownElement::Plain(Box::new(Plain::new(
-                "plain text"
-            )))),
-            MarkdownElementCollection::new(vec![MarkdownElement::Plain(Box::new(Plain::new(
-                "plain text"
-            )))]),
-        );
-    }
+    /// Visits plain text.
+    fn visit_plain(&mut self, _node: &Plain) {}
 
-    #[test]
-    fn test_element_collection_from_str() {
-        assert_eq!(
-            MarkdownElementCollection::from("plain text"),
-            MarkdownElementCollection::new(vec![MarkdownElement::Plain(Box::new(Plain::new(
-                "plain text"
-            )))]),
-        );
+    /// Visits italics text wrapped in `*`.
+    fn visit_italics_star(&mut self, node: &ItalicsStar) {
+        walk_italics_star(self, node);
     }
 
-    #[test]
-    fn test_element_collection_from_string() {
-        let test_case = "plain text".to_string();
-        assert_eq!(
-            MarkdownElementCollection::from(&test_case),
-            MarkdownElementCollection::new(vec![MarkdownElement::Plain(Box::new(Plain::new(
-                "plain text"
-            )))]),
-        );
-        assert_eq!(
-            MarkdownElementCollection::from(test_case),
-            MarkdownElementCollection::new(vec![MarkdownElement::Plain(Box::new(Plain::new(
-                "plain text"
-            )))]),
-        );
+    /// Visits italics text wrapped in `_`.
+    fn visit_italics_underscore(&mut self, node: &ItalicsUnderscore) {
+        walk_italics_underscore(self, node);
     }
 
-    #[test]
-    fn test_plain_content() {
-        assert_eq!(Plain::new("plain text").content(), "plain text");
+    /// Visits bold text.
+    fn visit_bold(&mut self, node: &Bold) {
+        walk_bold(self, node);
     }
 
-    #[test]
-    fn test_italics_star_content() {
-        assert_eq!(ItalicsStar::new(example_text()).content(), &example_text());
+    /// Visits underline text.
+    fn visit_underline(&mut self, node: &Underline) {
+        walk_underline(self, node);
     }
 
-    #[test]
-    fn test_italics_underscore_content() {
-        assert_eq!(
-            ItalicsUnderscore::new(example_text()).content(),
-            &example_text()
-        );
+    /// Visits strikethrough text.
+    fn visit_strikethrough(&mut self, node: &Strikethrough) {
+        walk_strikethrough(self, node);
     }
 
-    #[test]
-    fn test_bold_content() {
-        assert_eq!(Bold::new(example_text()).content(), &example_text());
+    /// Visits spoiler text.
+    fn visit_spoiler(&mut self, node: &Spoiler) {
+        walk_spoiler(self, node);
     }
 
-    #[test]
-    fn test_underline_content() {
-        assert_eq!(Underline::new(example_text()).content(), &example_text());
-    }
+    /// Visits an inline code.
+    fn visit_one_line_code(&mut self, _node: &OneLineCode) {}
 
-    #[test]
-    fn test_strikethrough_content() {
-        assert_eq!(
-            Strikethrough::new(example_text()).content(),
-            &example_text()
-        );
-    }
+    /// Visits a multiline code block.
+    fn visit_multi_line_code(&mut self, _node: &MultiLineCode) {}
 
-    #[test]
-    fn test_spoiler_content() {
-        assert_eq!(Spoiler::new(example_text()).content(), &example_text());
+    /// Visits a block quote.
+    fn visit_block_quote(&mut self, node: &BlockQuote) {
+        walk_block_quote(self, node);
     }
+}
 
-    #[test]
-    fn test_one_line_code_content() {
-        assert_eq!(OneLineCode::new("one line code").content(), "one line code");
-    }
+/// Walks into the content of a markdown document.
+pub fn walk_document<V: Visitor + ?Sized>(visitor: &mut V, node: &MarkdownDocument) {
+    visitor.visit_element_collection(&node.content);
+}
 
-    #[test]
-    fn test_multi_line_code_content() {
-        assert_eq!(
-            MultiLineCode::new("multi\nline\ncode\n", None).content(),
-            "multi\nline\ncode\n"
-        );
+/// Walks into each element of a collection.
+pub fn walk_element_collection<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    node: &MarkdownElementCollection,
+) {
+    for element in &node.0 {
+        visitor.visit_element(element);
     }
+}
 
-    #[test]
-    fn test_multi_line_code_language() {
-        assert_eq!(
-            MultiLineCode::new("multi\nline\ncode\n", Some("js".to_string())).language(),
-            Some("js")
-        );
-        assert_eq!(
-            MultiLineCode::new("multi\nline\ncode\n", None).language(),
-            None
-        );
+/// Calls the visitor method for the type of the element.
+pub fn walk_element<V: Visitor + ?Sized>(visitor: &mut V, node: &MarkdownElement) {
+    match node {
+        MarkdownElement::Plain(x) => visitor.visit_plain(x),
+        MarkdownElement::ItalicsStar(x) => visitor.visit_italics_star(x),
+        MarkdownElement::ItalicsUnderscore(x) => visitor.visit_italics_underscore(x),
+        MarkdownElement::Bold(x) => visitor.visit_bold(x),
+        MarkdownElement::Underline(x) => visitor.visit_underline(x),
+        MarkdownElement::Strikethrough(x) => visitor.visit_strikethrough(x),
+        MarkdownElement::Spoiler(x) => visitor.visit_spoiler(x),
+        MarkdownElement::OneLineCode(x) => visitor.visit_one_line_code(x),
+        MarkdownElement::MultiLineCode(x) => visitor.visit_multi_line_code(x),
+        MarkdownElement::BlockQuote(x) => visitor.visit_block_quote(x),
     }
+}
 
-    #[test]
-    fn test_block_quote_content() {
-        let test_case = || {
-            MarkdownElementCollection::new(vec![MarkdownElement::Plain(Box::new(Plain::new(
-                "block quote\ntext",
+/// Walks into the content of italics text wrapped in `*`.
+pub fn walk_italics_star<V: Visitor + ?Sized>(visitor: &mut V, node: &ItalicsStar) {
+    visitor.visit_element_collection(&node.content);
+}
+
+/// Walks into the content of italics text wrapped in `_`.
+pub fn walk_italics_underscore<V: Visitor + ?Sized>(visitor: &mut V, node: &ItalicsUnderscore) {
+    visitor.visit_element_collection(&node.content);
+}
+
+/// Walks into the content of bold text.
+pub fn walk_bold<V: Visitor + ?Sized>(visitor: &mut V, node: &Bold) {
+    visitor.visit_element_collection(&node.content);
+}
+
+/// Walks into the content of underline text.
+pub fn walk_underline<V: Visitor + ?Sized>(visitor: &mut V, node: &Underline) {
+    visitor.visit_element_collection(&node.content);
+}
+
+/// Walks into the content of strikethrough text.
+pub fn walk_strikethrough<V: Visitor + ?Sized>(visitor: &mut V, node: &Strikethrough) {
+    visitor.visit_element_collection(&node.content);
+}
+
+/// Walks into the content of spoiler text.
+pub fn walk_spoiler<V: Visitor + ?Sized>(visitor: &mut V, node: &Spoiler) {
+    visitor.visit_element_collection(&node.content);
+}
+
+/// Walks into the content of a block quote.
+pub fn walk_block_quote<V: Visitor + ?Sized>(visitor: &mut V, node: &BlockQuote) {
+    visitor.visit_element_collection(&node.content);
+}
+
+/// A visitor that walks through an AST mutably.
+///
+/// Every method has a default implementation that walks into the children of the node,
+/// so implementors only need to override the methods for the nodes they care about.
+/// An overriding method can call the corresponding `walk_*_mut` function to keep walking into the children.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::ast::*;
+/// use discord_md::builder::*;
+/// use discord_md::parse;
+///
+/// struct Shout;
+///
+/// impl VisitorMut for Shout {
+///     fn visit_plain_mut(&mut self, node: &mut Plain) {
+///         *node = Plain::new(node.content().to_uppercase());
+///     }
+/// }
+///
+/// let mut ast = parse("hello **world**");
+/// Shout.visit_document_mut(&mut ast);
+///
+/// assert_eq!(ast, MarkdownDocument::new(vec![plain("HELLO "), bold("WORLD")]));
+/// ```
+pub trait VisitorMut {
+    /// Visits a markdown document.
+    fn visit_document_mut(&mut self, node: &mut MarkdownDocument) {
+        walk_document_mut(self, node);
+    }
+
+    /// Visits a collection of markdown element.
+    fn visit_element_collection_mut(&mut self, node: &mut MarkdownElementCollection) {
+        walk_element_collection_mut(self, node);
+    }
+
+    /// Visits a markdown element.
+    fn visit_element_mut(&mut self, node: &mut MarkdownElement) {
+        walk_element_mut(self, node);
+    }
+
+    /// Visits plain text.
+    fn visit_plain_mut(&mut self, _node: &mut Plain) {}
+
+    /// Visits italics text wrapped in `*`.
+    fn visit_italics_star_mut(&mut self, node: &mut ItalicsStar) {
+        walk_italics_star_mut(self, node);
+    }
+
+    /// Visits italics text wrapped in `_`.
+    fn visit_italics_underscore_mut(&mut self, node: &mut ItalicsUnderscore) {
+        walk_italics_underscore_mut(self, node);
+    }
+
+    /// Visits bold text.
+    fn visit_bold_mut(&mut self, node: &mut Bold) {
+        walk_bold_mut(self, node);
+    }
+
+    /// Visits underline text.
+    fn visit_underline_mut(&mut self, node: &mut Underline) {
+        walk_underline_mut(self, node);
+    }
+
+    /// Visits strikethrough text.
+    fn visit_strikethrough_mut(&mut self, node: &mut Strikethrough) {
+        walk_strikethrough_mut(self, node);
+    }
+
+    /// Visits spoiler text.
+    fn visit_spoiler_mut(&mut self, node: &mut Spoiler) {
+        walk_spoiler_mut(self, node);
+    }
+
+    /// Visits an inline code.
+    fn visit_one_line_code_mut(&mut self, _node: &mut OneLineCode) {}
+
+    /// Visits a multiline code block.
+    fn visit_multi_line_code_mut(&mut self, _node: &mut MultiLineCode) {}
+
+    /// Visits a block quote.
+    fn visit_block_quote_mut(&mut self, node: &mut BlockQuote) {
+        walk_block_quote_mut(self, node);
+    }
+}
+
+/// Walks into the content of a markdown document.
+pub fn walk_document_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut MarkdownDocument) {
+    visitor.visit_element_collection_mut(&mut node.content);
+}
+
+/// Walks into each element of a collection.
+pub fn walk_element_collection_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    node: &mut MarkdownElementCollection,
+) {
+    for element in &mut node.0 {
+        visitor.visit_element_mut(element);
+    }
+}
+
+/// Calls the visitor method for the type of the element.
+pub fn walk_element_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut MarkdownElement) {
+    match node {
+        MarkdownElement::Plain(x) => visitor.visit_plain_mut(x),
+        MarkdownElement::ItalicsStar(x) => visitor.visit_italics_star_mut(x),
+        MarkdownElement::ItalicsUnderscore(x) => visitor.visit_italics_underscore_mut(x),
+        MarkdownElement::Bold(x) => visitor.visit_bold_mut(x),
+        MarkdownElement::Underline(x) => visitor.visit_underline_mut(x),
+        MarkdownElement::Strikethrough(x) => visitor.visit_strikethrough_mut(x),
+        MarkdownElement::Spoiler(x) => visitor.visit_spoiler_mut(x),
+        MarkdownElement::OneLineCode(x) => visitor.visit_one_line_code_mut(x),
+        MarkdownElement::MultiLineCode(x) => visitor.visit_multi_line_code_mut(x),
+        MarkdownElement::BlockQuote(x) => visitor.visit_block_quote_mut(x),
+    }
+}
+
+/// Walks into the content of italics text wrapped in `*`.
+pub fn walk_italics_star_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut ItalicsStar) {
+    visitor.visit_element_collection_mut(&mut node.content);
+}
+
+/// Walks into the content of italics text wrapped in `_`.
+pub fn walk_italics_underscore_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    node: &mut ItalicsUnderscore,
+) {
+    visitor.visit_element_collection_mut(&mut node.content);
+}
+
+/// Walks into the content of bold text.
+pub fn walk_bold_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut Bold) {
+    visitor.visit_element_collection_mut(&mut node.content);
+}
+
+/// Walks into the content of underline text.
+pub fn walk_underline_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut Underline) {
+    visitor.visit_element_collection_mut(&mut node.content);
+}
+
+/// Walks into the content of strikethrough text.
+pub fn walk_strikethrough_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut Strikethrough) {
+    visitor.visit_element_collection_mut(&mut node.content);
+}
+
+/// Walks into the content of spoiler text.
+pub fn walk_spoiler_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut Spoiler) {
+    visitor.visit_element_collection_mut(&mut node.content);
+}
+
+/// Walks into the content of a block quote.
+pub fn walk_block_quote_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut BlockQuote) {
+    visitor.visit_element_collection_mut(&mut node.content);
+}
+
+/// A folder that consumes an AST and rebuilds a new one.
+///
+/// Every method has a default implementation that rebuilds the node with its folded children,
+/// so implementors only need to override the methods for the nodes they care about.
+/// The methods for elements return `None` to remove the element from the AST.
+/// An overriding method can call the corresponding `fold_*` function to keep folding into the children.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::ast::*;
+/// use discord_md::builder::*;
+/// use discord_md::parse;
+///
+/// struct Rewriter;
+///
+/// impl Fold for Rewriter {
+///     // Turn every underline into bold
+///     fn fold_underline(&mut self, node: Underline) -> Option<MarkdownElement> {
+///         let content = self.fold_element_collection(node.into_content());
+///         Some(Bold::new(content).into())
+///     }
+///
+///     // Drop every spoiler
+///     fn fold_spoiler(&mut self, _node: Spoiler) -> Option<MarkdownElement> {
+///         None
+///     }
+/// }
+///
+/// let ast = Rewriter.fold_document(parse("__hello__ ||secret|| world"));
+///
+/// assert_eq!(ast, MarkdownDocument::new(vec![bold("hello"), plain(" "), plain(" world")]));
+/// ```
+pub trait Fold {
+    /// Folds a markdown document.
+    fn fold_document(&mut self, node: MarkdownDocument) -> MarkdownDocument {
+        fold_document(self, node)
+    }
+
+    /// Folds a collection of markdown element.
+    fn fold_element_collection(
+        &mut self,
+        node: MarkdownElementCollection,
+    ) -> MarkdownElementCollection {
+        fold_element_collection(self, node)
+    }
+
+    /// Folds a markdown element.
+    fn fold_element(&mut self, node: MarkdownElement) -> Option<MarkdownElement> {
+        fold_element(self, node)
+    }
+
+    /// Folds plain text.
+    fn fold_plain(&mut self, node: Plain) -> Option<MarkdownElement> {
+        Some(node.into())
+    }
+
+    /// Folds italics text wrapped in `*`.
+    fn fold_italics_star(&mut self, node: ItalicsStar) -> Option<MarkdownElement> {
+        fold_italics_star(self, node)
+    }
+
+    /// Folds italics text wrapped in `_`.
+    fn fold_italics_underscore(&mut self, node: ItalicsUnderscore) -> Option<MarkdownElement> {
+        fold_italics_underscore(self, node)
+    }
+
+    /// Folds bold text.
+    fn fold_bold(&mut self, node: Bold) -> Option<MarkdownElement> {
+        fold_bold(self, node)
+    }
+
+    /// Folds underline text.
+    fn fold_underline(&mut self, node: Underline) -> Option<MarkdownElement> {
+        fold_underline(self, node)
+    }
+
+    /// Folds strikethrough text.
+    fn fold_strikethrough(&mut self, node: Strikethrough) -> Option<MarkdownElement> {
+        fold_strikethrough(self, node)
+    }
+
+    /// Folds spoiler text.
+    fn fold_spoiler(&mut self, node: Spoiler) -> Option<MarkdownElement> {
+        fold_spoiler(self, node)
+    }
+
+    /// Folds an inline code.
+    fn fold_one_line_code(&mut self, node: OneLineCode) -> Option<MarkdownElement> {
+        Some(node.into())
+    }
+
+    /// Folds a multiline code block.
+    fn fold_multi_line_code(&mut self, node: MultiLineCode) -> Option<MarkdownElement> {
+        Some(node.into())
+    }
+
+    /// Folds a block quote.
+    fn fold_block_quote(&mut self, node: BlockQuote) -> Option<MarkdownElement> {
+        fold_block_quote(self, node)
+    }
+}
+
+/// Folds the content of a markdown document.
+pub fn fold_document<F: Fold + ?Sized>(folder: &mut F, node: MarkdownDocument) -> MarkdownDocument {
+    MarkdownDocument::new(folder.fold_element_collection(node.content))
+}
+
+/// Folds each element of a collection, removing the elements folded into `None`.
+pub fn fold_element_collection<F: Fold + ?Sized>(
+    folder: &mut F,
+    node: MarkdownElementCollection,
+) -> MarkdownElementCollection {
+    node.0
+        .into_iter()
+        .filter_map(|element| folder.fold_element(element))
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// Calls the folder method for the type of the element.
+pub fn fold_element<F: Fold + ?Sized>(
+    folder: &mut F,
+    node: MarkdownElement,
+) -> Option<MarkdownElement> {
+    match node {
+        MarkdownElement::Plain(x) => folder.fold_plain(*x),
+        MarkdownElement::ItalicsStar(x) => folder.fold_italics_star(*x),
+        MarkdownElement::ItalicsUnderscore(x) => folder.fold_italics_underscore(*x),
+        MarkdownElement::Bold(x) => folder.fold_bold(*x),
+        MarkdownElement::Underline(x) => folder.fold_underline(*x),
+        MarkdownElement::Strikethrough(x) => folder.fold_strikethrough(*x),
+        MarkdownElement::Spoiler(x) => folder.fold_spoiler(*x),
+        MarkdownElement::OneLineCode(x) => folder.fold_one_line_code(*x),
+        MarkdownElement::MultiLineCode(x) => folder.fold_multi_line_code(*x),
+        MarkdownElement::BlockQuote(x) => folder.fold_block_quote(*x),
+    }
+}
+
+/// Rebuilds italics text wrapped in `*` with its folded content.
+pub fn fold_italics_star<F: Fold + ?Sized>(
+    folder: &mut F,
+    node: ItalicsStar,
+) -> Option<MarkdownElement> {
+    Some(ItalicsStar::new(folder.fold_element_collection(node.content)).into())
+}
+
+/// Rebuilds italics text wrapped in `_` with its folded content.
+pub fn fold_italics_underscore<F: Fold + ?Sized>(
+    folder: &mut F,
+    node: ItalicsUnderscore,
+) -> Option<MarkdownElement> {
+    Some(ItalicsUnderscore::new(folder.fold_element_collection(node.content)).into())
+}
+
+/// Rebuilds bold text with its folded content.
+pub fn fold_bold<F: Fold + ?Sized>(folder: &mut F, node: Bold) -> Option<MarkdownElement> {
+    Some(Bold::new(folder.fold_element_collection(node.content)).into())
+}
+
+/// Rebuilds underline text with its folded content.
+pub fn fold_underline<F: Fold + ?Sized>(
+    folder: &mut F,
+    node: Underline,
+) -> Option<MarkdownElement> {
+    Some(Underline::new(folder.fold_element_collection(node.content)).into())
+}
+
+/// Rebuilds strikethrough text with its folded content.
+pub fn fold_strikethrough<F: Fold + ?Sized>(
+    folder: &mut F,
+    node: Strikethrough,
+) -> Option<MarkdownElement> {
+    Some(Strikethrough::new(folder.fold_element_collection(node.content)).into())
+}
+
+/// Rebuilds spoiler text with its folded content.
+pub fn fold_spoiler<F: Fold + ?Sized>(folder: &mut F, node: Spoiler) -> Option<MarkdownElement> {
+    Some(Spoiler::new(folder.fold_element_collection(node.content)).into())
+}
+
+/// Rebuilds a block quote with its folded content.
+pub fn fold_block_quote<F: Fold + ?Sized>(
+    folder: &mut F,
+    node: BlockQuote,
+) -> Option<MarkdownElement> {
+    Some(BlockQuote::new(folder.fold_element_collection(node.content)).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_text() -> MarkdownElementCollection {
+        MarkdownElementCollection::new(vec![MarkdownElement::Plain(Box::new(Plain::new("text")))])
+    }
+
+    #[test]
+    fn test_document_content() {
+        assert_eq!(
+            MarkdownDocument::new(example_text()).content(),
+            &example_text()
+        );
+    }
+
+    #[test]
+    fn test_document_into_content() {
+        assert_eq!(
+            MarkdownDocument::new(example_text()).into_content(),
+            example_text()
+        );
+    }
+
+    #[test]
+    fn test_document_auto_close() {
+        let ast = MarkdownDocument::new(vec![
+            MarkdownElement::Bold(Box::new(Bold::new("bold"))),
+            MarkdownElement::Plain(Box::new(Plain::new(" __underline"))),
+        ]);
+
+        assert_eq!(ast.auto_close(), "**bold** __underline__");
+    }
+
+    #[test]
+    fn test_element_children() {
+        assert_eq!(
+            MarkdownElement::Plain(Box::new(Plain::new("text"))).children(),
+            None
+        );
+        assert_eq!(
+            MarkdownElement::Bold(Box::new(Bold::new(example_text()))).children(),
+            Some(&example_text())
+        );
+        assert_eq!(
+            MarkdownElement::BlockQuote(Box::new(BlockQuote::new(example_text()))).children(),
+            Some(&example_text())
+        );
+        assert_eq!(
+            MarkdownElement::OneLineCode(Box::new(OneLineCode::new("code"))).children(),
+            None
+        );
+        assert_eq!(
+            MarkdownElement::MultiLineCode(Box::new(MultiLineCode::new("code", None))).children(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_element_kind() {
+        assert_eq!(
+            MarkdownElement::Plain(Box::new(Plain::new("text"))).kind(),
+            ElementKind::Plain
+        );
+        assert_eq!(
+            MarkdownElement::Spoiler(Box::new(Spoiler::new(example_text()))).kind(),
+            ElementKind::Spoiler
+        );
+        assert_eq!(
+            MarkdownElement::MultiLineCode(Box::new(MultiLineCode::new("code", None))).kind(),
+            ElementKind::MultiLineCode
+        );
+    }
+
+    #[test]
+    fn test_element_kind_name() {
+        for kind in ElementKind::ALL {
+            assert_eq!(ElementKind::from_name(kind.name()), Some(kind));
+            assert_eq!(kind.to_string(), kind.name());
+        }
+        assert_eq!(ElementKind::OneLineCode.name(), "one_line_code");
+        assert_eq!(ElementKind::from_name("OneLineCode"), None);
+    }
+
+    #[test]
+    fn test_from_element() {
+        let element = MarkdownElement::Bold(Box::new(Bold::new(example_text())));
+
+        assert_eq!(
+            Bold::from_element(&element),
+            Some(&Bold::new(example_text()))
+        );
+        assert_eq!(Underline::from_element(&element), None);
+        assert_eq!(Plain::from_element(&element), None);
+    }
+
+    #[test]
+    fn test_document_find_all() {
+        let ast = MarkdownDocument::new(vec![
+            MarkdownElement::Bold(Box::new(Bold::new(vec![MarkdownElement::Bold(Box::new(
+                Bold::new("inner"),
+            ))]))),
+            MarkdownElement::Plain(Box::new(Plain::new("plain"))),
+        ]);
+
+        assert_eq!(ast.find_all::<Bold>().len(), 2);
+        assert_eq!(
+            ast.find_all::<Plain>(),
+            vec![&Plain::new("inner"), &Plain::new("plain")]
+        );
+        assert_eq!(ast.find_all::<Spoiler>(), Vec::<&Spoiler>::new());
+    }
+
+    #[test]
+    fn test_document_select() {
+        let ast = MarkdownDocument::new(vec![
+            MarkdownElement::Spoiler(Box::new(Spoiler::new(vec![MarkdownElement::Plain(
+                Box::new(Plain::new("a")),
+            )]))),
+            MarkdownElement::Plain(Box::new(Plain::new("b"))),
+        ]);
+
+        assert_eq!(
+            ast.select("spoiler > plain"),
+            Ok(vec![&MarkdownElement::Plain(Box::new(Plain::new("a")))])
+        );
+        assert_eq!(ast.select("*").map(|x| x.len()), Ok(3));
+        assert_eq!(ast.select(""), Err(SelectorError::Empty));
+    }
+
+    #[test]
+    fn test_document_semantically_eq() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let hash = |document: &MarkdownDocument| {
+            let mut hasher = DefaultHasher::new();
+            document.semantic_hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let a = MarkdownDocument::new(vec![
+            MarkdownElement::ItalicsStar(Box::new(ItalicsStar::new("x"))),
+            MarkdownElement::Plain(Box::new(Plain::new("y"))),
+            MarkdownElement::Plain(Box::new(Plain::new("z"))),
+        ]);
+        let b = MarkdownDocument::new(vec![
+            MarkdownElement::ItalicsUnderscore(Box::new(ItalicsUnderscore::new("x"))),
+            MarkdownElement::Plain(Box::new(Plain::new("yz"))),
+        ]);
+        let c = MarkdownDocument::new(vec![
+            MarkdownElement::Bold(Box::new(Bold::new("x"))),
+            MarkdownElement::Plain(Box::new(Plain::new("yz"))),
+        ]);
+
+        assert_ne!(a, b);
+        assert!(a.semantically_eq(&b));
+        assert_eq!(hash(&a), hash(&b));
+        assert!(!a.semantically_eq(&c));
+    }
+
+    #[test]
+    fn test_document_len() {
+        let ast = MarkdownDocument::new(vec![
+            MarkdownElement::Spoiler(Box::new(Spoiler::new("日本"))),
+            MarkdownElement::Plain(Box::new(Plain::new(" <a:x:1>"))),
+        ]);
+
+        assert_eq!(ast.markdown_len(), 14);
+        assert_eq!(ast.visible_len(), 4);
+    }
+
+    #[test]
+    fn test_document_split() {
+        let ast = MarkdownDocument::new(vec![MarkdownElement::Spoiler(Box::new(Spoiler::new(
+            "a b",
+        )))]);
+
+        assert_eq!(
+            ast.split(&SplitOption::new().limit(5)),
+            vec!["||a||", "||b||"]
+        );
+        assert_eq!(ast.split(&SplitOption::new()), vec!["||a b||"]);
+    }
+
+    #[test]
+    fn test_document_truncate() {
+        let ast = MarkdownDocument::new(vec![MarkdownElement::Spoiler(Box::new(Spoiler::new(
+            "abc",
+        )))]);
+
+        assert_eq!(
+            ast.truncate(2, &TruncateOption::new().ellipsis(".")),
+            MarkdownDocument::new(vec![MarkdownElement::Spoiler(Box::new(Spoiler::new("a.")))])
+        );
+        assert_eq!(ast.truncate(3, &TruncateOption::new()), ast);
+    }
+
+    #[test]
+    fn test_document_normalize() {
+        let mut ast = MarkdownDocument::new(vec![
+            MarkdownElement::Plain(Box::new(Plain::new("a"))),
+            MarkdownElement::Plain(Box::new(Plain::new("b"))),
+            MarkdownElement::Bold(Box::new(Bold::new(vec![MarkdownElement::Bold(Box::new(
+                Bold::new("c"),
+            ))]))),
+        ]);
+        ast.normalize();
+
+        assert_eq!(
+            ast,
+            MarkdownDocument::new(vec![
+                MarkdownElement::Plain(Box::new(Plain::new("ab"))),
+                MarkdownElement::Bold(Box::new(Bold::new("c"))),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_element_collection_get() {
+        let test_case = || vec![MarkdownElement::Plain(Box::new(Plain::new("plain")))];
+        assert_eq!(
+            MarkdownElementCollection::new(test_case()).get(),
+            &test_case()
+        );
+    }
+
+    #[test]
+    fn test_element_collection_from_element() {
+        assert_eq!(
+            MarkdownElementCollection::from(MarkdownElement::Plain(Box::new(Plain::new(
+                "plain text"
+            )))),
+            MarkdownElementCollection::new(vec![MarkdownElement::Plain(Box::new(Plain::new(
+                "plain text"
+            )))]),
+        );
+    }
+
+    #[test]
+    fn test_element_collection_from_str() {
+        assert_eq!(
+            MarkdownElementCollection::from("plain text"),
+            MarkdownElementCollection::new(vec![MarkdownElement::Plain(Box::new(Plain::new(
+                "plain text"
+            )))]),
+        );
+    }
+
+    #[test]
+    fn test_element_collection_from_string() {
+        let test_case = "plain text".to_string();
+        assert_eq!(
+            MarkdownElementCollection::from(&test_case),
+            MarkdownElementCollection::new(vec![MarkdownElement::Plain(Box::new(Plain::new(
+                "plain text"
+            )))]),
+        );
+        assert_eq!(
+            MarkdownElementCollection::from(test_case),
+            MarkdownElementCollection::new(vec![MarkdownElement::Plain(Box::new(Plain::new(
+                "plain text"
+            )))]),
+        );
+    }
+
+    #[test]
+    fn test_plain_content() {
+        assert_eq!(Plain::new("plain text").content(), "plain text");
+    }
+
+    #[test]
+    fn test_italics_star_content() {
+        assert_eq!(ItalicsStar::new(example_text()).content(), &example_text());
+    }
+
+    #[test]
+    fn test_italics_underscore_content() {
+        assert_eq!(
+            ItalicsUnderscore::new(example_text()).content(),
+            &example_text()
+        );
+    }
+
+    #[test]
+    fn test_bold_content() {
+        assert_eq!(Bold::new(example_text()).content(), &example_text());
+    }
+
+    #[test]
+    fn test_underline_content() {
+        assert_eq!(Underline::new(example_text()).content(), &example_text());
+    }
+
+    #[test]
+    fn test_strikethrough_content() {
+        assert_eq!(
+            Strikethrough::new(example_text()).content(),
+            &example_text()
+        );
+    }
+
+    #[test]
+    fn test_spoiler_content() {
+        assert_eq!(Spoiler::new(example_text()).content(), &example_text());
+    }
+
+    #[test]
+    fn test_one_line_code_content() {
+        assert_eq!(OneLineCode::new("one line code").content(), "one line code");
+    }
+
+    #[test]
+    fn test_multi_line_code_content() {
+        assert_eq!(
+            MultiLineCode::new("multi\nline\ncode\n", None).content(),
+            "multi\nline\ncode\n"
+        );
+    }
+
+    #[test]
+    fn test_multi_line_code_language() {
+        assert_eq!(
+            MultiLineCode::new("multi\nline\ncode\n", Some("js".to_string())).language(),
+            Some("js")
+        );
+        assert_eq!(
+            MultiLineCode::new("multi\nline\ncode\n", None).language(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_clone() {
+        let ast = MarkdownDocument::new(vec![
+            MarkdownElement::Bold(Box::new(Bold::new(example_text()))),
+            MarkdownElement::MultiLineCode(Box::new(MultiLineCode::new(
+                "code",
+                Some("js".to_string()),
+            ))),
+        ]);
+
+        assert_eq!(ast.clone(), ast);
+    }
+
+    #[test]
+    fn test_document_content_mut() {
+        let mut ast = MarkdownDocument::new(example_text());
+        ast.content_mut()
+            .get_mut()
+            .push(MarkdownElement::Plain(Box::new(Plain::new("!"))));
+
+        assert_eq!(ast.to_string(), "text!");
+    }
+
+    #[test]
+    fn test_element_collection_get_mut() {
+        let mut collection = example_text();
+        collection.get_mut().clear();
+
+        assert_eq!(collection, MarkdownElementCollection::new(vec![]));
+    }
+
+    #[test]
+    fn test_element_collection_into_inner() {
+        assert_eq!(
+            example_text().into_inner(),
+            vec![MarkdownElement::Plain(Box::new(Plain::new("text")))]
+        );
+    }
+
+    #[test]
+    fn test_plain_content_mut() {
+        let mut plain = Plain::new("plain");
+        plain.content_mut().push_str(" text");
+
+        assert_eq!(plain.content(), "plain text");
+    }
+
+    #[test]
+    fn test_styled_content_mut() {
+        let replacement = || MarkdownElementCollection::from("replaced");
+
+        let mut italics_star = ItalicsStar::new(example_text());
+        *italics_star.content_mut() = replacement();
+        assert_eq!(italics_star.content(), &replacement());
+
+        let mut italics_underscore = ItalicsUnderscore::new(example_text());
+        *italics_underscore.content_mut() = replacement();
+        assert_eq!(italics_underscore.content(), &replacement());
+
+        let mut bold = Bold::new(example_text());
+        *bold.content_mut() = replacement();
+        assert_eq!(bold.content(), &replacement());
+
+        let mut underline = Underline::new(example_text());
+        *underline.content_mut() = replacement();
+        assert_eq!(underline.content(), &replacement());
+
+        let mut strikethrough = Strikethrough::new(example_text());
+        *strikethrough.content_mut() = replacement();
+        assert_eq!(strikethrough.content(), &replacement());
+
+        let mut spoiler = Spoiler::new(example_text());
+        *spoiler.content_mut() = replacement();
+        assert_eq!(spoiler.content(), &replacement());
+
+        let mut block_quote = BlockQuote::new(example_text());
+        *block_quote.content_mut() = replacement();
+        assert_eq!(block_quote.content(), &replacement());
+    }
+
+    #[test]
+    fn test_one_line_code_content_mut() {
+        let mut code = OneLineCode::new("one line");
+        code.content_mut().push_str(" code");
+
+        assert_eq!(code.content(), "one line code");
+    }
+
+    #[test]
+    fn test_multi_line_code_content_mut() {
+        let mut code = MultiLineCode::new("multi\nline\n", None);
+        code.content_mut().push_str("code\n");
+
+        assert_eq!(code.content(), "multi\nline\ncode\n");
+    }
+
+    #[test]
+    fn test_multi_line_code_set_language() {
+        let mut code = MultiLineCode::new("multi\nline\ncode\n", None);
+
+        code.set_language(Some("js".to_string()));
+        assert_eq!(code.language(), Some("js"));
+
+        code.set_language(None);
+        assert_eq!(code.language(), None);
+    }
+
+    #[test]
+    fn test_multi_line_code_into_inner() {
+        assert_eq!(
+            MultiLineCode::new("multi\nline\ncode\n", Some("js".to_string())).into_inner(),
+            ("multi\nline\ncode\n".to_string(), Some("js".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_block_quote_content() {
+        let test_case = || {
+            MarkdownElementCollection::new(vec![MarkdownElement::Plain(Box::new(Plain::new(
+                "block quote\ntext",
             )))])
         };
 
@@ -800,6 +2273,191 @@ mod tests {
         );
     }
 
+    /// Records the name of every visited node.
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl Visitor for Recorder {
+        fn visit_plain(&mut self, node: &Plain) {
+            self.0.push(format!("plain {}", node.content()));
+        }
+
+        fn visit_bold(&mut self, node: &Bold) {
+            self.0.push("bold".to_string());
+            walk_bold(self, node);
+        }
+
+        fn visit_spoiler(&mut self, _node: &Spoiler) {
+            self.0.push("spoiler".to_string());
+        }
+
+        fn visit_multi_line_code(&mut self, node: &MultiLineCode) {
+            self.0.push(format!("code {}", node.content()));
+        }
+
+        fn visit_block_quote(&mut self, node: &BlockQuote) {
+            self.0.push("quote".to_string());
+            walk_block_quote(self, node);
+        }
+    }
+
+    #[test]
+    fn test_visitor() {
+        let ast = MarkdownDocument::new(vec![
+            MarkdownElement::Plain(Box::new(Plain::new("a"))),
+            MarkdownElement::Bold(Box::new(Bold::new(vec![
+                MarkdownElement::ItalicsStar(Box::new(ItalicsStar::new("b"))),
+                MarkdownElement::Spoiler(Box::new(Spoiler::new("hidden"))),
+            ]))),
+            MarkdownElement::MultiLineCode(Box::new(MultiLineCode::new("c", None))),
+            MarkdownElement::BlockQuote(Box::new(BlockQuote::new(vec![
+                MarkdownElement::Underline(Box::new(Underline::new("d"))),
+                MarkdownElement::OneLineCode(Box::new(OneLineCode::new("e"))),
+            ]))),
+        ]);
+
+        let mut recorder = Recorder::default();
+        recorder.visit_document(&ast);
+
+        assert_eq!(
+            recorder.0,
+            vec!["plain a", "bold", "plain b", "spoiler", "code c", "quote", "plain d"]
+        );
+    }
+
+    /// Sets the language of every code block and removes the content of spoilers.
+    struct Rewriter;
+
+    impl VisitorMut for Rewriter {
+        fn visit_multi_line_code_mut(&mut self, node: &mut MultiLineCode) {
+            node.language = Some("rust".to_string());
+        }
+
+        fn visit_spoiler_mut(&mut self, node: &mut Spoiler) {
+            node.content = MarkdownElementCollection::from("***");
+        }
+    }
+
+    #[test]
+    fn test_visitor_mut() {
+        let mut ast = MarkdownDocument::new(vec![
+            MarkdownElement::Strikethrough(Box::new(Strikethrough::new(vec![
+                MarkdownElement::Spoiler(Box::new(Spoiler::new("secret"))),
+            ]))),
+            MarkdownElement::ItalicsUnderscore(Box::new(ItalicsUnderscore::new(vec![
+                MarkdownElement::MultiLineCode(Box::new(MultiLineCode::new(
+                    "\nfn main() {}",
+                    None,
+                ))),
+            ]))),
+        ]);
+
+        Rewriter.visit_document_mut(&mut ast);
+
+        assert_eq!(ast.to_string(), "~~||***||~~_```rust\nfn main() {}```_");
+    }
+
+    #[test]
+    fn test_plain_into_content() {
+        assert_eq!(Plain::new("plain text").into_content(), "plain text");
+    }
+
+    #[test]
+    fn test_styled_into_content() {
+        assert_eq!(
+            ItalicsStar::new(example_text()).into_content(),
+            example_text()
+        );
+        assert_eq!(
+            ItalicsUnderscore::new(example_text()).into_content(),
+            example_text()
+        );
+        assert_eq!(Bold::new(example_text()).into_content(), example_text());
+        assert_eq!(
+            Underline::new(example_text()).into_content(),
+            example_text()
+        );
+        assert_eq!(
+            Strikethrough::new(example_text()).into_content(),
+            example_text()
+        );
+        assert_eq!(Spoiler::new(example_text()).into_content(), example_text());
+        assert_eq!(
+            BlockQuote::new(example_text()).into_content(),
+            example_text()
+        );
+    }
+
+    #[test]
+    fn test_code_into_content() {
+        assert_eq!(
+            OneLineCode::new("one line code").into_content(),
+            "one line code"
+        );
+        assert_eq!(
+            MultiLineCode::new("multi\nline\ncode\n", Some("js".to_string())).into_content(),
+            "multi\nline\ncode\n"
+        );
+    }
+
+    /// Does not override anything.
+    struct Identity;
+
+    impl Fold for Identity {}
+
+    /// Turns bold text into italics, drops inline codes and capitalizes `a` in plain text.
+    struct Rebuilder;
+
+    impl Fold for Rebuilder {
+        fn fold_plain(&mut self, node: Plain) -> Option<MarkdownElement> {
+            Some(Plain::new(node.into_content().replace('a', "A")).into())
+        }
+
+        fn fold_bold(&mut self, node: Bold) -> Option<MarkdownElement> {
+            Some(ItalicsStar::new(self.fold_element_collection(node.into_content())).into())
+        }
+
+        fn fold_one_line_code(&mut self, _node: OneLineCode) -> Option<MarkdownElement> {
+            None
+        }
+    }
+
+    fn fold_test_case() -> MarkdownDocument {
+        MarkdownDocument::new(vec![
+            MarkdownElement::Plain(Box::new(Plain::new("a "))),
+            MarkdownElement::Underline(Box::new(Underline::new(vec![
+                MarkdownElement::Bold(Box::new(Bold::new("bar"))),
+                MarkdownElement::OneLineCode(Box::new(OneLineCode::new("code"))),
+            ]))),
+            MarkdownElement::BlockQuote(Box::new(BlockQuote::new(vec![MarkdownElement::Spoiler(
+                Box::new(Spoiler::new("baz")),
+            )]))),
+            MarkdownElement::MultiLineCode(Box::new(MultiLineCode::new("aaa", None))),
+        ])
+    }
+
+    #[test]
+    fn test_fold_identity() {
+        assert_eq!(Identity.fold_document(fold_test_case()), fold_test_case());
+    }
+
+    #[test]
+    fn test_fold() {
+        assert_eq!(
+            Rebuilder.fold_document(fold_test_case()),
+            MarkdownDocument::new(vec![
+                MarkdownElement::Plain(Box::new(Plain::new("A "))),
+                MarkdownElement::Underline(Box::new(Underline::new(vec![
+                    MarkdownElement::ItalicsStar(Box::new(ItalicsStar::new("bAr"))),
+                ]))),
+                MarkdownElement::BlockQuote(Box::new(BlockQuote::new(vec![
+                    MarkdownElement::Spoiler(Box::new(Spoiler::new("bAz"))),
+                ]))),
+                MarkdownElement::MultiLineCode(Box::new(MultiLineCode::new("aaa", None))),
+            ])
+        );
+    }
+
     #[test]
     fn test_element_from_block_quote() {
         let test_case = || {
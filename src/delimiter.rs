@@ -0,0 +1,275 @@
+//! Markdown delimiters
+//!
+//! [`delimiter`](crate::delimiter) module provides [`Delimiter`], which represents the markup wrapped around styled text,
+//! and functions to detect and close unterminated delimiters.
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::delimiter::{auto_close, unterminated_delimiters, Delimiter};
+//!
+//! let truncated = "**Release notes** ||the new ~~feature";
+//!
+//! assert_eq!(
+//!     unterminated_delimiters(truncated),
+//!     vec![(18, Delimiter::Spoiler), (28, Delimiter::Strikethrough)]
+//! );
+//! assert_eq!(auto_close(truncated), "**Release notes** ||the new ~~feature~~||");
+//! ```
+
+use crate::parser;
+use derive_more::Display;
+
+/// A markdown delimiter, such as `**` of bold text.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::delimiter::Delimiter;
+///
+/// assert_eq!(Delimiter::Bold.as_str(), "**");
+/// assert_eq!(Delimiter::MultiLineCode.to_string(), "```");
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Display)]
+pub enum Delimiter {
+    /// `*` of italics text.
+    #[display(fmt = "*")]
+    ItalicsStar,
+
+    /// `_` of italics text.
+    #[display(fmt = "_")]
+    ItalicsUnderscore,
+
+    /// `**` of bold text.
+    #[display(fmt = "**")]
+    Bold,
+
+    /// `__` of underline text.
+    #[display(fmt = "__")]
+    Underline,
+
+    /// `~~` of strikethrough text.
+    #[display(fmt = "~~")]
+    Strikethrough,
+
+    /// `||` of spoiler text.
+    #[display(fmt = "||")]
+    Spoiler,
+
+    /// `` ` `` of inline code.
+    #[display(fmt = "`")]
+    OneLineCode,
+
+    /// ```` ``` ```` of multiline code block.
+    #[display(fmt = "```")]
+    MultiLineCode,
+}
+
+impl Delimiter {
+    /// Every delimiter, longest first so that `**` is matched before `*`.
+    pub(crate) const LONGEST_FIRST: [Delimiter; 8] = [
+        Delimiter::MultiLineCode,
+        Delimiter::Bold,
+        Delimiter::Underline,
+        Delimiter::Strikethrough,
+        Delimiter::Spoiler,
+        Delimiter::OneLineCode,
+        Delimiter::ItalicsStar,
+        Delimiter::ItalicsUnderscore,
+    ];
+
+    /// Returns the markup of the delimiter.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Delimiter::ItalicsStar => "*",
+            Delimiter::ItalicsUnderscore => "_",
+            Delimiter::Bold => "**",
+            Delimiter::Underline => "__",
+            Delimiter::Strikethrough => "~~",
+            Delimiter::Spoiler => "||",
+            Delimiter::OneLineCode => "`",
+            Delimiter::MultiLineCode => "```",
+        }
+    }
+
+    /// Returns `true` if the content wrapped in the delimiter is code, which is not parsed as markdown.
+    pub fn is_code(&self) -> bool {
+        matches!(self, Delimiter::OneLineCode | Delimiter::MultiLineCode)
+    }
+}
+
+/// Finds opening delimiters that are never closed in the markdown text.
+///
+/// Returns the byte offset and the kind of each unterminated delimiter, outermost first.
+/// These are the delimiters that the parser leaves as plain text because it cannot find the closing one.
+/// Delimiters inside an unterminated code are not reported, since code is not parsed as markdown.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::delimiter::{unterminated_delimiters, Delimiter};
+///
+/// assert_eq!(unterminated_delimiters("*closed* **open"), vec![(9, Delimiter::Bold)]);
+/// assert_eq!(unterminated_delimiters("`code **"), vec![(0, Delimiter::OneLineCode)]);
+/// ```
+pub fn unterminated_delimiters(text: &str) -> Vec<(usize, Delimiter)> {
+    parser::unterminated_delimiters(text)
+}
+
+/// Appends closing delimiters to the markdown text so that every unterminated delimiter is closed.
+///
+/// - Closing delimiters are appended innermost first, so nested styles are closed in order.
+/// - Unterminated delimiters without any content after them are removed instead,
+///   since an empty pair of delimiters is not parsed as styled text.
+/// - A partially received closing delimiter at the end, like `|` of `||` or ``` `` ``` of ```` ``` ````, is completed.
+/// - A closing delimiter is separated by a zero width space (U+200B) from a preceding character of the same kind,
+///   so that the parser does not read them as one delimiter, like `***` of `**a *b***`.
+/// - If the parser still cannot read some delimiters as closed, they are removed as a last resort.
+///
+/// The returned text never has unterminated delimiters.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::delimiter::auto_close;
+///
+/// assert_eq!(auto_close("**bold __underline"), "**bold __underline__**");
+/// assert_eq!(auto_close("```sh\necho hi\n``"), "```sh\necho hi\n```");
+/// assert_eq!(auto_close("dangling **"), "dangling ");
+/// assert_eq!(auto_close("**bold *italics"), "**bold *italics*\u{200B}**");
+/// assert_eq!(auto_close("nothing to close"), "nothing to close");
+/// ```
+pub fn auto_close(text: &str) -> String {
+    let mut text = append_closers(text);
+
+    loop {
+        let mut unterminated = unterminated_delimiters(&text);
+        if unterminated.is_empty() {
+            return text;
+        }
+        unterminated.sort_by_key(|&(offset, _)| std::cmp::Reverse(offset));
+        for (offset, delimiter) in unterminated {
+            text.replace_range(offset..offset + delimiter.as_str().len(), "");
+        }
+    }
+}
+
+/// Appends closing delimiters of the unterminated delimiters, innermost first.
+fn append_closers(text: &str) -> String {
+    let mut text = text.to_string();
+    let mut unterminated = unterminated_delimiters(&text);
+
+    while let Some(&(offset, delimiter)) = unterminated.last() {
+        if offset + delimiter.as_str().len() != text.len() {
+            break;
+        }
+        text.truncate(offset);
+        unterminated.pop();
+    }
+
+    for (index, &(_, delimiter)) in unterminated.iter().enumerate().rev() {
+        let closer = delimiter.as_str();
+
+        // Complete a partially received closer of the innermost delimiter, e.g. "``" of a code block.
+        let received = if index + 1 == unterminated.len() {
+            (1..closer.len())
+                .rev()
+                .find(|&len| text.ends_with(&closer[..len]))
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        if received == 0 && !delimiter.is_code() && text.ends_with(&closer[..1]) {
+            text.push('\u{200B}');
+        }
+        text.push_str(&closer[received..]);
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delimiter_as_str() {
+        assert_eq!(Delimiter::ItalicsStar.as_str(), "*");
+        assert_eq!(Delimiter::ItalicsUnderscore.as_str(), "_");
+        assert_eq!(Delimiter::Bold.as_str(), "**");
+        assert_eq!(Delimiter::Underline.as_str(), "__");
+        assert_eq!(Delimiter::Strikethrough.as_str(), "~~");
+        assert_eq!(Delimiter::Spoiler.as_str(), "||");
+        assert_eq!(Delimiter::OneLineCode.as_str(), "`");
+        assert_eq!(Delimiter::MultiLineCode.as_str(), "```");
+    }
+
+    #[test]
+    fn test_delimiter_to_string() {
+        for delimiter in Delimiter::LONGEST_FIRST {
+            assert_eq!(delimiter.to_string(), delimiter.as_str());
+        }
+    }
+
+    #[test]
+    fn test_delimiter_is_code() {
+        assert!(!Delimiter::Bold.is_code());
+        assert!(Delimiter::OneLineCode.is_code());
+        assert!(Delimiter::MultiLineCode.is_code());
+    }
+
+    #[test]
+    fn test_auto_close() {
+        assert_eq!(auto_close(""), "");
+        assert_eq!(auto_close("plain"), "plain");
+        assert_eq!(auto_close("**bold**"), "**bold**");
+        assert_eq!(auto_close("**bold"), "**bold**");
+        assert_eq!(auto_close("**bold*"), "**bold**");
+        assert_eq!(auto_close("**bold __under"), "**bold __under__**");
+        assert_eq!(auto_close("||spoiler|"), "||spoiler||");
+        assert_eq!(auto_close("||spoiler **"), "||spoiler ||");
+        assert_eq!(auto_close("text **"), "text ");
+        assert_eq!(auto_close("`code **"), "`code **`");
+        assert_eq!(auto_close("```js\ncode\n"), "```js\ncode\n```");
+        assert_eq!(auto_close("```js\ncode\n``"), "```js\ncode\n```");
+        assert_eq!(auto_close("**a ```js\nb"), "**a ```js\nb```**");
+        assert_eq!(auto_close("_a **"), "_a _");
+        assert_eq!(auto_close("||a __b ~~c"), "||a __b ~~c~~__||");
+    }
+
+    #[test]
+    fn test_auto_close_same_character() {
+        assert_eq!(auto_close("**a *b"), "**a *b*\u{200B}**");
+        assert_eq!(auto_close("***a"), "***a*\u{200B}**");
+        assert_eq!(auto_close("__a _b"), "__a _b_\u{200B}__");
+        assert_eq!(auto_close("*a **b"), "*a **b*");
+    }
+
+    #[test]
+    fn test_auto_close_is_closed() {
+        let tokens = [
+            "*", "**", "_", "__", "~~", "~", "||", "|", "`", "```", "a", " ", "\n",
+        ];
+
+        // Every sequence of up to 4 tokens.
+        let mut texts = vec![String::new()];
+        let mut level = vec![String::new()];
+        for _ in 0..4 {
+            level = level
+                .iter()
+                .flat_map(|text| tokens.iter().map(move |token| format!("{}{}", text, token)))
+                .collect();
+            texts.extend(level.iter().cloned());
+        }
+
+        for text in texts {
+            let closed = auto_close(&text);
+            assert!(
+                unterminated_delimiters(&closed).is_empty(),
+                "{:?} was closed as {:?}",
+                text,
+                closed
+            );
+        }
+    }
+}
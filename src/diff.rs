@@ -0,0 +1,489 @@
+//! Structural diff between two documents
+//!
+//! [`diff`](crate::diff) module provides [`diff()`], which reports how the elements of a document changed,
+//! including changes of formatting that keep the text as it is.
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::diff::{diff, Change};
+//! use discord_md::parse;
+//!
+//! let old = parse("release `v1` is out");
+//! let new = parse("release **`v1`** is out");
+//!
+//! assert_eq!(
+//!     diff(&old, &new)
+//!         .iter()
+//!         .map(|change| change.to_string())
+//!         .collect::<Vec<_>>(),
+//!     vec!["restyled `v1` from `` `v1` `` to `` **`v1`** ``"]
+//! );
+//! ```
+
+use crate::ast::{MarkdownDocument, MarkdownElement};
+use derive_more::Display;
+use std::ops::Range;
+
+/// A change between two documents, returned by [`diff()`].
+///
+/// Paths are indices of elements from the root of the document.
+/// For instance, `[1, 0]` is the first child of the second top-level element.
+/// Paths of deleted elements point into the old document, and paths of inserted elements point into the new document.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Display)]
+pub enum Change<'a> {
+    /// The element only exists in the new document.
+    #[display(fmt = "inserted {}", "quote(&element.to_string())")]
+    Inserted {
+        path: Vec<usize>,
+        element: &'a MarkdownElement,
+    },
+
+    /// The element only exists in the old document.
+    #[display(fmt = "deleted {}", "quote(&element.to_string())")]
+    Deleted {
+        path: Vec<usize>,
+        element: &'a MarkdownElement,
+    },
+
+    /// The elements have the same text in both documents, but are formatted differently.
+    ///
+    /// `old_path` and `new_path` point to the first element of `old` and `new` respectively.
+    #[display(
+        fmt = "restyled {} from {} to {}",
+        "quote(&text(old))",
+        "quote(&markdown(old))",
+        "quote(&markdown(new))"
+    )]
+    Restyled {
+        old_path: Vec<usize>,
+        old: &'a [MarkdownElement],
+        new_path: Vec<usize>,
+        new: &'a [MarkdownElement],
+    },
+}
+
+/// Computes the changes from `old` to `new` in document order.
+///
+/// Equal elements are matched first, using the [`PartialEq`] implementation of [`MarkdownElement`].
+/// Among the remaining elements, elements of the same kind are compared child by child,
+/// and elements with the same text are reported as [`Change::Restyled`].
+/// Everything else is reported as [`Change::Deleted`] or [`Change::Inserted`].
+///
+/// Plain text is compared as a whole, so editing a word in plain text deletes and inserts the whole plain text.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::builder::*;
+/// use discord_md::diff::{diff, Change};
+/// use discord_md::parse;
+///
+/// let old = parse("**hello** world");
+/// let new = parse("**hello there** world");
+///
+/// assert_eq!(
+///     diff(&old, &new),
+///     vec![
+///         Change::Deleted { path: vec![0, 0], element: &plain("hello") },
+///         Change::Inserted { path: vec![0, 0], element: &plain("hello there") },
+///     ]
+/// );
+/// ```
+pub fn diff<'a>(old: &'a MarkdownDocument, new: &'a MarkdownDocument) -> Vec<Change<'a>> {
+    let mut changes = Vec::new();
+    diff_elements(
+        old.content().get(),
+        new.content().get(),
+        &[],
+        &[],
+        &mut changes,
+    );
+    changes
+}
+
+/// Diffs two collections of elements whose paths start with `old_path` and `new_path`.
+fn diff_elements<'a>(
+    old: &'a [MarkdownElement],
+    new: &'a [MarkdownElement],
+    old_path: &[usize],
+    new_path: &[usize],
+    changes: &mut Vec<Change<'a>>,
+) {
+    for_each_gap(
+        old,
+        new,
+        |a, b| a == b,
+        |old_range, new_range| {
+            diff_unmatched(old, new, old_range, new_range, old_path, new_path, changes);
+        },
+    );
+}
+
+/// Diffs elements between two equal elements.
+fn diff_unmatched<'a>(
+    old: &'a [MarkdownElement],
+    new: &'a [MarkdownElement],
+    old_range: Range<usize>,
+    new_range: Range<usize>,
+    old_path: &[usize],
+    new_path: &[usize],
+    changes: &mut Vec<Change<'a>>,
+) {
+    let old_gap = &old[old_range.clone()];
+    let new_gap = &new[new_range.clone()];
+    let pairs = lcs(old_gap, new_gap, is_comparable);
+
+    let mut old_start = 0;
+    let mut new_start = 0;
+    for (i, j) in pairs.into_iter().chain([(old_gap.len(), new_gap.len())]) {
+        report_rest(
+            old,
+            new,
+            old_range.start + old_start..old_range.start + i,
+            new_range.start + new_start..new_range.start + j,
+            old_path,
+            new_path,
+            changes,
+        );
+
+        if i < old_gap.len() {
+            let old_path = child_path(old_path, old_range.start + i);
+            let new_path = child_path(new_path, new_range.start + j);
+            match (old_gap[i].children(), new_gap[j].children()) {
+                (Some(a), Some(b)) if old_gap[i].kind() == new_gap[j].kind() => {
+                    diff_elements(a.get(), b.get(), &old_path, &new_path, changes);
+                }
+                _ => changes.push(Change::Restyled {
+                    old_path,
+                    old: &old_gap[i..=i],
+                    new_path,
+                    new: &new_gap[j..=j],
+                }),
+            }
+        }
+
+        old_start = i + 1;
+        new_start = j + 1;
+    }
+}
+
+/// Reports elements that cannot be paired with each other.
+fn report_rest<'a>(
+    old: &'a [MarkdownElement],
+    new: &'a [MarkdownElement],
+    old_range: Range<usize>,
+    new_range: Range<usize>,
+    old_path: &[usize],
+    new_path: &[usize],
+    changes: &mut Vec<Change<'a>>,
+) {
+    let old_rest = &old[old_range.clone()];
+    let new_rest = &new[new_range.clone()];
+
+    // Splitting or merging styled text keeps its text, e.g. `foo` in "foo bar" becomes bold.
+    if !old_rest.is_empty() && !new_rest.is_empty() && text(old_rest) == text(new_rest) {
+        changes.push(Change::Restyled {
+            old_path: child_path(old_path, old_range.start),
+            old: old_rest,
+            new_path: child_path(new_path, new_range.start),
+            new: new_rest,
+        });
+        return;
+    }
+
+    for (index, element) in old_range.zip(old_rest) {
+        changes.push(Change::Deleted {
+            path: child_path(old_path, index),
+            element,
+        });
+    }
+    for (index, element) in new_range.zip(new_rest) {
+        changes.push(Change::Inserted {
+            path: child_path(new_path, index),
+            element,
+        });
+    }
+}
+
+/// Returns `true` if two different elements should be reported as a change of one element.
+fn is_comparable(a: &MarkdownElement, b: &MarkdownElement) -> bool {
+    let same_container = a.kind() == b.kind() && a.children().is_some();
+    same_container || text(std::slice::from_ref(a)) == text(std::slice::from_ref(b))
+}
+
+/// Calls `f` with the ranges of elements between pairs matched by `eq`.
+fn for_each_gap<T>(
+    old: &[T],
+    new: &[T],
+    eq: impl Fn(&T, &T) -> bool,
+    mut f: impl FnMut(Range<usize>, Range<usize>),
+) {
+    let mut old_start = 0;
+    let mut new_start = 0;
+    for (i, j) in lcs(old, new, eq)
+        .into_iter()
+        .chain([(old.len(), new.len())])
+    {
+        if old_start < i || new_start < j {
+            f(old_start..i, new_start..j);
+        }
+        old_start = i + 1;
+        new_start = j + 1;
+    }
+}
+
+/// Returns the indices of a longest common subsequence of `old` and `new` under `eq`.
+fn lcs<T>(old: &[T], new: &[T], eq: impl Fn(&T, &T) -> bool) -> Vec<(usize, usize)> {
+    // lengths[i][j] is the length of a longest common subsequence of old[i..] and new[j..].
+    let mut lengths = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if eq(&old[i], &new[j]) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if eq(&old[i], &new[j]) {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// Returns the path of the child at `index` of the element at `path`.
+fn child_path(path: &[usize], index: usize) -> Vec<usize> {
+    let mut path = path.to_vec();
+    path.push(index);
+    path
+}
+
+/// Returns the text of elements without markup.
+fn text(elements: &[MarkdownElement]) -> String {
+    elements
+        .iter()
+        .map(|element| match element {
+            MarkdownElement::Plain(x) => x.content().to_string(),
+            MarkdownElement::OneLineCode(x) => x.content().to_string(),
+            MarkdownElement::MultiLineCode(x) => x.content().to_string(),
+            element => element
+                .children()
+                .map_or_else(String::new, |c| text(c.get())),
+        })
+        .collect()
+}
+
+/// Returns the markdown text of elements.
+fn markdown(elements: &[MarkdownElement]) -> String {
+    elements.iter().map(|element| element.to_string()).collect()
+}
+
+/// Wraps text in inline code, using enough backticks to contain the text.
+fn quote(text: &str) -> String {
+    let mut longest = 0;
+    let mut current = 0;
+    for c in text.chars() {
+        current = if c == '`' { current + 1 } else { 0 };
+        longest = longest.max(current);
+    }
+
+    let fence = "`".repeat(longest + 1);
+    if longest == 0 {
+        format!("{}{}{}", fence, text, fence)
+    } else {
+        format!("{} {} {}", fence, text, fence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::parse;
+
+    #[test]
+    fn test_diff_equal() {
+        let ast = parse(include_str!("../tests/example.md"));
+        assert_eq!(diff(&ast, &ast), vec![]);
+    }
+
+    #[test]
+    fn test_diff_insert_and_delete() {
+        let old = MarkdownDocument::new(vec![plain("a"), bold("b"), one_line_code("c")]);
+        let new = MarkdownDocument::new(vec![bold("b"), one_line_code("c"), spoiler("d")]);
+
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                Change::Deleted {
+                    path: vec![0],
+                    element: &plain("a")
+                },
+                Change::Inserted {
+                    path: vec![2],
+                    element: &spoiler("d")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_restyled() {
+        let old = MarkdownDocument::new(vec![plain("a "), plain("foo")]);
+        let new = MarkdownDocument::new(vec![plain("a "), bold("foo")]);
+
+        assert_eq!(
+            diff(&old, &new),
+            vec![Change::Restyled {
+                old_path: vec![1],
+                old: &[plain("foo")],
+                new_path: vec![1],
+                new: &[bold("foo")],
+            }]
+        );
+
+        let old = MarkdownDocument::new(vec![italics_star("x")]);
+        let new = MarkdownDocument::new(vec![italics_underscore("x")]);
+        assert_eq!(
+            diff(&old, &new),
+            vec![Change::Restyled {
+                old_path: vec![0],
+                old: &[italics_star("x")],
+                new_path: vec![0],
+                new: &[italics_underscore("x")],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_restyled_split() {
+        let old = parse("say foo");
+        let new = parse("say **foo**");
+
+        assert_eq!(
+            diff(&old, &new),
+            vec![Change::Restyled {
+                old_path: vec![0],
+                old: &[plain("say foo")],
+                new_path: vec![0],
+                new: &[plain("say "), bold("foo")],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_nested() {
+        let old = parse("||a **b** c||");
+        let new = parse("||a __b__ d||");
+
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                Change::Restyled {
+                    old_path: vec![0, 1],
+                    old: &[bold("b")],
+                    new_path: vec![0, 1],
+                    new: &[underline("b")],
+                },
+                Change::Deleted {
+                    path: vec![0, 2],
+                    element: &plain(" c")
+                },
+                Change::Inserted {
+                    path: vec![0, 2],
+                    element: &plain(" d")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_code_language() {
+        let old = MarkdownDocument::new(vec![multi_line_code("x", None)]);
+        let new = MarkdownDocument::new(vec![multi_line_code("x", Some("rs".to_string()))]);
+
+        assert_eq!(
+            diff(&old, &new),
+            vec![Change::Restyled {
+                old_path: vec![0],
+                old: &[multi_line_code("x", None)],
+                new_path: vec![0],
+                new: &[multi_line_code("x", Some("rs".to_string()))],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_empty() {
+        let empty = MarkdownDocument::new(vec![]);
+        let ast = MarkdownDocument::new(vec![plain("a")]);
+
+        assert_eq!(diff(&empty, &empty), vec![]);
+        assert_eq!(
+            diff(&empty, &ast),
+            vec![Change::Inserted {
+                path: vec![0],
+                element: &plain("a")
+            }]
+        );
+        assert_eq!(
+            diff(&ast, &empty),
+            vec![Change::Deleted {
+                path: vec![0],
+                element: &plain("a")
+            }]
+        );
+    }
+
+    #[test]
+    fn test_lcs() {
+        assert_eq!(
+            lcs(&[1, 2, 3], &[2, 3, 4], |a, b| a == b),
+            vec![(1, 0), (2, 1)]
+        );
+        assert_eq!(lcs(&[1, 2], &[], |a, b| a == b), vec![]);
+        assert_eq!(lcs(&[1, 2, 1], &[1], |a, b| a == b), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn test_change_to_string() {
+        assert_eq!(
+            Change::Inserted {
+                path: vec![0],
+                element: &bold("a")
+            }
+            .to_string(),
+            "inserted `**a**`"
+        );
+        assert_eq!(
+            Change::Deleted {
+                path: vec![0],
+                element: &one_line_code("a")
+            }
+            .to_string(),
+            "deleted `` `a` ``"
+        );
+        assert_eq!(
+            Change::Restyled {
+                old_path: vec![0],
+                old: &[plain("a b")],
+                new_path: vec![0],
+                new: &[plain("a "), spoiler("b")],
+            }
+            .to_string(),
+            "restyled `a b` from `a b` to `a ||b||`"
+        );
+    }
+}
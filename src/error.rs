@@ -0,0 +1,119 @@
+//! Error types
+//!
+//! [`error`](crate::error) module provides errors returned by fallible functions such as [`try_parse`](crate::try_parse)
+//! and [`Selector::parse`](crate::select::Selector::parse).
+
+use derive_more::Display;
+
+/// An error returned by [`try_parse`](crate::try_parse) and [`try_parse_with_option`](crate::try_parse_with_option).
+///
+/// # Example
+///
+/// ```
+/// use discord_md::error::ParseError;
+/// use discord_md::{try_parse_with_option, ParseOption};
+///
+/// let option = ParseOption::new().max_length(Some(5));
+///
+/// assert_eq!(
+///     try_parse_with_option("**too long**", &option),
+///     Err(ParseError::LimitExceeded { limit: 5, length: 12 })
+/// );
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Display)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The input is longer than [`ParseOption::max_length`](crate::ParseOption::max_length).
+    /// Both `limit` and `length` are measured in bytes.
+    #[display(fmt = "input length {} exceeds the limit of {} bytes", length, limit)]
+    LimitExceeded { limit: usize, length: usize },
+
+    /// The parser stopped at byte `offset` without consuming the whole input.
+    ///
+    /// Every string is a valid markdown document, so this error indicates a bug in the parser.
+    #[display(fmt = "parser stopped unexpectedly at byte offset {}", offset)]
+    Internal { offset: usize },
+}
+
+impl std::error::Error for ParseError {}
+
+/// An error returned when parsing a [`Selector`](crate::select::Selector).
+///
+/// # Example
+///
+/// ```
+/// use discord_md::error::SelectorError;
+/// use discord_md::select::Selector;
+///
+/// assert_eq!(
+///     Selector::parse("spoiler > boldd"),
+///     Err(SelectorError::UnknownKind { name: "boldd".to_string(), offset: 10 })
+/// );
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Display)]
+#[non_exhaustive]
+pub enum SelectorError {
+    /// The selector does not contain any element kind.
+    #[display(fmt = "selector is empty")]
+    Empty,
+
+    /// The name at byte `offset` is not a name of [`ElementKind`](crate::ast::ElementKind).
+    #[display(fmt = "unknown element kind `{}` at byte offset {}", name, offset)]
+    UnknownKind { name: String, offset: usize },
+
+    /// The `>` combinator at byte `offset` is not placed between two element kinds.
+    #[display(fmt = "unexpected `>` at byte offset {}", offset)]
+    UnexpectedCombinator { offset: usize },
+
+    /// The character at byte `offset` cannot be used in a selector.
+    #[display(fmt = "unexpected character `{}` at byte offset {}", character, offset)]
+    UnexpectedCharacter { character: char, offset: usize },
+}
+
+impl std::error::Error for SelectorError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_error_to_string() {
+        assert_eq!(
+            ParseError::LimitExceeded {
+                limit: 5,
+                length: 12
+            }
+            .to_string(),
+            "input length 12 exceeds the limit of 5 bytes"
+        );
+        assert_eq!(
+            ParseError::Internal { offset: 3 }.to_string(),
+            "parser stopped unexpectedly at byte offset 3"
+        );
+    }
+
+    #[test]
+    fn test_selector_error_to_string() {
+        assert_eq!(SelectorError::Empty.to_string(), "selector is empty");
+        assert_eq!(
+            SelectorError::UnknownKind {
+                name: "foo".to_string(),
+                offset: 2
+            }
+            .to_string(),
+            "unknown element kind `foo` at byte offset 2"
+        );
+        assert_eq!(
+            SelectorError::UnexpectedCombinator { offset: 0 }.to_string(),
+            "unexpected `>` at byte offset 0"
+        );
+        assert_eq!(
+            SelectorError::UnexpectedCharacter {
+                character: '.',
+                offset: 4
+            }
+            .to_string(),
+            "unexpected character `.` at byte offset 4"
+        );
+    }
+}
@@ -47,6 +47,21 @@ use crate::ast::{
 /// assert_eq!(ast.to_markdown_string(&ToMarkdownStringOption::new().omit_spoiler(true)), " text `code`");
 /// assert_eq!(ast.to_markdown_string(&ToMarkdownStringOption::new().omit_format(true).omit_one_line_code(true)), "spoiler text ");
 /// ```
+///
+/// Line endings can be normalised with [`line_ending()`](ToMarkdownStringOption::line_ending()):
+///
+/// ```
+/// use discord_md::ast::*;
+/// use discord_md::generate::{LineEnding, ToMarkdownString, ToMarkdownStringOption};
+///
+/// let ast = MarkdownDocument::new(vec![
+///     MarkdownElement::Plain(Box::new(Plain::new("windows\r\nand\nunix"))),
+/// ]);
+///
+/// assert_eq!(ast.to_markdown_string(&ToMarkdownStringOption::new()), "windows\r\nand\nunix");
+/// assert_eq!(ast.to_markdown_string(&ToMarkdownStringOption::new().line_ending(LineEnding::Lf)), "windows\nand\nunix");
+/// assert_eq!(ast.to_markdown_string(&ToMarkdownStringOption::new().line_ending(LineEnding::CrLf)), "windows\r\nand\r\nunix");
+/// ```
 #[derive(Default)]
 #[non_exhaustive]
 pub struct ToMarkdownStringOption {
@@ -61,6 +76,9 @@ pub struct ToMarkdownStringOption {
 
     /// Omit multiline code blocks from the output
     pub omit_multi_line_code: bool,
+
+    /// Line ending style of the output
+    pub line_ending: LineEnding,
 }
 
 impl ToMarkdownStringOption {
@@ -87,6 +105,47 @@ impl ToMarkdownStringOption {
         self.omit_multi_line_code = value;
         self
     }
+
+    pub fn line_ending(mut self, value: LineEnding) -> Self {
+        self.line_ending = value;
+        self
+    }
+
+    /// Returns the option for children, which keeps line endings as they are.
+    ///
+    /// Line endings are converted once over the whole output,
+    /// since a line ending may be split between nodes, like `\r` at the end of one and `\n` at the start of the next.
+    fn for_children(&self) -> Self {
+        Self {
+            line_ending: LineEnding::Preserve,
+            ..*self
+        }
+    }
+}
+
+/// Line ending style of the generated text.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum LineEnding {
+    /// Keep line endings as they are in the AST.
+    #[default]
+    Preserve,
+
+    /// Convert every line ending to `\n`.
+    Lf,
+
+    /// Convert every line ending to `\r\n`.
+    CrLf,
+}
+
+impl LineEnding {
+    /// Converts line endings in the text.
+    fn apply(self, text: &str) -> String {
+        match self {
+            LineEnding::Preserve => text.to_string(),
+            LineEnding::Lf => text.replace("\r\n", "\n"),
+            LineEnding::CrLf => text.replace("\r\n", "\n").replace('\n', "\r\n"),
+        }
+    }
 }
 
 /// A trait for converting a markdown component into a String.
@@ -105,10 +164,14 @@ impl ToMarkdownString for MarkdownDocument {
 impl ToMarkdownString for MarkdownElementCollection {
     /// Returns the content of the collection as markdown styled text.
     fn to_markdown_string(&self, option: &ToMarkdownStringOption) -> String {
-        self.get()
+        let children = option.for_children();
+        let content = self
+            .get()
             .iter()
-            .map(|c| c.to_markdown_string(option))
-            .collect::<String>()
+            .map(|c| c.to_markdown_string(&children))
+            .collect::<String>();
+
+        option.line_ending.apply(&content)
     }
 }
 
@@ -132,15 +195,17 @@ impl ToMarkdownString for MarkdownElement {
 
 impl ToMarkdownString for Plain {
     /// Returns the content of the plain text.
-    fn to_markdown_string(&self, _option: &ToMarkdownStringOption) -> String {
-        self.content().to_string()
+    fn to_markdown_string(&self, option: &ToMarkdownStringOption) -> String {
+        option.line_ending.apply(self.content())
     }
 }
 
 impl ToMarkdownString for ItalicsStar {
     /// Returns the content of italics text as markdown styled text.
     fn to_markdown_string(&self, option: &ToMarkdownStringOption) -> String {
-        let content = self.content().to_markdown_string(option);
+        let content = option
+            .line_ending
+            .apply(&self.content().to_markdown_string(&option.for_children()));
 
         if option.omit_format {
             content
@@ -153,7 +218,9 @@ impl ToMarkdownString for ItalicsStar {
 impl ToMarkdownString for ItalicsUnderscore {
     /// Returns the content of italics text as markdown styled text.
     fn to_markdown_string(&self, option: &ToMarkdownStringOption) -> String {
-        let content = self.content().to_markdown_string(option);
+        let content = option
+            .line_ending
+            .apply(&self.content().to_markdown_string(&option.for_children()));
 
         if option.omit_format {
             content
@@ -166,7 +233,9 @@ impl ToMarkdownString for ItalicsUnderscore {
 impl ToMarkdownString for Bold {
     /// Returns the content of bold text as markdown styled text.
     fn to_markdown_string(&self, option: &ToMarkdownStringOption) -> String {
-        let content = self.content().to_markdown_string(option);
+        let content = option
+            .line_ending
+            .apply(&self.content().to_markdown_string(&option.for_children()));
 
         if option.omit_format {
             content
@@ -179,7 +248,9 @@ impl ToMarkdownString for Bold {
 impl ToMarkdownString for Underline {
     /// Returns the content of underline text as markdown styled text.
     fn to_markdown_string(&self, option: &ToMarkdownStringOption) -> String {
-        let content = self.content().to_markdown_string(option);
+        let content = option
+            .line_ending
+            .apply(&self.content().to_markdown_string(&option.for_children()));
 
         if option.omit_format {
             content
@@ -192,7 +263,9 @@ impl ToMarkdownString for Underline {
 impl ToMarkdownString for Strikethrough {
     /// Returns the content of strikethrough text as markdown styled text.
     fn to_markdown_string(&self, option: &ToMarkdownStringOption) -> String {
-        let content = self.content().to_markdown_string(option);
+        let content = option
+            .line_ending
+            .apply(&self.content().to_markdown_string(&option.for_children()));
 
         if option.omit_format {
             content
@@ -205,7 +278,9 @@ impl ToMarkdownString for Strikethrough {
 impl ToMarkdownString for Spoiler {
     /// Returns the content of spoiler text as markdown styled text.
     fn to_markdown_string(&self, option: &ToMarkdownStringOption) -> String {
-        let content = self.content().to_markdown_string(option);
+        let content = option
+            .line_ending
+            .apply(&self.content().to_markdown_string(&option.for_children()));
 
         if option.omit_spoiler {
             "".to_string()
@@ -220,7 +295,7 @@ impl ToMarkdownString for Spoiler {
 impl ToMarkdownString for OneLineCode {
     /// Returns the content of the inline code as markdown styled text.
     fn to_markdown_string(&self, option: &ToMarkdownStringOption) -> String {
-        let content = self.content().to_string();
+        let content = option.line_ending.apply(self.content());
 
         if option.omit_one_line_code {
             "".to_string()
@@ -235,7 +310,7 @@ impl ToMarkdownString for OneLineCode {
 impl ToMarkdownString for MultiLineCode {
     /// Returns the content of the multiline code block as markdown styled text.
     fn to_markdown_string(&self, option: &ToMarkdownStringOption) -> String {
-        let content = self.content().to_string();
+        let content = option.line_ending.apply(self.content());
 
         if option.omit_multi_line_code {
             "".to_string()
@@ -250,11 +325,14 @@ impl ToMarkdownString for MultiLineCode {
 impl ToMarkdownString for BlockQuote {
     /// Returns the content of the block quote as markdown styled text.
     fn to_markdown_string(&self, option: &ToMarkdownStringOption) -> String {
-        let content = self.content().to_markdown_string(option);
+        let content = option
+            .line_ending
+            .apply(&self.content().to_markdown_string(&option.for_children()));
 
         if option.omit_format {
             content
         } else {
+            // With `\r\n`, `\r` stays at the end of each line, so the prefix is still placed at the line start.
             content
                 .split('\n')
                 .map(|line| format!("> {}", line))
@@ -509,6 +587,85 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_line_ending() {
+        let ast = MarkdownDocument::new(MarkdownElementCollection::new(vec![
+            MarkdownElement::Plain(Box::new(Plain::new("a\r\nb\n"))),
+            MarkdownElement::MultiLineCode(Box::new(MultiLineCode::new(
+                "\r\ncode\n",
+                Some("js".to_string()),
+            ))),
+        ]));
+
+        assert_eq!(
+            ast.to_markdown_string(&option_default()),
+            "a\r\nb\n```js\r\ncode\n```"
+        );
+        assert_eq!(
+            ast.to_markdown_string(&option_default().line_ending(LineEnding::Preserve)),
+            "a\r\nb\n```js\r\ncode\n```"
+        );
+        assert_eq!(
+            ast.to_markdown_string(&option_default().line_ending(LineEnding::Lf)),
+            "a\nb\n```js\ncode\n```"
+        );
+        assert_eq!(
+            ast.to_markdown_string(&option_default().line_ending(LineEnding::CrLf)),
+            "a\r\nb\r\n```js\r\ncode\r\n```"
+        );
+    }
+
+    #[test]
+    fn test_line_ending_split_between_nodes() {
+        let ast = MarkdownDocument::new(MarkdownElementCollection::new(vec![
+            MarkdownElement::Plain(Box::new(Plain::new("a\r"))),
+            MarkdownElement::Plain(Box::new(Plain::new("\nb\r"))),
+            MarkdownElement::Plain(Box::new(Plain::new("\n"))),
+        ]));
+
+        assert_eq!(
+            ast.to_markdown_string(&option_default().line_ending(LineEnding::CrLf)),
+            "a\r\nb\r\n"
+        );
+        assert_eq!(
+            ast.to_markdown_string(&option_default().line_ending(LineEnding::Lf)),
+            "a\nb\n"
+        );
+
+        let bold = Bold::new(vec![
+            MarkdownElement::Plain(Box::new(Plain::new("a\r"))),
+            MarkdownElement::Plain(Box::new(Plain::new("\nb"))),
+        ]);
+        assert_eq!(
+            bold.to_markdown_string(&option_default().line_ending(LineEnding::CrLf)),
+            "**a\r\nb**"
+        );
+    }
+
+    #[test]
+    fn test_block_quote_crlf_to_string() {
+        let test_case = || {
+            MarkdownElementCollection::new(vec![MarkdownElement::Plain(Box::new(Plain::new(
+                "block quote\r\ntext\nhere",
+            )))])
+        };
+
+        assert_eq!(
+            BlockQuote::new(test_case()).to_markdown_string(&option_default()),
+            "> block quote\r\n> text\n> here"
+        );
+        assert_eq!(
+            BlockQuote::new(test_case())
+                .to_markdown_string(&option_default().line_ending(LineEnding::Lf)),
+            "> block quote\n> text\n> here"
+        );
+        assert_eq!(
+            BlockQuote::new(test_case())
+                .to_markdown_string(&option_default().line_ending(LineEnding::CrLf)),
+            "> block quote\r\n> text\r\n> here"
+        );
+    }
+
     #[test]
     fn test_block_quote_to_string() {
         let test_case = || {
@@ -0,0 +1,712 @@
+//! Converts between an AST and CommonMark / GitHub Flavored Markdown
+//!
+//! [`gfm`](crate::gfm) module provides [`ToGfm`] trait, which converts a markdown component to GFM text,
+//! and [`parse_gfm()`], which converts GFM text into an AST.
+//! Discord's markdown looks like CommonMark, but some syntax has a different meaning.
+//! For instance, `__x__` is bold text in CommonMark but underline text in Discord.
+//! Each element is mapped to the closest GFM construct.
+//!
+//! | Element | GFM |
+//! |---|---|
+//! | [`ItalicsStar`](crate::ast::ItalicsStar), [`ItalicsUnderscore`](crate::ast::ItalicsUnderscore) | `*x*` |
+//! | [`Bold`](crate::ast::Bold) | `**x**` |
+//! | [`Underline`](crate::ast::Underline) | `<u>x</u>` |
+//! | [`Strikethrough`](crate::ast::Strikethrough) | `~~x~~` |
+//! | [`Spoiler`](crate::ast::Spoiler) | See [`SpoilerFallback`] |
+//! | [`OneLineCode`](crate::ast::OneLineCode) | `` `x` `` |
+//! | [`MultiLineCode`] | fenced code block |
+//! | [`BlockQuote`](crate::ast::BlockQuote) | `> x` |
+//!
+//! Plain text is escaped per CommonMark rules, so that it is never interpreted as markup.
+//! A line break is converted to a hard line break (`\` at the end of the line),
+//! and consecutive line breaks are converted to a paragraph break.
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::gfm::{GfmOption, ToGfm};
+//! use discord_md::parse;
+//!
+//! let ast = parse("__under__ **bold** ||spoiler||\n1. not a list");
+//!
+//! assert_eq!(
+//!     ast.to_gfm(&GfmOption::new()),
+//!     "<u>under</u> **bold** \\|\\|spoiler\\|\\|\\\n1\\. not a list"
+//! );
+//! ```
+//!
+//! # Importing GFM
+//!
+//! [`parse_gfm()`] maps GFM constructs to Discord's markdown.
+//! Constructs that Discord does not support are degraded into readable text.
+//!
+//! | GFM | Discord |
+//! |---|---|
+//! | `*x*`, `_x_` | [`ItalicsStar`](crate::ast::ItalicsStar) |
+//! | `**x**`, `__x__` | [`Bold`](crate::ast::Bold) |
+//! | `~~x~~`, `~x~` | [`Strikethrough`](crate::ast::Strikethrough) |
+//! | `<u>x</u>`, `<ins>x</ins>` | [`Underline`](crate::ast::Underline) |
+//! | `` `x` `` | [`OneLineCode`](crate::ast::OneLineCode) |
+//! | fenced and indented code blocks | [`MultiLineCode`] |
+//! | `> x` | [`BlockQuote`](crate::ast::BlockQuote), whose nested block quotes are flattened |
+//! | headings | bold text |
+//! | `- x`, `1. x` | `• x`, `1. x`, indented by 2 spaces per nesting level |
+//! | `[label](url)` | `[label](url)`, Discord's masked link |
+//! | `<url>` | `url` |
+//! | `![alt](url)` | `alt (url)` |
+//! | tables | rows with cells separated by ` \| `, whose header cells are bold |
+//! | `---` | `---` |
+//! | other HTML tags | removed, keeping their content |
+//!
+//! ```
+//! use discord_md::gfm::parse_gfm;
+//!
+//! let ast = parse_gfm("## Install\n\nRun `cargo add discord-md`, see [docs](https://docs.rs/discord-md).");
+//!
+//! assert_eq!(
+//!     ast.to_string(),
+//!     "**Install**\n\nRun `cargo add discord-md`, see [docs](https://docs.rs/discord-md)."
+//! );
+//! ```
+
+pub(crate) mod parser;
+
+use crate::ast::{MarkdownDocument, MarkdownElement, MarkdownElementCollection, MultiLineCode};
+use crate::generate::{ToMarkdownString, ToMarkdownStringOption};
+
+/// Struct that allows to alter [`to_gfm()`](ToGfm::to_gfm())'s behaviour.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::gfm::{GfmOption, SpoilerFallback, ToGfm};
+/// use discord_md::parse;
+///
+/// let ast = parse("the answer is ||42||");
+///
+/// assert_eq!(ast.to_gfm(&GfmOption::new()), "the answer is \\|\\|42\\|\\|");
+/// assert_eq!(ast.to_gfm(&GfmOption::new().spoiler(SpoilerFallback::Plain)), "the answer is 42");
+/// assert_eq!(ast.to_gfm(&GfmOption::new().spoiler(SpoilerFallback::Omit)), "the answer is ");
+/// ```
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct GfmOption {
+    /// How spoilers are converted, since GFM does not have spoilers
+    pub spoiler: SpoilerFallback,
+}
+
+impl GfmOption {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn spoiler(mut self, value: SpoilerFallback) -> Self {
+        self.spoiler = value;
+        self
+    }
+}
+
+/// How spoilers are converted by [`to_gfm()`](ToGfm::to_gfm()).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum SpoilerFallback {
+    /// Keep the content surrounded by literal `||`, so that readers can tell it was a spoiler.
+    #[default]
+    Marker,
+
+    /// Keep the content without any indication.
+    Plain,
+
+    /// Remove the spoiler and its content.
+    Omit,
+}
+
+/// Parses CommonMark / GFM text into an AST of Discord's markdown.
+///
+/// Every string is a valid GFM document, so this function never fails.
+/// See [the module documentation](crate::gfm#importing-gfm) for how constructs are mapped.
+///
+/// # Limitations
+///
+/// - Link reference definitions must fit in one line.
+/// - HTML blocks are parsed as paragraphs.
+/// - Emphasis follows a simplified version of CommonMark's delimiter rules.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::gfm::parse_gfm;
+///
+/// let ast = parse_gfm("- **bold** item\n  1. nested\n\n| a | b |\n|---|---|\n| 1 | 2 |");
+///
+/// assert_eq!(ast.to_string(), "• **bold** item\n  1. nested\n\n**a** | **b**\n1 | 2");
+/// ```
+pub fn parse_gfm(text: &str) -> MarkdownDocument {
+    parser::document(text)
+}
+
+/// A trait for converting a markdown component into GFM text.
+pub trait ToGfm {
+    /// Returns the component as CommonMark / GitHub Flavored Markdown text.
+    fn to_gfm(&self, option: &GfmOption) -> String;
+}
+
+impl ToGfm for MarkdownDocument {
+    fn to_gfm(&self, option: &GfmOption) -> String {
+        self.content().to_gfm(option)
+    }
+}
+
+impl ToGfm for MarkdownElementCollection {
+    fn to_gfm(&self, option: &GfmOption) -> String {
+        let mut renderer = Renderer::new(option);
+        renderer.elements(self.get());
+        renderer.output
+    }
+}
+
+impl ToGfm for MarkdownElement {
+    fn to_gfm(&self, option: &GfmOption) -> String {
+        let mut renderer = Renderer::new(option);
+        renderer.element(self);
+        renderer.output
+    }
+}
+
+/// Converts elements into GFM text.
+///
+/// Line breaks are not written immediately, because how they are written depends on what follows them.
+struct Renderer<'a> {
+    option: &'a GfmOption,
+    output: String,
+    /// Number of line breaks in plain text not written yet.
+    newlines: usize,
+    /// Separator required after the last block element, if the last written element is a block.
+    block_end: Option<&'static str>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(option: &'a GfmOption) -> Self {
+        Self {
+            option,
+            output: String::new(),
+            newlines: 0,
+            block_end: None,
+        }
+    }
+
+    fn elements(&mut self, elements: &[MarkdownElement]) {
+        for element in elements {
+            self.element(element);
+        }
+    }
+
+    fn element(&mut self, element: &MarkdownElement) {
+        match element {
+            MarkdownElement::Plain(x) => self.text(x.content()),
+            MarkdownElement::ItalicsStar(x) => self.styled(x.content(), "*", "em"),
+            MarkdownElement::ItalicsUnderscore(x) => self.styled(x.content(), "*", "em"),
+            MarkdownElement::Bold(x) => self.styled(x.content(), "**", "strong"),
+            MarkdownElement::Underline(x) => {
+                self.inline("<u>");
+                self.elements(x.content().get());
+                self.output.push_str("</u>");
+            }
+            MarkdownElement::Strikethrough(x) => self.styled(x.content(), "~~", "del"),
+            MarkdownElement::Spoiler(x) => match self.option.spoiler {
+                SpoilerFallback::Marker => {
+                    self.inline("\\|\\|");
+                    self.elements(x.content().get());
+                    self.output.push_str("\\|\\|");
+                }
+                SpoilerFallback::Plain => self.elements(x.content().get()),
+                SpoilerFallback::Omit => {}
+            },
+            MarkdownElement::OneLineCode(x) => {
+                let content = x.content().replace("\r\n", "\n");
+                let fence = "`".repeat(longest_run(&content, '`') + 1);
+                let padding = if needs_padding(&content) { " " } else { "" };
+                self.inline(&format!(
+                    "{}{}{}{}{}",
+                    fence, padding, content, padding, fence
+                ));
+            }
+            MarkdownElement::MultiLineCode(x) => {
+                self.start_block();
+                self.output.push_str(&fenced_code(x));
+                self.block_end = Some("\n");
+            }
+            MarkdownElement::BlockQuote(x) => {
+                let mut inner = Renderer::new(self.option);
+                inner.elements(x.content().get());
+
+                self.start_block();
+                let quoted = inner
+                    .output
+                    .split('\n')
+                    .map(|line| {
+                        if line.is_empty() {
+                            ">".to_string()
+                        } else {
+                            format!("> {}", line)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                self.output.push_str(&quoted);
+                // Without a blank line, the following text would continue the block quote.
+                self.block_end = Some("\n\n");
+            }
+        }
+    }
+
+    /// Writes emphasis. Content with whitespace at either end cannot be emphasis in CommonMark, so HTML is used instead.
+    fn styled(&mut self, content: &MarkdownElementCollection, delimiter: &str, tag: &str) {
+        let text = content.to_markdown_string(&ToMarkdownStringOption::new().omit_format(true));
+        if text.is_empty() {
+            return;
+        }
+
+        let (opener, closer) =
+            if text.starts_with(char::is_whitespace) || text.ends_with(char::is_whitespace) {
+                (format!("<{}>", tag), format!("</{}>", tag))
+            } else {
+                (delimiter.to_string(), delimiter.to_string())
+            };
+
+        self.inline(&opener);
+        self.elements(content.get());
+        self.output.push_str(&closer);
+    }
+
+    /// Writes plain text.
+    fn text(&mut self, text: &str) {
+        for (index, line) in text.split('\n').enumerate() {
+            if index > 0 {
+                self.newlines += 1;
+            }
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if !line.is_empty() {
+                self.flush();
+                let escaped = escape(line, self.at_line_start());
+                self.output.push_str(&escaped);
+            }
+        }
+    }
+
+    /// Writes inline markup after pending line breaks.
+    fn inline(&mut self, markup: &str) {
+        self.flush();
+        self.output.push_str(markup);
+    }
+
+    /// Writes pending line breaks before inline content.
+    fn flush(&mut self) {
+        let newlines = std::mem::take(&mut self.newlines);
+        match self.block_end.take() {
+            // The first line break after a block is the end of the line of the block.
+            Some(separator) => {
+                self.output.push_str(separator);
+                if newlines >= 2 && separator == "\n" {
+                    self.output.push('\n');
+                }
+            }
+            None if newlines == 1 => self.output.push_str("\\\n"),
+            None if newlines >= 2 => self.output.push_str("\n\n"),
+            None => {}
+        }
+    }
+
+    /// Moves to the start of a line before writing a block element.
+    fn start_block(&mut self) {
+        self.newlines = 0;
+        match self.block_end.take() {
+            Some(separator) => self.output.push_str(separator),
+            None if !self.output.is_empty() => self.output.push('\n'),
+            None => {}
+        }
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.output.is_empty() || self.output.ends_with('\n')
+    }
+}
+
+/// Returns a fenced code block, using a fence longer than any backtick run in the content.
+fn fenced_code(code: &MultiLineCode) -> String {
+    let content = code.content().replace("\r\n", "\n");
+    // The line break after the opening delimiter is a part of the fence in Discord.
+    let content = content.strip_prefix('\n').unwrap_or(&content);
+    let fence = "`".repeat((longest_run(content, '`') + 1).max(3));
+
+    let mut block = format!("{}{}\n{}", fence, code.language().unwrap_or(""), content);
+    if !content.is_empty() && !content.ends_with('\n') {
+        block.push('\n');
+    }
+    block.push_str(&fence);
+    block
+}
+
+/// Returns `true` if inline code needs a space inside the backticks to keep its content.
+fn needs_padding(content: &str) -> bool {
+    let all_spaces = content.chars().all(|c| c == ' ');
+    content.starts_with('`')
+        || content.ends_with('`')
+        || (!all_spaces && content.starts_with(' ') && content.ends_with(' '))
+}
+
+/// Returns the length of the longest run of the character.
+fn longest_run(text: &str, target: char) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for c in text.chars() {
+        current = if c == target { current + 1 } else { 0 };
+        longest = longest.max(current);
+    }
+    longest
+}
+
+/// Escapes a line of plain text so that it is not interpreted as markup.
+///
+/// Characters which start a block, such as `#` of headings and `1.` of ordered lists,
+/// are escaped only at the start of a line.
+fn escape(line: &str, at_line_start: bool) -> String {
+    let mut escaped = String::with_capacity(line.len());
+    let mut chars = line.char_indices().peekable();
+
+    if at_line_start {
+        // Indentation of 4 columns starts an indented code block, so its first character is encoded.
+        let indent = line
+            .chars()
+            .take_while(|&c| c == ' ' || c == '\t')
+            .fold(0, |column, c| {
+                if c == '\t' {
+                    column / 4 * 4 + 4
+                } else {
+                    column + 1
+                }
+            });
+        if indent >= 4 {
+            let (_, c) = chars.next().expect("the line starts with indentation");
+            escaped.push_str(if c == '\t' { "&#9;" } else { "&#32;" });
+        }
+
+        // Leading spaces do not change block markers.
+        while let Some(&(_, c)) = chars.peek() {
+            if c != ' ' {
+                break;
+            }
+            escaped.push(c);
+            chars.next();
+        }
+
+        let start = chars.peek().map_or(line.len(), |&(offset, _)| offset);
+        let rest = &line[start..];
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        if digits > 0 && (rest[digits..].starts_with('.') || rest[digits..].starts_with(')')) {
+            // An ordered list marker like `1.` or `1)`.
+            escaped.push_str(&rest[..digits]);
+            escaped.push('\\');
+            for _ in 0..digits {
+                chars.next();
+            }
+        } else if rest.starts_with(['#', '+', '-', '=']) {
+            escaped.push('\\');
+        }
+    }
+
+    for (offset, c) in chars {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '~' | '|' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            // `&` starts an entity reference only when followed by a name or `#`.
+            '&' if line[offset + 1..]
+                .starts_with(|c: char| c.is_ascii_alphanumeric() || c == '#') =>
+            {
+                escaped.push_str("\\&");
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::parse;
+
+    fn to_gfm(text: &str) -> String {
+        parse(text).to_gfm(&GfmOption::new())
+    }
+
+    #[test]
+    fn test_styles_to_gfm() {
+        assert_eq!(to_gfm("*a* _b_"), "*a* *b*");
+        assert_eq!(to_gfm("**a**"), "**a**");
+        assert_eq!(to_gfm("__a__"), "<u>a</u>");
+        assert_eq!(to_gfm("~~a~~"), "~~a~~");
+        assert_eq!(to_gfm("||a||"), "\\|\\|a\\|\\|");
+        assert_eq!(to_gfm("__*a* **b**__"), "<u>*a* **b**</u>");
+    }
+
+    #[test]
+    fn test_styles_with_whitespace_to_gfm() {
+        assert_eq!(
+            MarkdownDocument::new(vec![bold(" a ")]).to_gfm(&GfmOption::new()),
+            "<strong> a </strong>"
+        );
+        assert_eq!(
+            MarkdownDocument::new(vec![italics_star("a "), strikethrough(" b")])
+                .to_gfm(&GfmOption::new()),
+            "<em>a </em><del> b</del>"
+        );
+        assert_eq!(
+            MarkdownDocument::new(vec![plain("a"), bold(vec![]), plain("b")])
+                .to_gfm(&GfmOption::new()),
+            "ab"
+        );
+    }
+
+    #[test]
+    fn test_spoiler_to_gfm() {
+        let ast = parse("a ||**b**|| c");
+
+        assert_eq!(ast.to_gfm(&GfmOption::new()), "a \\|\\|**b**\\|\\| c");
+        assert_eq!(
+            ast.to_gfm(&GfmOption::new().spoiler(SpoilerFallback::Plain)),
+            "a **b** c"
+        );
+        assert_eq!(
+            ast.to_gfm(&GfmOption::new().spoiler(SpoilerFallback::Omit)),
+            "a  c"
+        );
+    }
+
+    #[test]
+    fn test_one_line_code_to_gfm() {
+        assert_eq!(to_gfm("`a*b`"), "`a*b`");
+        assert_eq!(
+            MarkdownDocument::new(vec![one_line_code("a`b")]).to_gfm(&GfmOption::new()),
+            "``a`b``"
+        );
+        assert_eq!(
+            MarkdownDocument::new(vec![one_line_code("`a")]).to_gfm(&GfmOption::new()),
+            "`` `a ``"
+        );
+        assert_eq!(
+            MarkdownDocument::new(vec![one_line_code(" a ")]).to_gfm(&GfmOption::new()),
+            "`  a  `"
+        );
+        assert_eq!(
+            MarkdownDocument::new(vec![one_line_code(" ")]).to_gfm(&GfmOption::new()),
+            "` `"
+        );
+    }
+
+    #[test]
+    fn test_multi_line_code_to_gfm() {
+        assert_eq!(
+            to_gfm("```rs\nfn main() {}\n```"),
+            "```rs\nfn main() {}\n```"
+        );
+        assert_eq!(to_gfm("```code```"), "```\ncode\n```");
+        assert_eq!(
+            MarkdownDocument::new(vec![
+                plain("before"),
+                multi_line_code("\n````\n", None),
+                plain("after"),
+            ])
+            .to_gfm(&GfmOption::new()),
+            "before\n`````\n````\n`````\nafter"
+        );
+        assert_eq!(to_gfm("```a```\n\nb"), "```\na\n```\n\nb");
+    }
+
+    #[test]
+    fn test_block_quote_to_gfm() {
+        let ast = MarkdownDocument::new(vec![
+            plain("said:"),
+            block_quote(vec![plain("a\n\n"), bold("b")]),
+            plain("reply"),
+        ]);
+
+        assert_eq!(
+            ast.to_gfm(&GfmOption::new()),
+            "said:\n> a\n>\n> **b**\n\nreply"
+        );
+    }
+
+    #[test]
+    fn test_line_breaks_to_gfm() {
+        assert_eq!(to_gfm("a\nb"), "a\\\nb");
+        assert_eq!(to_gfm("a\r\nb"), "a\\\nb");
+        assert_eq!(to_gfm("a\n\n\nb"), "a\n\nb");
+        assert_eq!(to_gfm("a\n"), "a");
+        assert_eq!(to_gfm("**a\nb**"), "**a\\\nb**");
+        assert_eq!(to_gfm("*a\n*b"), "<em>a</em>\\\nb");
+    }
+
+    #[test]
+    fn test_escape_to_gfm() {
+        assert_eq!(
+            to_gfm("# not a heading\n- not a list\n2) no"),
+            "\\# not a heading\\\n\\- not a list\\\n2\\) no"
+        );
+        assert_eq!(
+            to_gfm("a <b> [c](d) \\ &amp; & | e"),
+            "a \\<b\\> \\[c\\](d) \\\\ \\&amp; & \\| e"
+        );
+
+        let ast = MarkdownDocument::new(vec![plain("    a\n\n    b")]);
+        let gfm = ast.to_gfm(&GfmOption::new());
+        assert_eq!(gfm, "&#32;   a\n\n&#32;   b");
+        assert_eq!(
+            parse_gfm(&gfm),
+            MarkdownDocument::new(vec![plain("    a\n\n    b")])
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("", true), "");
+        assert_eq!(escape("# a", true), "\\# a");
+        assert_eq!(escape("# a", false), "# a");
+        assert_eq!(escape("  + a", true), "  \\+ a");
+        assert_eq!(escape("10. a", true), "10\\. a");
+        assert_eq!(escape("10 a", true), "10 a");
+        assert_eq!(escape("===", true), "\\===");
+        assert_eq!(escape("   a", true), "   a");
+        assert_eq!(escape("    a", true), "&#32;   a");
+        assert_eq!(escape("     # a", true), "&#32;    \\# a");
+        assert_eq!(escape("  \ta", true), "&#32; \ta");
+        assert_eq!(escape("\ta", true), "&#9;a");
+        assert_eq!(escape("    a", false), "    a");
+        assert_eq!(escape("*_`~|", false), "\\*\\_\\`\\~\\|");
+        assert_eq!(escape("&#123; &x", false), "\\&#123; \\&x");
+    }
+
+    #[test]
+    fn test_longest_run() {
+        assert_eq!(longest_run("", '`'), 0);
+        assert_eq!(longest_run("a`b``c", '`'), 2);
+    }
+
+    #[test]
+    fn test_parse_gfm_inlines() {
+        assert_eq!(
+            parse_gfm("*a* _b_ **c** __d__ ~~e~~ <u>f</u> `g`"),
+            MarkdownDocument::new(vec![
+                italics_star("a"),
+                plain(" "),
+                italics_star("b"),
+                plain(" "),
+                bold("c"),
+                plain(" "),
+                bold("d"),
+                plain(" "),
+                strikethrough("e"),
+                plain(" "),
+                underline("f"),
+                plain(" "),
+                one_line_code("g"),
+            ])
+        );
+        assert_eq!(
+            parse_gfm("soft\nbreak  \nhard\\\nhard \\* &amp; &#35;"),
+            MarkdownDocument::new(vec![plain("soft break\nhard\nhard * & #")])
+        );
+    }
+
+    #[test]
+    fn test_parse_gfm_links() {
+        assert_eq!(
+            parse_gfm("[**docs**](https://a.example) <https://b.example> [c][ref] [https://d](https://d)\n\n[ref]: https://c.example")
+                .to_string(),
+            "[**docs**](https://a.example) https://b.example [c](https://c.example) https://d"
+        );
+        assert_eq!(
+            parse_gfm("![diagram](img.png) ![](img.png) [not a link]").to_string(),
+            "diagram (img.png) (img.png) [not a link]"
+        );
+    }
+
+    #[test]
+    fn test_parse_gfm_html() {
+        assert_eq!(
+            parse_gfm("<div align=\"center\"><b>a</b><br><em>b</em></div> <!-- c --><@123> <:e:1>")
+                .to_string(),
+            "**a**\n*b* <@123> <:e:1>"
+        );
+    }
+
+    #[test]
+    fn test_parse_gfm_blocks() {
+        let text = "# Title\n\nSub\n---\n\n```rust\nfn main() {}\n```\n\n    indented\n\n***";
+
+        assert_eq!(
+            parse_gfm(text),
+            MarkdownDocument::new(vec![
+                bold("Title"),
+                plain("\n\n"),
+                bold("Sub"),
+                plain("\n\n"),
+                multi_line_code("\nfn main() {}\n", Some("rust".to_string())),
+                plain("\n\n"),
+                multi_line_code("\nindented\n", None),
+                plain("\n\n---"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_gfm_tabs() {
+        assert_eq!(
+            parse_gfm("```\na\tb\n```"),
+            MarkdownDocument::new(vec![multi_line_code("\na\tb\n", None)])
+        );
+        assert_eq!(
+            parse_gfm("\tall:\n\t\tcc\tmain.c"),
+            MarkdownDocument::new(vec![multi_line_code("\nall:\n\tcc\tmain.c\n", None)])
+        );
+        assert_eq!(
+            parse_gfm("a\tb"),
+            MarkdownDocument::new(vec![plain("a\tb")])
+        );
+    }
+
+    #[test]
+    fn test_parse_gfm_block_quote() {
+        assert_eq!(
+            parse_gfm("> a\n>\n> > b\n\nc"),
+            MarkdownDocument::new(vec![block_quote("a\n\nb"), plain("\n\nc"),])
+        );
+        assert_eq!(parse_gfm("> quote\n\ntext").to_string(), "> quote\n\ntext");
+    }
+
+    #[test]
+    fn test_parse_gfm_lists() {
+        assert_eq!(
+            parse_gfm("* a\n* b\n\n  second paragraph\n  - c\n    1. d\n    2. e").to_string(),
+            "• a\n• b\n  second paragraph\n  • c\n    1. d\n    2. e"
+        );
+        assert_eq!(parse_gfm("3) a\n4) b").to_string(), "3. a\n4. b");
+    }
+
+    #[test]
+    fn test_parse_gfm_table() {
+        assert_eq!(
+            parse_gfm("| Name | *Value* |\n| :--- | ---: |\n| `a` | 1 |\n| b |  |").to_string(),
+            "**Name** | ***Value***\n`a` | 1\nb | "
+        );
+    }
+
+    #[test]
+    fn test_gfm_round_trip() {
+        let ast = parse("*a* **b** __c__ ~~d~~ `e` 1. [f](g) # h");
+        assert_eq!(parse_gfm(&ast.to_gfm(&GfmOption::new())), ast);
+    }
+}
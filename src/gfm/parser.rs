@@ -0,0 +1,1350 @@
+//! CommonMark / GFM parser
+//!
+//! The input is parsed in two passes, as CommonMark specifies:
+//! lines are first grouped into [`Block`]s, and then the text of each block is parsed into inline elements.
+//! Only the subset of the specification that can be mapped to Discord's markdown is supported.
+
+use crate::ast::{
+    BlockQuote, Bold, ItalicsStar, MarkdownDocument, MarkdownElement, MultiLineCode, OneLineCode,
+    Plain, Strikethrough, Underline,
+};
+use std::collections::HashMap;
+
+/// Parses a GFM document. See [`parse_gfm()`](super::parse_gfm()).
+pub fn document(text: &str) -> MarkdownDocument {
+    let lines: Vec<String> = text
+        .replace("\r\n", "\n")
+        .split('\n')
+        .map(String::from)
+        .collect();
+
+    let mut block_parser = BlockParser::default();
+    let blocks = block_parser.blocks(&lines);
+
+    let converter = Converter {
+        definitions: &block_parser.definitions,
+    };
+    MarkdownDocument::new(converter.blocks(&blocks, 0, false))
+}
+
+/// A block of a GFM document.
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Block {
+    Paragraph(String),
+    Heading(String),
+    Code {
+        language: Option<String>,
+        content: String,
+    },
+    Quote(Vec<Block>),
+    /// A list, whose `start` is `None` if it is a bullet list.
+    List {
+        start: Option<usize>,
+        items: Vec<Vec<Block>>,
+    },
+    /// A table, whose first row is the header.
+    Table(Vec<Vec<String>>),
+    ThematicBreak,
+}
+
+/// Groups lines into blocks, and collects link reference definitions.
+#[derive(Default)]
+struct BlockParser {
+    /// Destinations of link reference definitions, keyed by normalized labels.
+    definitions: HashMap<String, String>,
+}
+
+impl BlockParser {
+    fn blocks(&mut self, lines: &[String]) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        let mut i = 0;
+
+        while i < lines.len() {
+            let line = &lines[i];
+
+            if line.trim().is_empty() {
+                i += 1;
+            } else if let Some(fence) = fence_start(line) {
+                let mut content = Vec::new();
+                i += 1;
+                while i < lines.len() && !is_fence_end(&lines[i], &fence) {
+                    content.push(strip_indent(&lines[i], fence.indent));
+                    i += 1;
+                }
+                // Skip the closing fence. An unclosed block continues to the end of the document.
+                i += 1;
+
+                let language = fence
+                    .info
+                    .split_whitespace()
+                    .next()
+                    .filter(|language| language.chars().all(|c| c.is_ascii_alphanumeric()))
+                    .map(String::from);
+                blocks.push(Block::Code {
+                    language,
+                    content: content.join("\n"),
+                });
+            } else if let Some(text) = atx_heading(line) {
+                blocks.push(Block::Heading(text.to_string()));
+                i += 1;
+            } else if is_thematic_break(line) {
+                blocks.push(Block::ThematicBreak);
+                i += 1;
+            } else if quote_content(line).is_some() {
+                let mut content: Vec<String> = Vec::new();
+                while i < lines.len() {
+                    if let Some(text) = quote_content(&lines[i]) {
+                        content.push(text.to_string());
+                    } else if is_lazy_continuation(&lines[i], content.last()) {
+                        content.push(lines[i].clone());
+                    } else {
+                        break;
+                    }
+                    i += 1;
+                }
+                blocks.push(Block::Quote(self.blocks(&content)));
+            } else if let Some(marker) = list_marker(line) {
+                let mut items = Vec::new();
+                while let Some(item_marker) = lines.get(i).and_then(|line| list_marker(line)) {
+                    if !item_marker.same_list(&marker) {
+                        break;
+                    }
+
+                    let mut content = vec![item_marker.content.clone()];
+                    i += 1;
+                    while i < lines.len() {
+                        let line = &lines[i];
+                        if line.trim().is_empty() {
+                            content.push(String::new());
+                        } else if indent(line) >= item_marker.content_indent {
+                            content.push(strip_indent(line, item_marker.content_indent));
+                        } else if list_marker(line).is_none()
+                            && is_lazy_continuation(line, content.last())
+                        {
+                            content.push(line.trim_start().to_string());
+                        } else {
+                            break;
+                        }
+                        i += 1;
+                    }
+                    items.push(self.blocks(&content));
+                }
+                blocks.push(Block::List {
+                    start: marker.start,
+                    items,
+                });
+            } else if indent(line) >= 4 {
+                let mut content = Vec::new();
+                while i < lines.len() && (lines[i].trim().is_empty() || indent(&lines[i]) >= 4) {
+                    content.push(strip_indent(&lines[i], 4));
+                    i += 1;
+                }
+                while content.last().is_some_and(|line| line.trim().is_empty()) {
+                    content.pop();
+                }
+                blocks.push(Block::Code {
+                    language: None,
+                    content: content.join("\n"),
+                });
+            } else if line.contains('|')
+                && lines.get(i + 1).is_some_and(|next| {
+                    is_table_delimiter(next) && cells(next).len() == cells(line).len()
+                })
+            {
+                let mut rows = vec![cells(line)];
+                i += 2;
+                while i < lines.len() && !lines[i].trim().is_empty() && !interrupts(&lines[i]) {
+                    rows.push(cells(&lines[i]));
+                    i += 1;
+                }
+                blocks.push(Block::Table(rows));
+            } else {
+                let mut content = vec![line.trim_start()];
+                let mut heading = false;
+                i += 1;
+                while i < lines.len() {
+                    let line = &lines[i];
+                    if is_setext_underline(line) {
+                        heading = true;
+                        i += 1;
+                        break;
+                    }
+                    if line.trim().is_empty() || interrupts(line) {
+                        break;
+                    }
+                    content.push(line.trim_start());
+                    i += 1;
+                }
+
+                // Link reference definitions can only appear at the start of a paragraph.
+                let definitions = content
+                    .iter()
+                    .map_while(|line| link_reference_definition(line))
+                    .count();
+                for line in content.drain(..definitions) {
+                    if let Some((label, destination)) = link_reference_definition(line) {
+                        self.definitions.entry(label).or_insert(destination);
+                    }
+                }
+
+                if !content.is_empty() {
+                    let text = content.join("\n").trim_end().to_string();
+                    blocks.push(if heading {
+                        Block::Heading(text)
+                    } else {
+                        Block::Paragraph(text)
+                    });
+                }
+            }
+        }
+
+        blocks
+    }
+}
+
+/// An opening code fence.
+struct Fence<'a> {
+    character: char,
+    len: usize,
+    indent: usize,
+    info: &'a str,
+}
+
+/// A list item marker, like `-` or `1.`.
+struct ListMarker {
+    /// The number of an ordered list item, or `None` for a bullet list item.
+    start: Option<usize>,
+    /// The bullet character, or the delimiter after the number.
+    delimiter: char,
+    /// The indent of lines that belong to the item.
+    content_indent: usize,
+    /// The text after the marker.
+    content: String,
+}
+
+impl ListMarker {
+    fn same_list(&self, other: &ListMarker) -> bool {
+        self.start.is_some() == other.start.is_some() && self.delimiter == other.delimiter
+    }
+}
+
+/// Returns the width of the leading whitespace in columns, where a tab advances to the next multiple of 4.
+fn indent(line: &str) -> usize {
+    let mut column = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => column += 1,
+            '\t' => column = column / 4 * 4 + 4,
+            _ => break,
+        }
+    }
+    column
+}
+
+/// Removes up to `n` columns of leading whitespace.
+///
+/// A tab which is only partly removed is replaced with spaces for the rest of its columns.
+/// Tabs after the removed indent are kept as they are.
+fn strip_indent(line: &str, n: usize) -> String {
+    let mut column = 0;
+    for (offset, c) in line.char_indices() {
+        let next = match c {
+            ' ' => column + 1,
+            '\t' => column / 4 * 4 + 4,
+            _ => return line[offset..].to_string(),
+        };
+        if column == n {
+            return line[offset..].to_string();
+        }
+        if next > n {
+            return " ".repeat(next - n) + &line[offset + 1..];
+        }
+        column = next;
+    }
+    String::new()
+}
+
+/// Returns the line without indent, if the indent is at most 3 spaces.
+fn block_start(line: &str) -> Option<&str> {
+    (indent(line) <= 3).then(|| line.trim_start_matches(' '))
+}
+
+/// Returns the length of the run of the character at the start of the text.
+fn run_len(text: &str, character: char) -> usize {
+    text.len() - text.trim_start_matches(character).len()
+}
+
+/// Returns `true` if the line starts a block which can interrupt a paragraph.
+fn interrupts(line: &str) -> bool {
+    fence_start(line).is_some()
+        || atx_heading(line).is_some()
+        || is_thematic_break(line)
+        || quote_content(line).is_some()
+        || list_marker(line).is_some_and(|marker| {
+            !marker.content.trim().is_empty() && matches!(marker.start, None | Some(1))
+        })
+}
+
+/// Returns `true` if the line continues the paragraph of the previous line without its block marker.
+fn is_lazy_continuation(line: &str, previous: Option<&String>) -> bool {
+    !line.trim().is_empty()
+        && previous
+            .is_some_and(|previous| !previous.trim().is_empty() && fence_start(previous).is_none())
+        && !interrupts(line)
+}
+
+fn fence_start(line: &str) -> Option<Fence<'_>> {
+    let text = block_start(line)?;
+    let character = text.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let len = run_len(text, character);
+    let info = text[len..].trim();
+
+    (len >= 3 && !(character == '`' && info.contains('`'))).then(|| Fence {
+        character,
+        len,
+        indent: indent(line),
+        info,
+    })
+}
+
+fn is_fence_end(line: &str, fence: &Fence) -> bool {
+    block_start(line).is_some_and(|text| {
+        let len = run_len(text, fence.character);
+        len >= fence.len && text[len..].trim().is_empty()
+    })
+}
+
+/// Returns the text of an ATX heading, like `## text ##`.
+fn atx_heading(line: &str) -> Option<&str> {
+    let text = block_start(line)?;
+    let level = run_len(text, '#');
+    let rest = &text[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+
+    // The optional closing sequence must be preceded by a space.
+    let rest = rest.trim();
+    let without_closing = rest.trim_end_matches('#');
+    if without_closing.is_empty() {
+        Some(without_closing)
+    } else if without_closing.ends_with([' ', '\t']) {
+        Some(without_closing.trim_end())
+    } else {
+        Some(rest)
+    }
+}
+
+/// Returns `true` if the line is a thematic break, like `---` or `* * *`.
+fn is_thematic_break(line: &str) -> bool {
+    block_start(line).is_some_and(|text| {
+        ['-', '*', '_'].iter().any(|&character| {
+            text.chars().filter(|&c| c == character).count() >= 3
+                && text
+                    .chars()
+                    .all(|c| c == character || c == ' ' || c == '\t')
+        })
+    })
+}
+
+/// Returns `true` if the line turns the preceding paragraph into a heading, like `===`.
+fn is_setext_underline(line: &str) -> bool {
+    block_start(line).is_some_and(|text| {
+        let text = text.trim_end();
+        ['=', '-']
+            .iter()
+            .any(|&character| !text.is_empty() && text.chars().all(|c| c == character))
+    })
+}
+
+/// Returns the line without the block quote marker `>`.
+fn quote_content(line: &str) -> Option<&str> {
+    let text = block_start(line)?.strip_prefix('>')?;
+    Some(text.strip_prefix(' ').unwrap_or(text))
+}
+
+fn list_marker(line: &str) -> Option<ListMarker> {
+    let text = block_start(line)?;
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+
+    let (start, delimiter, marker_len) = if digits > 0 {
+        let delimiter = text[digits..]
+            .chars()
+            .next()
+            .filter(|&c| c == '.' || c == ')')?;
+        (Some(text[..digits].parse().ok()?), delimiter, digits + 1)
+    } else {
+        let bullet = text
+            .chars()
+            .next()
+            .filter(|&c| c == '-' || c == '+' || c == '*')?;
+        (None, bullet, 1)
+    };
+    if digits > 9 {
+        return None;
+    }
+
+    let rest = &text[marker_len..];
+    if !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+
+    // Content indented by 5 or more spaces is an indented code block, which starts 1 space after the marker.
+    let spaces = indent(rest);
+    let spaces = if (1..=4).contains(&spaces) && !rest.trim().is_empty() {
+        spaces
+    } else {
+        1
+    };
+
+    Some(ListMarker {
+        start,
+        delimiter,
+        content_indent: indent(line) + marker_len + spaces,
+        content: strip_indent(rest, spaces),
+    })
+}
+
+/// Returns `true` if the line is a delimiter row of a table, like `| --- | :-: |`.
+fn is_table_delimiter(line: &str) -> bool {
+    let cells = cells(line);
+    !cells.is_empty()
+        && cells.iter().all(|cell| {
+            let cell = cell.trim_start_matches(':').trim_end_matches(':');
+            !cell.is_empty() && cell.chars().all(|c| c == '-')
+        })
+}
+
+/// Splits a row of a table into cells at unescaped `|`.
+fn cells(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = if line.ends_with('|') && !line.ends_with("\\|") {
+        &line[..line.len() - 1]
+    } else {
+        line
+    };
+
+    let mut cells = vec![String::new()];
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cells.last_mut().unwrap().push('|');
+                chars.next();
+            }
+            '|' => cells.push(String::new()),
+            c => cells.last_mut().unwrap().push(c),
+        }
+    }
+    cells.iter().map(|cell| cell.trim().to_string()).collect()
+}
+
+/// Parses a link reference definition, like `[label]: https://example.com "title"`.
+fn link_reference_definition(line: &str) -> Option<(String, String)> {
+    let text = block_start(line)?.strip_prefix('[')?;
+    let (label, rest) = text.split_once("]:")?;
+    if label.trim().is_empty() || label.contains(['[', ']']) {
+        return None;
+    }
+
+    let rest = rest.trim_start();
+    let (destination, rest) = match rest.strip_prefix('<') {
+        Some(rest) => rest.split_once('>')?,
+        None => rest.split_at(rest.find(' ').unwrap_or(rest.len())),
+    };
+    let title = rest.trim();
+    let valid_title = title.is_empty()
+        || [('"', '"'), ('\'', '\''), ('(', ')')]
+            .iter()
+            .any(|&(open, close)| {
+                title.len() >= 2 && title.starts_with(open) && title.ends_with(close)
+            });
+
+    (!destination.is_empty() && valid_title)
+        .then(|| (normalize_label(label), destination.to_string()))
+}
+
+/// Normalizes a link label, so that labels match case-insensitively and regardless of whitespace.
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Converts blocks into Discord's markdown elements.
+struct Converter<'a> {
+    definitions: &'a HashMap<String, String>,
+}
+
+impl Converter<'_> {
+    /// Converts blocks separated by blank lines.
+    ///
+    /// `depth` is the nesting level of lists, and `quoted` is `true` inside a block quote,
+    /// since Discord supports neither nested lists nor nested block quotes.
+    fn blocks(&self, blocks: &[Block], depth: usize, quoted: bool) -> Vec<MarkdownElement> {
+        let mut output = Inlines::default();
+        for (index, block) in blocks.iter().enumerate() {
+            if index > 0 {
+                output.push_str("\n\n");
+            }
+            output.extend(self.block(block, depth, quoted));
+        }
+        output.finish()
+    }
+
+    fn block(&self, block: &Block, depth: usize, quoted: bool) -> Vec<MarkdownElement> {
+        match block {
+            Block::Paragraph(text) => self.inlines(text),
+            // Discord's markdown has no headings, so they are emphasized instead.
+            Block::Heading(text) if text.is_empty() => vec![],
+            Block::Heading(text) => vec![Bold::new(self.inlines(text)).into()],
+            Block::Code { language, content } => {
+                let content = if content.is_empty() {
+                    "\n".to_string()
+                } else {
+                    format!("\n{}\n", content)
+                };
+                vec![MultiLineCode::new(content, language.clone()).into()]
+            }
+            Block::Quote(blocks) if quoted => self.blocks(blocks, depth, true),
+            Block::Quote(blocks) => vec![BlockQuote::new(self.blocks(blocks, depth, true)).into()],
+            Block::List { start, items } => {
+                let mut output = Inlines::default();
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        output.push_str("\n");
+                    }
+                    output.push_str(&"  ".repeat(depth));
+                    match start {
+                        Some(start) => output.push_str(&format!("{}. ", start + index)),
+                        None => output.push_str("• "),
+                    }
+
+                    for (index, block) in item.iter().enumerate() {
+                        if let Block::List { .. } = block {
+                            output.push_str("\n");
+                            output.extend(self.block(block, depth + 1, quoted));
+                        } else {
+                            if index > 0 {
+                                output.push_str("\n");
+                                output.push_str(&"  ".repeat(depth + 1));
+                            }
+                            output.extend(self.block(block, depth, quoted));
+                        }
+                    }
+                }
+                output.finish()
+            }
+            // Tables are written row by row, with bold header cells.
+            Block::Table(rows) => {
+                let mut output = Inlines::default();
+                for (row_index, row) in rows.iter().enumerate() {
+                    if row_index > 0 {
+                        output.push_str("\n");
+                    }
+                    for (index, cell) in row.iter().enumerate() {
+                        if index > 0 {
+                            output.push_str(" | ");
+                        }
+                        if row_index == 0 && !cell.is_empty() {
+                            output.push(Bold::new(self.inlines(cell)).into());
+                        } else {
+                            output.extend(self.inlines(cell));
+                        }
+                    }
+                }
+                output.finish()
+            }
+            Block::ThematicBreak => vec![Plain::new("---").into()],
+        }
+    }
+
+    /// Parses inline elements in the text of a block.
+    fn inlines(&self, text: &str) -> Vec<MarkdownElement> {
+        let mut output = Inlines::default();
+        let mut pos = 0;
+
+        while let Some(c) = text[pos..].chars().next() {
+            let rest = &text[pos..];
+            let parsed = match c {
+                '\\' => match rest[1..].chars().next() {
+                    Some('\n') => {
+                        output.push_str("\n");
+                        Some(2)
+                    }
+                    Some(next) if next.is_ascii_punctuation() => {
+                        output.push_char(next);
+                        Some(1 + next.len_utf8())
+                    }
+                    _ => None,
+                },
+                '`' => {
+                    let len = match code_span(rest) {
+                        Some((content, len)) => {
+                            output.push(OneLineCode::new(content).into());
+                            len
+                        }
+                        // A backtick run without a closing run is plain text as a whole.
+                        None => {
+                            let len = run_len(rest, '`');
+                            output.push_str(&rest[..len]);
+                            len
+                        }
+                    };
+                    Some(len)
+                }
+                '*' | '_' | '~' => {
+                    let len = match self.emphasis(text, pos, c) {
+                        Some((element, len)) => {
+                            output.extend(element);
+                            len
+                        }
+                        None => {
+                            let len = run_len(rest, c);
+                            output.push_str(&rest[..len]);
+                            len
+                        }
+                    };
+                    Some(len)
+                }
+                '!' if rest[1..].starts_with('[') => {
+                    link(rest, 1, self.definitions).map(|(alt, destination, len)| {
+                        // Images are replaced with their description and URL.
+                        let alt = self.inlines(alt);
+                        if !alt.is_empty() {
+                            output.extend(alt);
+                            output.push_str(" ");
+                        }
+                        output.push_str(&format!("({})", destination));
+                        len
+                    })
+                }
+                '[' => link(rest, 0, self.definitions).map(|(label, destination, len)| {
+                    if label.is_empty() || label == destination {
+                        output.push_str(&destination);
+                    } else {
+                        output.push_str("[");
+                        output.extend(self.inlines(label));
+                        output.push_str(&format!("]({})", destination));
+                    }
+                    len
+                }),
+                '<' => self.angle_bracket(rest, &mut output),
+                '&' => entity(rest).map(|(decoded, len)| {
+                    output.push_char(decoded);
+                    len
+                }),
+                '\n' => {
+                    // A line ending after 2 or more spaces is a hard line break, which is a line break in Discord.
+                    // Otherwise, it is a soft line break, which is rendered as a space.
+                    let spaces = output.text.len() - output.text.trim_end_matches(' ').len();
+                    output.text.truncate(output.text.len() - spaces);
+                    output.push_str(if spaces >= 2 { "\n" } else { " " });
+                    Some(1)
+                }
+                _ => None,
+            };
+
+            match parsed {
+                Some(len) => pos += len,
+                None => {
+                    output.push_char(c);
+                    pos += c.len_utf8();
+                }
+            }
+        }
+
+        output.finish()
+    }
+
+    /// Parses emphasis or strikethrough starting at `pos`, and returns the elements and the consumed length.
+    ///
+    /// Delimiters are matched in a simplified way of CommonMark's rules:
+    /// an opening run must be followed by a non-whitespace character,
+    /// and a closing run must be preceded by a non-whitespace character.
+    fn emphasis(
+        &self,
+        text: &str,
+        pos: usize,
+        character: char,
+    ) -> Option<(Vec<MarkdownElement>, usize)> {
+        let run = run_len(&text[pos..], character);
+        if !can_open(text, pos, run, character) {
+            return None;
+        }
+
+        let strengths: &[usize] = match (character, run) {
+            ('~', 1) => &[1],
+            ('~', 2) => &[2],
+            ('~', _) => &[],
+            (_, 1) => &[1],
+            (_, 2) => &[2, 1],
+            _ => &[3, 2, 1],
+        };
+
+        strengths.iter().find_map(|&strength| {
+            let exact = character == '~';
+            let (inner_end, end) = find_closer(text, pos + run, character, strength, exact)?;
+            let inner = self.inlines(&text[pos + run..inner_end]);
+
+            let element: MarkdownElement = match (character, strength) {
+                ('~', _) => Strikethrough::new(inner).into(),
+                (_, 1) => ItalicsStar::new(inner).into(),
+                (_, 2) => Bold::new(inner).into(),
+                _ => Bold::new(vec![ItalicsStar::new(inner).into()]).into(),
+            };
+
+            // The unused part of the opening run is plain text.
+            let mut elements = Vec::new();
+            if run > strength {
+                elements.push(Plain::new(&text[pos..pos + run - strength]).into());
+            }
+            elements.push(element);
+            Some((elements, end - pos))
+        })
+    }
+
+    /// Parses an autolink like `<https://example.com>`, or an HTML tag, starting with `<`.
+    fn angle_bracket(&self, rest: &str, output: &mut Inlines) -> Option<usize> {
+        let end = rest.find('>')?;
+        let inside = &rest[1..end];
+
+        if is_autolink(inside) {
+            output.push_str(inside);
+            return Some(end + 1);
+        }
+        if rest.starts_with("<!--") {
+            return Some(rest.find("-->").map_or(rest.len(), |end| end + 3));
+        }
+
+        let name_len = inside
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .count();
+        let closing = inside.starts_with('/');
+        let name = inside.trim_start_matches('/')[..name_len].to_ascii_lowercase();
+        let after_name = &inside[usize::from(closing) + name_len..];
+        let is_tag = name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && (after_name.is_empty()
+                || after_name.starts_with([' ', '/', '\n'])
+                || after_name == "/")
+            && !inside.contains('<');
+        if !is_tag {
+            return None;
+        }
+
+        if name == "br" {
+            output.push_str("\n");
+            return Some(end + 1);
+        }
+
+        // Formatting tags are converted into elements. Other tags are removed, and their content is kept.
+        let closing_tag = format!("</{}>", name);
+        let content_end = (!closing)
+            .then(|| find_ignore_case(&rest[end + 1..], &closing_tag))
+            .flatten()
+            .map(|offset| end + 1 + offset);
+        let element = |inner| -> Option<MarkdownElement> {
+            match name.as_str() {
+                "em" | "i" => Some(ItalicsStar::new(self.inlines(inner)).into()),
+                "strong" | "b" => Some(Bold::new(self.inlines(inner)).into()),
+                "u" | "ins" => Some(Underline::new(self.inlines(inner)).into()),
+                "s" | "del" | "strike" => Some(Strikethrough::new(self.inlines(inner)).into()),
+                "code" => Some(OneLineCode::new(inner).into()),
+                _ => None,
+            }
+        };
+
+        match content_end.and_then(|content_end| {
+            element(&rest[end + 1..content_end]).map(|element| (element, content_end))
+        }) {
+            Some((element, content_end)) => {
+                output.push(element);
+                Some(content_end + closing_tag.len())
+            }
+            None => Some(end + 1),
+        }
+    }
+}
+
+/// Text and elements being parsed, which merges adjacent plain text into one element.
+#[derive(Default)]
+struct Inlines {
+    elements: Vec<MarkdownElement>,
+    text: String,
+}
+
+impl Inlines {
+    fn push_char(&mut self, c: char) {
+        self.text.push(c);
+    }
+
+    fn push_str(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+
+    fn push(&mut self, element: MarkdownElement) {
+        match element {
+            MarkdownElement::Plain(plain) => self.text.push_str(plain.content()),
+            element => {
+                self.flush();
+                self.elements.push(element);
+            }
+        }
+    }
+
+    fn extend(&mut self, elements: Vec<MarkdownElement>) {
+        for element in elements {
+            self.push(element);
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.text.is_empty() {
+            let text = std::mem::take(&mut self.text);
+            self.elements.push(Plain::new(text).into());
+        }
+    }
+
+    fn finish(mut self) -> Vec<MarkdownElement> {
+        self.flush();
+        self.elements
+    }
+}
+
+/// Returns `true` if a delimiter run at `pos` can open emphasis.
+fn can_open(text: &str, pos: usize, run: usize, character: char) -> bool {
+    let next = text[pos + run..].chars().next();
+    let previous = text[..pos].chars().next_back();
+
+    next.is_some_and(|c| !c.is_whitespace())
+        && !(character == '_' && previous.is_some_and(char::is_alphanumeric))
+}
+
+/// Returns `true` if a delimiter run at `pos` can close emphasis.
+fn can_close(text: &str, pos: usize, run: usize, character: char) -> bool {
+    let next = text[pos + run..].chars().next();
+    let previous = text[..pos].chars().next_back();
+
+    previous.is_some_and(|c| !c.is_whitespace())
+        && !(character == '_' && next.is_some_and(char::is_alphanumeric))
+}
+
+/// Finds a closing delimiter run of at least `strength` characters, or exactly `strength` if `exact`.
+///
+/// Returns the end of the content and the end of the closing delimiter.
+/// The closing delimiter is the first `strength` characters of the run,
+/// so that the rest of the run can close outer emphasis.
+/// Code spans, escaped characters and nested emphasis are skipped.
+fn find_closer(
+    text: &str,
+    start: usize,
+    character: char,
+    strength: usize,
+    exact: bool,
+) -> Option<(usize, usize)> {
+    let mut pos = start;
+
+    while let Some(c) = text[pos..].chars().next() {
+        let rest = &text[pos..];
+        if c == '\\' {
+            pos += 1 + rest[1..].chars().next().map_or(0, char::len_utf8);
+        } else if c == '`' {
+            pos += code_span(rest).map_or_else(|| run_len(rest, '`'), |(_, len)| len);
+        } else if c == character {
+            let run = run_len(rest, character);
+            let long_enough = if exact {
+                run == strength
+            } else {
+                run >= strength
+            };
+            if long_enough && can_close(text, pos, run, character) {
+                return Some((pos, pos + strength));
+            }
+
+            // A run which only opens starts nested emphasis, whose closing run must be skipped.
+            let nested = (!can_close(text, pos, run, character)
+                && can_open(text, pos, run, character))
+            .then(|| find_closer(text, pos + run, character, run.min(3), exact))
+            .flatten();
+            pos = nested.map_or(pos + run, |(_, end)| end);
+        } else {
+            pos += c.len_utf8();
+        }
+    }
+
+    None
+}
+
+/// Parses a code span starting with a backtick run, and returns the content and the consumed length.
+fn code_span(text: &str) -> Option<(String, usize)> {
+    let run = run_len(text, '`');
+    let mut pos = run;
+
+    while let Some(offset) = text[pos..].find('`') {
+        let start = pos + offset;
+        let len = run_len(&text[start..], '`');
+        if len == run {
+            let content = text[run..start].replace('\n', " ");
+            let content = if content.len() >= 2
+                && content.starts_with(' ')
+                && content.ends_with(' ')
+                && !content.chars().all(|c| c == ' ')
+            {
+                content[1..content.len() - 1].to_string()
+            } else {
+                content
+            };
+            return Some((content, start + len));
+        }
+        pos = start + len;
+    }
+
+    None
+}
+
+/// Parses a link starting with `[` at `offset`, and returns the label, the destination and the consumed length.
+///
+/// Inline links like `[label](url "title")` and reference links like `[label][ref]`, `[label][]` and `[label]` are supported.
+fn link<'a>(
+    text: &'a str,
+    offset: usize,
+    definitions: &HashMap<String, String>,
+) -> Option<(&'a str, String, usize)> {
+    let label_start = offset + 1;
+    let label_end = find_bracket_end(text, label_start)?;
+    let label = &text[label_start..label_end];
+    let rest = &text[label_end + 1..];
+
+    if let Some(inner) = rest.strip_prefix('(') {
+        if let Some((destination, len)) = inline_destination(inner) {
+            return Some((label, destination, label_end + 2 + len));
+        }
+    }
+
+    let (reference, len) = match rest.strip_prefix('[') {
+        Some(inner) => match inner.find(']') {
+            Some(0) => (label, 2),
+            Some(end) => (&inner[..end], end + 2),
+            None => (label, 0),
+        },
+        None => (label, 0),
+    };
+    let destination = definitions.get(&normalize_label(reference))?;
+    Some((label, destination.clone(), label_end + 1 + len))
+}
+
+/// Returns the position of `]` matching the `[` before `start`.
+fn find_bracket_end(text: &str, start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut pos = start;
+
+    while let Some(c) = text[pos..].chars().next() {
+        let rest = &text[pos..];
+        match c {
+            '\\' => pos += 1 + rest[1..].chars().next().map_or(0, char::len_utf8),
+            '`' => pos += code_span(rest).map_or_else(|| run_len(rest, '`'), |(_, len)| len),
+            '[' => {
+                depth += 1;
+                pos += 1;
+            }
+            ']' if depth == 0 => return Some(pos),
+            ']' => {
+                depth -= 1;
+                pos += 1;
+            }
+            c => pos += c.len_utf8(),
+        }
+    }
+
+    None
+}
+
+/// Parses the destination and the optional title of an inline link after `(`,
+/// and returns the destination and the consumed length including `)`.
+fn inline_destination(text: &str) -> Option<(String, usize)> {
+    let trimmed = text.trim_start();
+    let mut pos = text.len() - trimmed.len();
+
+    let destination = if let Some(inner) = trimmed.strip_prefix('<') {
+        let end = inner.find(['>', '\n'])?;
+        if !inner[end..].starts_with('>') {
+            return None;
+        }
+        pos += end + 2;
+        &inner[..end]
+    } else {
+        let mut depth = 0;
+        let end = trimmed
+            .char_indices()
+            .find(|&(_, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' if depth == 0 => return true,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                c.is_whitespace()
+            })
+            .map_or(trimmed.len(), |(end, _)| end);
+        pos += end;
+        &trimmed[..end]
+    };
+
+    let rest = text[pos..].trim_start();
+    pos = text.len() - rest.len();
+    if let Some(close) = [('"', '"'), ('\'', '\''), ('(', ')')]
+        .iter()
+        .find(|(open, _)| rest.starts_with(*open))
+        .map(|&(_, close)| close)
+    {
+        let end = rest[1..].find(close)?;
+        let rest = rest[end + 2..].trim_start();
+        pos = text.len() - rest.len();
+    }
+
+    text[pos..]
+        .starts_with(')')
+        .then(|| (destination.to_string(), pos + 1))
+}
+
+/// Returns `true` if the text between `<` and `>` is a URL or an email address.
+fn is_autolink(text: &str) -> bool {
+    if text.is_empty() || text.contains(|c: char| c.is_whitespace() || c == '<') {
+        return false;
+    }
+
+    match text.split_once(':') {
+        Some((scheme, _)) => {
+            (2..=32).contains(&scheme.len())
+                && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '.' || c == '-')
+        }
+        None => text.split_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty() && domain.contains('.') && !domain.contains('@')
+        }),
+    }
+}
+
+/// Decodes an entity reference starting with `&`, and returns the character and the consumed length.
+pub(crate) fn entity(text: &str) -> Option<(char, usize)> {
+    let end = text.find(';')?;
+    let name = &text[1..end];
+
+    let decoded = if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        char::from_u32(code)?
+    } else {
+        match name {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            "nbsp" => '\u{a0}',
+            "copy" => '©',
+            "reg" => '®',
+            "trade" => '™',
+            "hellip" => '…',
+            "mdash" => '—',
+            "ndash" => '–',
+            _ => return None,
+        }
+    };
+    Some((decoded, end + 1))
+}
+
+/// Finds a pattern in ASCII case-insensitive manner.
+fn find_ignore_case(text: &str, pattern: &str) -> Option<usize> {
+    text.char_indices()
+        .map(|(offset, _)| offset)
+        .find(|&offset| {
+            text.get(offset..offset + pattern.len())
+                .is_some_and(|candidate| candidate.eq_ignore_ascii_case(pattern))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+
+    fn parse_blocks(text: &str) -> Vec<Block> {
+        let lines: Vec<String> = text.split('\n').map(String::from).collect();
+        BlockParser::default().blocks(&lines)
+    }
+
+    fn paragraph(text: &str) -> Block {
+        Block::Paragraph(text.to_string())
+    }
+
+    #[test]
+    fn test_blocks() {
+        assert_eq!(
+            parse_blocks("# Title #\n\ntext\nmore\n\n---"),
+            vec![
+                Block::Heading("Title".to_string()),
+                paragraph("text\nmore"),
+                Block::ThematicBreak,
+            ]
+        );
+        assert_eq!(
+            parse_blocks("Title\n===\ntext"),
+            vec![Block::Heading("Title".to_string()), paragraph("text")]
+        );
+        assert_eq!(parse_blocks("#hashtag"), vec![paragraph("#hashtag")]);
+    }
+
+    #[test]
+    fn test_code_blocks() {
+        assert_eq!(
+            parse_blocks("```rust title\nfn main() {}\n\n```\n~~~\n```\n~~~"),
+            vec![
+                Block::Code {
+                    language: Some("rust".to_string()),
+                    content: "fn main() {}\n".to_string(),
+                },
+                Block::Code {
+                    language: None,
+                    content: "```".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            parse_blocks("    let a;\n\n      let b;\n\ntext"),
+            vec![
+                Block::Code {
+                    language: None,
+                    content: "let a;\n\n  let b;".to_string(),
+                },
+                paragraph("text"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tabs() {
+        assert_eq!(
+            parse_blocks("```\na\tb\n\tc\n```"),
+            vec![Block::Code {
+                language: None,
+                content: "a\tb\n\tc".to_string(),
+            }]
+        );
+        assert_eq!(
+            parse_blocks("\ta\tb\n  \t\tc"),
+            vec![Block::Code {
+                language: None,
+                content: "a\tb\n\tc".to_string(),
+            }]
+        );
+        assert_eq!(parse_blocks("a\tb"), vec![paragraph("a\tb")]);
+        assert_eq!(
+            parse_blocks("-\ta\tb"),
+            vec![Block::List {
+                start: None,
+                items: vec![vec![paragraph("a\tb")]],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_strip_indent() {
+        assert_eq!(indent("  \ta"), 4);
+        assert_eq!(indent("\t  a"), 6);
+        assert_eq!(strip_indent("\ta\tb", 4), "a\tb");
+        assert_eq!(strip_indent("\ta", 2), "  a");
+        assert_eq!(strip_indent(" \ta", 2), "  a");
+        assert_eq!(strip_indent("  a", 4), "a");
+        assert_eq!(strip_indent("\ta", 0), "\ta");
+    }
+
+    #[test]
+    fn test_quote_blocks() {
+        assert_eq!(
+            parse_blocks("> a\nlazy\n> > b\n\nc"),
+            vec![
+                Block::Quote(vec![
+                    paragraph("a\nlazy"),
+                    Block::Quote(vec![paragraph("b")]),
+                ]),
+                paragraph("c"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_blocks() {
+        assert_eq!(
+            parse_blocks("- a\n  - b\n- c\ncontinued\n\n3. d\n4) e"),
+            vec![
+                Block::List {
+                    start: None,
+                    items: vec![
+                        vec![
+                            paragraph("a"),
+                            Block::List {
+                                start: None,
+                                items: vec![vec![paragraph("b")]],
+                            },
+                        ],
+                        vec![paragraph("c\ncontinued")],
+                    ],
+                },
+                Block::List {
+                    start: Some(3),
+                    items: vec![vec![paragraph("d")]],
+                },
+                Block::List {
+                    start: Some(4),
+                    items: vec![vec![paragraph("e")]],
+                },
+            ]
+        );
+        assert_eq!(
+            parse_blocks("text\n2. not a list"),
+            vec![paragraph("text\n2. not a list")]
+        );
+    }
+
+    #[test]
+    fn test_table_blocks() {
+        assert_eq!(
+            parse_blocks("| a | b \\| c |\n|---|:-:|\n| 1 | 2 |\n\ntext"),
+            vec![
+                Block::Table(vec![
+                    vec!["a".to_string(), "b | c".to_string()],
+                    vec!["1".to_string(), "2".to_string()],
+                ]),
+                paragraph("text"),
+            ]
+        );
+        assert_eq!(
+            parse_blocks("a | b\n--- | ---"),
+            vec![Block::Table(vec![vec!["a".to_string(), "b".to_string()]])]
+        );
+        assert_eq!(
+            parse_blocks("a | b\n---"),
+            vec![Block::Heading("a | b".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_link_reference_definitions() {
+        let mut parser = BlockParser::default();
+        let lines = vec![
+            "[Docs]: https://example.com/docs \"Docs\"".to_string(),
+            "[b]: <https://example.com/b>".to_string(),
+            "text".to_string(),
+        ];
+
+        assert_eq!(parser.blocks(&lines), vec![paragraph("text")]);
+        assert_eq!(parser.definitions["docs"], "https://example.com/docs");
+        assert_eq!(parser.definitions["b"], "https://example.com/b");
+    }
+
+    #[test]
+    fn test_inlines() {
+        let definitions = HashMap::new();
+        let converter = Converter {
+            definitions: &definitions,
+        };
+
+        assert_eq!(
+            converter.inlines("a *b **c*** `d`"),
+            vec![
+                plain("a "),
+                italics_star(vec![plain("b "), bold("c")]),
+                plain(" "),
+                one_line_code("d"),
+            ]
+        );
+        assert_eq!(
+            converter.inlines("***a*** **b*"),
+            vec![bold(italics_star("a")), plain(" *"), italics_star("b"),]
+        );
+        assert_eq!(
+            converter.inlines("snake_case_name * a *"),
+            vec![plain("snake_case_name * a *")]
+        );
+        assert_eq!(
+            converter.inlines("~~a~~ ~b~ ~~c~"),
+            vec![
+                strikethrough("a"),
+                plain(" "),
+                strikethrough("b"),
+                plain(" ~~c~"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_code_span() {
+        assert_eq!(code_span("`a`"), Some(("a".to_string(), 3)));
+        assert_eq!(code_span("`` a`b ``c"), Some(("a`b".to_string(), 9)));
+        assert_eq!(code_span("`a\nb`"), Some(("a b".to_string(), 5)));
+        assert_eq!(code_span("`  `"), Some(("  ".to_string(), 4)));
+        assert_eq!(code_span("``a`"), None);
+    }
+
+    #[test]
+    fn test_link() {
+        let definitions = HashMap::from([("ref".to_string(), "https://r".to_string())]);
+
+        assert_eq!(
+            link("[a](https://a) b", 0, &definitions),
+            Some(("a", "https://a".to_string(), 14))
+        );
+        assert_eq!(
+            link("[a [b]](<https://a b> 'title')", 0, &definitions),
+            Some(("a [b]", "https://a b".to_string(), 30))
+        );
+        assert_eq!(
+            link("[a](https://a_(b))", 0, &definitions),
+            Some(("a", "https://a_(b)".to_string(), 18))
+        );
+        assert_eq!(
+            link("[a][Ref]", 0, &definitions),
+            Some(("a", "https://r".to_string(), 8))
+        );
+        assert_eq!(
+            link("[ref][] x", 0, &definitions),
+            Some(("ref", "https://r".to_string(), 7))
+        );
+        assert_eq!(
+            link("[ref]", 0, &definitions),
+            Some(("ref", "https://r".to_string(), 5))
+        );
+        assert_eq!(link("[a] (b)", 0, &definitions), None);
+        assert_eq!(link("[a", 0, &definitions), None);
+    }
+
+    #[test]
+    fn test_is_autolink() {
+        assert!(is_autolink("https://example.com"));
+        assert!(is_autolink("mailto:a@b.c"));
+        assert!(is_autolink("a@example.com"));
+        assert!(!is_autolink("@123"));
+        assert!(!is_autolink(":emoji:123"));
+        assert!(!is_autolink("a:emoji:123"));
+        assert!(!is_autolink("https://a b"));
+    }
+
+    #[test]
+    fn test_entity() {
+        assert_eq!(entity("&amp; x"), Some(('&', 5)));
+        assert_eq!(entity("&#65;"), Some(('A', 5)));
+        assert_eq!(entity("&#x1F44D;"), Some(('👍', 9)));
+        assert_eq!(entity("&unknown;"), None);
+        assert_eq!(entity("& x"), None);
+    }
+}
@@ -0,0 +1,371 @@
+//! Renders an AST to HTML
+//!
+//! [`html`](crate::html) module provides [`ToHtml`] trait, which renders a markdown component to HTML.
+//! Text, code, class names and languages are escaped, so the output is safe to embed in a web page
+//! even if the message contains HTML.
+//!
+//! | Element | HTML |
+//! |---|---|
+//! | [`ItalicsStar`], [`ItalicsUnderscore`] | `<em>` |
+//! | [`Bold`] | `<strong>` |
+//! | [`Underline`] | `<u>` |
+//! | [`Strikethrough`] | `<s>` |
+//! | [`Spoiler`] | `<span class="spoiler">` |
+//! | [`OneLineCode`] | `<code>` |
+//! | [`MultiLineCode`] | `<pre><code class="language-x">` |
+//! | [`BlockQuote`] | `<blockquote>` |
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::html::{HtmlOption, ToHtml};
+//! use discord_md::parse;
+//!
+//! let ast = parse("**bold** ||<script>||");
+//!
+//! assert_eq!(
+//!     ast.to_html(&HtmlOption::new()),
+//!     r#"<strong>bold</strong> <span class="spoiler">&lt;script&gt;</span>"#
+//! );
+//! ```
+
+use crate::ast::{
+    BlockQuote, Bold, ItalicsStar, ItalicsUnderscore, MarkdownDocument, MarkdownElement,
+    MarkdownElementCollection, MultiLineCode, OneLineCode, Plain, Spoiler, Strikethrough,
+    Underline,
+};
+
+/// Struct that allows to alter [`to_html()`](ToHtml::to_html())'s behaviour.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::builder::*;
+/// use discord_md::html::{HtmlOption, ToHtml};
+///
+/// let ast = spoiler("secret");
+/// assert_eq!(
+///     ast.to_html(&HtmlOption::new().spoiler_class("hidden")),
+///     r#"<span class="hidden">secret</span>"#
+/// );
+///
+/// let ast = multi_line_code("\nfn main() {}\n", Some("rust".to_string()));
+/// assert_eq!(
+///     ast.to_html(&HtmlOption::new().language_class_prefix("lang-")),
+///     "<pre><code class=\"lang-rust\">fn main() {}\n</code></pre>"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct HtmlOption {
+    /// Class name of spoilers, which is `spoiler` by default
+    pub spoiler_class: String,
+
+    /// Prefix of the class name of code blocks with language, which is `language-` by default
+    pub language_class_prefix: String,
+
+    /// Render line breaks in text as `<br>`, which is enabled by default
+    pub line_break: bool,
+}
+
+impl Default for HtmlOption {
+    fn default() -> Self {
+        Self {
+            spoiler_class: "spoiler".to_string(),
+            language_class_prefix: "language-".to_string(),
+            line_break: true,
+        }
+    }
+}
+
+impl HtmlOption {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn spoiler_class(mut self, value: impl Into<String>) -> Self {
+        self.spoiler_class = value.into();
+        self
+    }
+
+    pub fn language_class_prefix(mut self, value: impl Into<String>) -> Self {
+        self.language_class_prefix = value.into();
+        self
+    }
+
+    pub fn line_break(mut self, value: bool) -> Self {
+        self.line_break = value;
+        self
+    }
+}
+
+/// A trait for rendering a markdown component to HTML.
+pub trait ToHtml {
+    /// Returns the component as escaped HTML.
+    fn to_html(&self, option: &HtmlOption) -> String;
+}
+
+impl ToHtml for MarkdownDocument {
+    fn to_html(&self, option: &HtmlOption) -> String {
+        self.content().to_html(option)
+    }
+}
+
+impl ToHtml for MarkdownElementCollection {
+    fn to_html(&self, option: &HtmlOption) -> String {
+        self.get().iter().map(|c| c.to_html(option)).collect()
+    }
+}
+
+impl ToHtml for MarkdownElement {
+    fn to_html(&self, option: &HtmlOption) -> String {
+        match self {
+            MarkdownElement::Plain(x) => x.to_html(option),
+            MarkdownElement::ItalicsStar(x) => x.to_html(option),
+            MarkdownElement::ItalicsUnderscore(x) => x.to_html(option),
+            MarkdownElement::Bold(x) => x.to_html(option),
+            MarkdownElement::Underline(x) => x.to_html(option),
+            MarkdownElement::Strikethrough(x) => x.to_html(option),
+            MarkdownElement::Spoiler(x) => x.to_html(option),
+            MarkdownElement::OneLineCode(x) => x.to_html(option),
+            MarkdownElement::MultiLineCode(x) => x.to_html(option),
+            MarkdownElement::BlockQuote(x) => x.to_html(option),
+        }
+    }
+}
+
+impl ToHtml for Plain {
+    fn to_html(&self, option: &HtmlOption) -> String {
+        let text = escape(self.content());
+
+        if option.line_break {
+            text.replace("\r\n", "\n").replace('\n', "<br>\n")
+        } else {
+            text
+        }
+    }
+}
+
+impl ToHtml for ItalicsStar {
+    fn to_html(&self, option: &HtmlOption) -> String {
+        format!("<em>{}</em>", self.content().to_html(option))
+    }
+}
+
+impl ToHtml for ItalicsUnderscore {
+    fn to_html(&self, option: &HtmlOption) -> String {
+        format!("<em>{}</em>", self.content().to_html(option))
+    }
+}
+
+impl ToHtml for Bold {
+    fn to_html(&self, option: &HtmlOption) -> String {
+        format!("<strong>{}</strong>", self.content().to_html(option))
+    }
+}
+
+impl ToHtml for Underline {
+    fn to_html(&self, option: &HtmlOption) -> String {
+        format!("<u>{}</u>", self.content().to_html(option))
+    }
+}
+
+impl ToHtml for Strikethrough {
+    fn to_html(&self, option: &HtmlOption) -> String {
+        format!("<s>{}</s>", self.content().to_html(option))
+    }
+}
+
+impl ToHtml for Spoiler {
+    fn to_html(&self, option: &HtmlOption) -> String {
+        format!(
+            "<span class=\"{}\">{}</span>",
+            escape(&option.spoiler_class),
+            self.content().to_html(option)
+        )
+    }
+}
+
+impl ToHtml for OneLineCode {
+    fn to_html(&self, _option: &HtmlOption) -> String {
+        format!("<code>{}</code>", escape(self.content()))
+    }
+}
+
+impl ToHtml for MultiLineCode {
+    /// Renders the code block. The line break right after the opening delimiter is not rendered, as in Discord.
+    fn to_html(&self, option: &HtmlOption) -> String {
+        let content = self.content();
+        let content = content
+            .strip_prefix("\r\n")
+            .or_else(|| content.strip_prefix('\n'))
+            .unwrap_or(content);
+
+        match self.language() {
+            Some(language) => format!(
+                "<pre><code class=\"{}{}\">{}</code></pre>",
+                escape(&option.language_class_prefix),
+                escape(language),
+                escape(content)
+            ),
+            None => format!("<pre><code>{}</code></pre>", escape(content)),
+        }
+    }
+}
+
+impl ToHtml for BlockQuote {
+    fn to_html(&self, option: &HtmlOption) -> String {
+        format!(
+            "<blockquote>{}</blockquote>",
+            self.content().to_html(option)
+        )
+    }
+}
+
+/// Escapes characters that have special meanings in HTML text and attribute values.
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::parse;
+
+    fn option_default() -> HtmlOption {
+        HtmlOption::new()
+    }
+
+    #[test]
+    fn test_document_to_html() {
+        let ast = parse("plain *italics* _italics_ **bold** __under__ ~~strike~~ ||spoiler||");
+
+        assert_eq!(
+            ast.to_html(&option_default()),
+            "plain <em>italics</em> <em>italics</em> <strong>bold</strong> <u>under</u> <s>strike</s> <span class=\"spoiler\">spoiler</span>"
+        );
+    }
+
+    #[test]
+    fn test_nested_to_html() {
+        let ast = parse("__*nested* **styles**__");
+
+        assert_eq!(
+            ast.to_html(&option_default()),
+            "<u><em>nested</em> <strong>styles</strong></u>"
+        );
+    }
+
+    #[test]
+    fn test_plain_to_html() {
+        assert_eq!(
+            plain("a\nb\r\nc").to_html(&option_default()),
+            "a<br>\nb<br>\nc"
+        );
+        assert_eq!(
+            plain("a\nb").to_html(&option_default().line_break(false)),
+            "a\nb"
+        );
+    }
+
+    #[test]
+    fn test_one_line_code_to_html() {
+        assert_eq!(
+            one_line_code("**not bold**").to_html(&option_default()),
+            "<code>**not bold**</code>"
+        );
+    }
+
+    #[test]
+    fn test_multi_line_code_to_html() {
+        assert_eq!(
+            multi_line_code("\nfn main() {}\n", Some("rust".to_string()))
+                .to_html(&option_default()),
+            "<pre><code class=\"language-rust\">fn main() {}\n</code></pre>"
+        );
+        assert_eq!(
+            multi_line_code("\r\na\nb", None).to_html(&option_default()),
+            "<pre><code>a\nb</code></pre>"
+        );
+        assert_eq!(
+            multi_line_code("inline", None).to_html(&option_default()),
+            "<pre><code>inline</code></pre>"
+        );
+    }
+
+    #[test]
+    fn test_block_quote_to_html() {
+        assert_eq!(
+            block_quote(vec![plain("quote "), bold("text")]).to_html(&option_default()),
+            "<blockquote>quote <strong>text</strong></blockquote>"
+        );
+    }
+
+    #[test]
+    fn test_spoiler_class() {
+        assert_eq!(
+            spoiler("a").to_html(&option_default().spoiler_class("blur dark")),
+            "<span class=\"blur dark\">a</span>"
+        );
+    }
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(
+            parse("<script>alert('x')</script> & \"quoted\"").to_html(&option_default()),
+            "&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt; &amp; &quot;quoted&quot;"
+        );
+        assert_eq!(
+            parse("**<img src=x onerror=alert(1)>**").to_html(&option_default()),
+            "<strong>&lt;img src=x onerror=alert(1)&gt;</strong>"
+        );
+    }
+
+    #[test]
+    fn test_escape_code() {
+        assert_eq!(
+            one_line_code("</code><script>").to_html(&option_default()),
+            "<code>&lt;/code&gt;&lt;script&gt;</code>"
+        );
+        assert_eq!(
+            multi_line_code("\n</pre>", None).to_html(&option_default()),
+            "<pre><code>&lt;/pre&gt;</code></pre>"
+        );
+    }
+
+    #[test]
+    fn test_escape_attribute() {
+        assert_eq!(
+            multi_line_code("\nx", Some("\"><script>".to_string())).to_html(&option_default()),
+            "<pre><code class=\"language-&quot;&gt;&lt;script&gt;\">x</code></pre>"
+        );
+        assert_eq!(
+            spoiler("a").to_html(&option_default().spoiler_class("\" onclick=\"alert(1)")),
+            "<span class=\"&quot; onclick=&quot;alert(1)\">a</span>"
+        );
+        assert_eq!(
+            multi_line_code("\nx", Some("rs".to_string()))
+                .to_html(&option_default().language_class_prefix("'><b>")),
+            "<pre><code class=\"&#39;&gt;&lt;b&gt;rs\">x</code></pre>"
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape(""), "");
+        assert_eq!(escape("a&b<c>d\"e'f"), "a&amp;b&lt;c&gt;d&quot;e&#39;f");
+        assert_eq!(escape("&amp;"), "&amp;amp;");
+    }
+}
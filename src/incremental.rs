@@ -0,0 +1,350 @@
+//! Incremental re-parsing of edited documents
+//!
+//! [`incremental`](crate::incremental) module re-parses only the top-level elements affected by a text edit,
+//! instead of parsing the whole document again.
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::incremental::{reparse, TextEdit};
+//! use discord_md::parse;
+//!
+//! let previous = parse("**hello** world, `foo` bar");
+//!
+//! // Replace "world" with "everyone"
+//! let reparsed = reparse(previous, &TextEdit::new(10..15, "everyone")).unwrap();
+//!
+//! assert_eq!(reparsed.document(), &parse("**hello** everyone, `foo` bar"));
+//! assert_eq!(reparsed.changed(), 1..2);
+//! ```
+
+use crate::ast::{MarkdownDocument, MarkdownElement};
+use crate::error::ParseError;
+use crate::generate::{ToMarkdownString, ToMarkdownStringOption};
+use crate::parser;
+use std::ops::Range;
+
+/// Characters that may form a markdown delimiter.
+const DELIMITER_CHARS: [char; 5] = ['*', '_', '~', '|', '`'];
+
+/// A text edit, which replaces a byte range of the document with a new text.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TextEdit {
+    range: Range<usize>,
+    replacement: String,
+}
+
+impl TextEdit {
+    /// Creates a text edit that replaces `range` (byte offsets in the previous text) with `replacement`.
+    pub fn new(range: Range<usize>, replacement: impl Into<String>) -> Self {
+        Self {
+            range,
+            replacement: replacement.into(),
+        }
+    }
+
+    /// Returns the replaced byte range in the previous text.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Returns the text inserted in place of the range.
+    pub fn replacement(&self) -> &str {
+        &self.replacement
+    }
+}
+
+/// The result of [`reparse`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Reparsed {
+    document: MarkdownDocument,
+    changed: Range<usize>,
+    removed: Range<usize>,
+}
+
+impl Reparsed {
+    /// Returns the new document.
+    pub fn document(&self) -> &MarkdownDocument {
+        &self.document
+    }
+
+    /// Consumes the result and returns the new document.
+    pub fn into_document(self) -> MarkdownDocument {
+        self.document
+    }
+
+    /// Returns the indices of the re-parsed top-level elements in the new document.
+    pub fn changed(&self) -> Range<usize> {
+        self.changed.clone()
+    }
+
+    /// Returns the re-parsed top-level elements of the new document.
+    pub fn changed_elements(&self) -> &[MarkdownElement] {
+        &self.document.content().get()[self.changed()]
+    }
+
+    /// Returns the indices of the top-level elements in the previous document that were replaced.
+    pub fn removed(&self) -> Range<usize> {
+        self.removed.clone()
+    }
+}
+
+/// Returns the byte ranges of the top-level elements in the markdown text of the document.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::incremental::spans;
+/// use discord_md::parse;
+///
+/// assert_eq!(spans(&parse("**hello** world")), vec![0..9, 9..15]);
+/// ```
+pub fn spans(document: &MarkdownDocument) -> Vec<Range<usize>> {
+    let option = ToMarkdownStringOption::new();
+
+    let mut start = 0;
+    document
+        .content()
+        .get()
+        .iter()
+        .map(|element| {
+            let end = start + element.to_markdown_string(&option).len();
+            let span = start..end;
+            start = end;
+            span
+        })
+        .collect()
+}
+
+/// Applies a text edit to a parsed document and re-parses only the affected top-level elements.
+///
+/// `previous` must be the result of parsing the text before the edit, e.g. with [`parse`](crate::parse).
+/// Elements before and after the affected region are moved into the new document without being parsed again.
+///
+/// The result is always equal to parsing the edited text from scratch.
+/// If the edit inserts, removes or touches markdown delimiters (`*`, `_`, `~`, `|` and `` ` ``),
+/// the elements before the edit may change as well, so they are re-parsed too.
+///
+/// # Panics
+///
+/// Panics if the range of the edit is out of bounds or does not lie on `char` boundaries
+/// of the markdown text of `previous`.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::incremental::{reparse, TextEdit};
+/// use discord_md::parse;
+///
+/// let previous = parse("one *two* three");
+///
+/// // Append "!" to the last plain text
+/// let reparsed = reparse(previous, &TextEdit::new(15..15, "!")).unwrap();
+///
+/// assert_eq!(reparsed.document(), &parse("one *two* three!"));
+/// assert_eq!(reparsed.removed(), 2..3);
+/// assert_eq!(reparsed.changed(), 2..3);
+/// ```
+pub fn reparse(previous: MarkdownDocument, edit: &TextEdit) -> Result<Reparsed, ParseError> {
+    let old_text = previous.to_markdown_string(&ToMarkdownStringOption::new());
+    let old_spans = spans(&previous);
+
+    let mut new_text = old_text.clone();
+    new_text.replace_range(edit.range(), edit.replacement());
+
+    // The first top-level element touching the edit, or the number of elements if there are none.
+    let mut first = old_spans
+        .iter()
+        .position(|span| span.end >= edit.range.start)
+        .unwrap_or(old_spans.len());
+
+    // The end of a plain text is determined by the element after it.
+    let old_elements = previous.into_content().into_inner();
+    if first > 0 && matches!(old_elements[first - 1], MarkdownElement::Plain(_)) {
+        first -= 1;
+    }
+
+    // Elements before the edit may have looked ahead for closing delimiters.
+    if touches_delimiter(&old_text, edit) {
+        first = 0;
+    }
+
+    let start = old_spans
+        .get(first)
+        .map_or(old_text.len(), |span| span.start);
+    let edit_end = edit.range.start + edit.replacement.len();
+
+    // Parse elements until a boundary lines up with one in the previous document.
+    let mut parsed = Vec::new();
+    let mut position = start;
+    let mut resume = old_elements.len();
+    while position < new_text.len() {
+        if position >= edit_end {
+            let old_position = position + edit.range.len() - edit.replacement.len();
+            if let Some(index) = old_spans.iter().position(|span| span.start == old_position) {
+                resume = index;
+                break;
+            }
+        }
+
+        let input = &new_text[position..];
+        match parser::markdown_element(input) {
+            Ok((rest, element)) if rest.len() < input.len() => {
+                parsed.push(element);
+                position = new_text.len() - rest.len();
+            }
+            Ok(_) => return Err(ParseError::Internal { offset: position }),
+            Err(nom::Err::Error(err) | nom::Err::Failure(err)) => {
+                return Err(ParseError::Internal {
+                    offset: new_text.len() - err.input.len(),
+                })
+            }
+            Err(nom::Err::Incomplete(_)) => {
+                return Err(ParseError::Internal {
+                    offset: new_text.len(),
+                })
+            }
+        }
+    }
+
+    let changed = first..first + parsed.len();
+    let removed = first..resume;
+
+    let mut elements = old_elements;
+    elements.splice(removed.clone(), parsed);
+
+    Ok(Reparsed {
+        document: MarkdownDocument::new(elements),
+        changed,
+        removed,
+    })
+}
+
+/// Returns `true` if the edit inserts or removes a delimiter character, or is adjacent to one.
+fn touches_delimiter(old_text: &str, edit: &TextEdit) -> bool {
+    let before = old_text[..edit.range.start].chars().next_back();
+    let after = old_text[edit.range.end..].chars().next();
+
+    old_text[edit.range()]
+        .chars()
+        .chain(edit.replacement.chars())
+        .chain(before)
+        .chain(after)
+        .any(|c| DELIMITER_CHARS.contains(&c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::parse;
+
+    fn assert_reparse(old_text: &str, edit: TextEdit) {
+        let mut new_text = old_text.to_string();
+        new_text.replace_range(edit.range(), edit.replacement());
+
+        let reparsed = reparse(parse(old_text), &edit).unwrap();
+        assert_eq!(
+            reparsed.document(),
+            &parse(&new_text),
+            "{:?} with {:?}",
+            old_text,
+            edit
+        );
+    }
+
+    #[test]
+    fn test_spans() {
+        assert_eq!(spans(&parse("")), vec![]);
+        assert_eq!(
+            spans(&parse("a **b** `c` ```d```")),
+            vec![0..2, 2..7, 7..8, 8..11, 11..12, 12..19]
+        );
+    }
+
+    #[test]
+    fn test_reparse_plain() {
+        let reparsed = reparse(
+            parse("**hello** world `code`"),
+            &TextEdit::new(10..15, "there"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            reparsed.document(),
+            &MarkdownDocument::new(vec![bold("hello"), plain(" there "), one_line_code("code")])
+        );
+        assert_eq!(reparsed.removed(), 1..2);
+        assert_eq!(reparsed.changed(), 1..2);
+        assert_eq!(reparsed.changed_elements(), &[plain(" there ")]);
+    }
+
+    #[test]
+    fn test_reparse_inside_styled() {
+        let reparsed = reparse(
+            parse("a **bold** b ~~strike~~"),
+            &TextEdit::new(4..8, "BOLD"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            reparsed.into_document(),
+            MarkdownDocument::new(vec![
+                plain("a "),
+                bold("BOLD"),
+                plain(" b "),
+                strikethrough("strike")
+            ])
+        );
+    }
+
+    #[test]
+    fn test_reparse_delimiter() {
+        // Closing "**" turns the leading plain text into bold.
+        let reparsed = reparse(parse("**hello world"), &TextEdit::new(7..7, "**")).unwrap();
+
+        assert_eq!(
+            reparsed.document(),
+            &MarkdownDocument::new(vec![bold("hello"), plain(" world")])
+        );
+        assert_eq!(reparsed.removed(), 0..1);
+        assert_eq!(reparsed.changed(), 0..2);
+    }
+
+    #[test]
+    fn test_reparse_empty() {
+        assert_reparse("", TextEdit::new(0..0, "*new*"));
+        assert_reparse("*old*", TextEdit::new(0..5, ""));
+    }
+
+    #[test]
+    fn test_reparse_matches_full_parse() {
+        let texts = [
+            "__This **is** an *example* `markdown` document.__",
+            "a *b* c **d** e",
+            "**a __b** c__ ||d||",
+            "```js\nlet a = 1;\n``` and `code`",
+            "text with no style at all",
+            "*a* *b",
+        ];
+        let replacements = ["", "x", " ", "*", "**", "_", "`", "```", "||", "\n"];
+
+        for text in texts {
+            let boundaries = (0..=text.len()).filter(|&i| text.is_char_boundary(i));
+            for start in boundaries {
+                for end in (start..=text.len().min(start + 3)).filter(|&i| text.is_char_boundary(i))
+                {
+                    for replacement in replacements {
+                        assert_reparse(text, TextEdit::new(start..end, replacement));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_reparse_out_of_bounds() {
+        let _ = reparse(parse("text"), &TextEdit::new(2..10, ""));
+    }
+}
@@ -0,0 +1,638 @@
+//! Converts between an AST and IRC formatting codes
+//!
+//! [`irc`](crate::irc) module provides [`ToIrc`] trait, which converts a markdown component into text with mIRC formatting codes,
+//! and [`parse_irc()`], which converts IRC formatted text into an AST.
+//!
+//! | Element | Code |
+//! |---|---|
+//! | [`ItalicsStar`](crate::ast::ItalicsStar), [`ItalicsUnderscore`](crate::ast::ItalicsUnderscore) | `\x1D` |
+//! | [`Bold`](crate::ast::Bold) | `\x02` |
+//! | [`Underline`](crate::ast::Underline) | `\x1F` |
+//! | [`Strikethrough`](crate::ast::Strikethrough) | `\x1E` |
+//! | [`Spoiler`](crate::ast::Spoiler) | See [`SpoilerFallback`] |
+//! | [`OneLineCode`](crate::ast::OneLineCode) | `\x11` |
+//! | [`MultiLineCode`](crate::ast::MultiLineCode) | `\x11` on each line, without the language |
+//! | [`BlockQuote`](crate::ast::BlockQuote) | `> x` |
+//!
+//! IRC clients reset formatting at the end of each line,
+//! so styles are closed before every line break and reopened on the next line.
+//! This allows to send each line of the output as a separate message.
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::irc::{parse_irc, IrcOption, ToIrc};
+//! use discord_md::parse;
+//!
+//! let ast = parse("**bold** *italics* `code`");
+//! assert_eq!(ast.to_irc(&IrcOption::new()), "\x02bold\x02 \x1Ditalics\x1D \x11code\x11");
+//!
+//! let ast = parse_irc("\x02bold\x0F \x0304red\x03 \x1Fline");
+//! assert_eq!(ast.to_string(), "**bold** red __line__");
+//! ```
+
+use crate::ast::{MarkdownDocument, MarkdownElement, MarkdownElementCollection};
+use crate::telegram::{from_entities, EntityKind, MessageEntity};
+
+/// Struct that allows to alter [`to_irc()`](ToIrc::to_irc())'s behaviour.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::irc::{IrcOption, SpoilerFallback, ToIrc};
+/// use discord_md::parse;
+///
+/// let ast = parse("the answer is ||42||");
+///
+/// assert_eq!(ast.to_irc(&IrcOption::new()), "the answer is ||42||");
+/// assert_eq!(ast.to_irc(&IrcOption::new().spoiler(SpoilerFallback::Hidden)), "the answer is \x0301,0142\x03");
+/// assert_eq!(ast.to_irc(&IrcOption::new().spoiler(SpoilerFallback::Omit)), "the answer is ");
+/// ```
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct IrcOption {
+    /// How spoilers are converted, since IRC does not have spoilers
+    pub spoiler: SpoilerFallback,
+}
+
+impl IrcOption {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn spoiler(mut self, value: SpoilerFallback) -> Self {
+        self.spoiler = value;
+        self
+    }
+}
+
+/// How spoilers are converted by [`to_irc()`](ToIrc::to_irc()).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum SpoilerFallback {
+    /// Keep the content surrounded by literal `||`, so that readers can tell it was a spoiler.
+    #[default]
+    Marker,
+
+    /// Color the content black on black (`\x0301,01`), so that it is revealed by selecting it.
+    ///
+    /// [`parse_irc()`] converts text with the same foreground and background colors back into spoilers.
+    Hidden,
+
+    /// Keep the content without any indication.
+    Plain,
+
+    /// Remove the spoiler and its content.
+    Omit,
+}
+
+/// A trait for converting a markdown component into text with IRC formatting codes.
+pub trait ToIrc {
+    /// Returns the component as IRC formatted text.
+    ///
+    /// Formatting codes in plain text are removed, so that they are not interpreted.
+    fn to_irc(&self, option: &IrcOption) -> String;
+}
+
+impl ToIrc for MarkdownDocument {
+    fn to_irc(&self, option: &IrcOption) -> String {
+        self.content().to_irc(option)
+    }
+}
+
+impl ToIrc for MarkdownElementCollection {
+    fn to_irc(&self, option: &IrcOption) -> String {
+        let mut renderer = Renderer::new(option);
+        renderer.collection(self);
+        renderer.finish()
+    }
+}
+
+impl ToIrc for MarkdownElement {
+    fn to_irc(&self, option: &IrcOption) -> String {
+        let mut renderer = Renderer::new(option);
+        renderer.element(self);
+        renderer.finish()
+    }
+}
+
+/// Characters which change the formatting of IRC text.
+const FORMAT_CODES: [char; 9] = [
+    '\x02', '\x03', '\x04', '\x0F', '\x11', '\x16', '\x1D', '\x1E', '\x1F',
+];
+
+/// Styles in the order they are opened.
+#[derive(Debug, Clone, Copy)]
+enum Style {
+    Bold,
+    Italics,
+    Underline,
+    Strikethrough,
+    Monospace,
+    Hidden,
+}
+
+impl Style {
+    const ALL: [Style; 6] = [
+        Style::Bold,
+        Style::Italics,
+        Style::Underline,
+        Style::Strikethrough,
+        Style::Monospace,
+        Style::Hidden,
+    ];
+
+    fn open(self) -> &'static str {
+        match self {
+            Style::Bold => "\x02",
+            Style::Italics => "\x1D",
+            Style::Underline => "\x1F",
+            Style::Strikethrough => "\x1E",
+            Style::Monospace => "\x11",
+            Style::Hidden => "\x0301,01",
+        }
+    }
+
+    fn close(self) -> &'static str {
+        match self {
+            Style::Hidden => "\x03",
+            style => style.open(),
+        }
+    }
+}
+
+/// Writes IRC formatted text.
+///
+/// Formatting codes are written lazily before the next character,
+/// so that empty elements and nested elements of the same style do not write redundant codes.
+struct Renderer<'a> {
+    option: &'a IrcOption,
+    output: String,
+    /// How many elements of each style are open
+    depth: [usize; 6],
+    /// Whether each style is applied to the output
+    applied: [bool; 6],
+    quoted: bool,
+    line_start: bool,
+    block_ended: bool,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(option: &'a IrcOption) -> Self {
+        Self {
+            option,
+            output: String::new(),
+            depth: [0; 6],
+            applied: [false; 6],
+            quoted: false,
+            line_start: true,
+            block_ended: false,
+        }
+    }
+
+    fn collection(&mut self, collection: &MarkdownElementCollection) {
+        for element in collection.get() {
+            self.element(element);
+        }
+    }
+
+    fn element(&mut self, element: &MarkdownElement) {
+        match element {
+            MarkdownElement::Plain(x) => self.text(x.content()),
+            MarkdownElement::ItalicsStar(x) => {
+                self.styled(Style::Italics, |r| r.collection(x.content()))
+            }
+            MarkdownElement::ItalicsUnderscore(x) => {
+                self.styled(Style::Italics, |r| r.collection(x.content()))
+            }
+            MarkdownElement::Bold(x) => self.styled(Style::Bold, |r| r.collection(x.content())),
+            MarkdownElement::Underline(x) => {
+                self.styled(Style::Underline, |r| r.collection(x.content()))
+            }
+            MarkdownElement::Strikethrough(x) => {
+                self.styled(Style::Strikethrough, |r| r.collection(x.content()))
+            }
+            MarkdownElement::Spoiler(x) => match self.option.spoiler {
+                SpoilerFallback::Marker => {
+                    self.text("||");
+                    self.collection(x.content());
+                    self.text("||");
+                }
+                SpoilerFallback::Hidden => {
+                    self.styled(Style::Hidden, |r| r.collection(x.content()))
+                }
+                SpoilerFallback::Plain => self.collection(x.content()),
+                SpoilerFallback::Omit => {}
+            },
+            MarkdownElement::OneLineCode(x) => {
+                self.styled(Style::Monospace, |r| r.text(x.content()))
+            }
+            MarkdownElement::MultiLineCode(x) => {
+                let content = x.content().replace("\r\n", "\n");
+                let content = content.strip_prefix('\n').unwrap_or(&content);
+                let content = content.strip_suffix('\n').unwrap_or(content);
+
+                self.start_block();
+                self.styled(Style::Monospace, |r| r.text(content));
+                self.block_ended = true;
+            }
+            MarkdownElement::BlockQuote(x) => {
+                self.start_block();
+                self.quoted = true;
+                self.collection(x.content());
+                self.quoted = false;
+                self.block_ended = true;
+            }
+        }
+    }
+
+    fn styled(&mut self, style: Style, content: impl FnOnce(&mut Self)) {
+        self.depth[style as usize] += 1;
+        content(self);
+        self.depth[style as usize] -= 1;
+    }
+
+    /// Starts a new line for a block, unless it is at the start of a line.
+    fn start_block(&mut self) {
+        if !self.line_start {
+            self.text("\n");
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        for c in text.chars() {
+            if c == '\r' || FORMAT_CODES.contains(&c) {
+                continue;
+            }
+
+            if c == '\n' {
+                if self.quoted && self.line_start {
+                    self.output.push('>');
+                }
+                self.apply(|_| false);
+                self.output.push('\n');
+                self.line_start = true;
+                self.block_ended = false;
+                continue;
+            }
+
+            if self.block_ended {
+                self.text("\n");
+            }
+            if self.quoted && self.line_start {
+                self.output.push_str("> ");
+            }
+            self.line_start = false;
+
+            let depth = self.depth;
+            self.apply(|style| depth[style as usize] > 0);
+            // A digit or a comma after `\x03` would be read as a color, so it is separated by an empty bold text.
+            if self.output.ends_with('\x03') && (c.is_ascii_digit() || c == ',') {
+                self.output.push_str("\x02\x02");
+            }
+            self.output.push(c);
+        }
+    }
+
+    /// Writes formatting codes, so that the styles `applied` returns `true` for are applied.
+    fn apply(&mut self, applied: impl Fn(Style) -> bool) {
+        // Styles are closed in the reverse order, so that the codes are nested.
+        for style in Style::ALL.into_iter().rev() {
+            if self.applied[style as usize] && !applied(style) {
+                self.output.push_str(style.close());
+                self.applied[style as usize] = false;
+            }
+        }
+        for style in Style::ALL {
+            if !self.applied[style as usize] && applied(style) {
+                self.output.push_str(style.open());
+                self.applied[style as usize] = true;
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.apply(|_| false);
+        self.output
+    }
+}
+
+/// Parses text with IRC formatting codes into an AST.
+///
+/// Each line is parsed separately, since formatting is reset at the end of a line.
+///
+/// | Code | Element |
+/// |---|---|
+/// | `\x02` | [`Bold`](crate::ast::Bold) |
+/// | `\x1D` | [`ItalicsStar`](crate::ast::ItalicsStar) |
+/// | `\x1F` | [`Underline`](crate::ast::Underline) |
+/// | `\x1E` | [`Strikethrough`](crate::ast::Strikethrough) |
+/// | `\x11` | [`OneLineCode`](crate::ast::OneLineCode) |
+/// | `\x03` and `\x04` colors | [`Spoiler`](crate::ast::Spoiler) if the foreground and background colors are the same, otherwise ignored |
+/// | `\x0F` | Resets all formatting |
+///
+/// Reverse color (`\x16`) is ignored.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::ast::MarkdownDocument;
+/// use discord_md::builder::*;
+/// use discord_md::irc::parse_irc;
+///
+/// assert_eq!(
+///     parse_irc("\x02\x1Dhi\x02 there\x1D, \x031,1secret"),
+///     MarkdownDocument::new(vec![
+///         italics_star(vec![bold("hi"), plain(" there")]),
+///         plain(", "),
+///         spoiler("secret"),
+///     ])
+/// );
+/// ```
+pub fn parse_irc(text: &str) -> MarkdownDocument {
+    let mut parser = Parser::default();
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            parser.push('\n');
+        }
+        parser.line(line);
+    }
+    from_entities(&parser.text, &parser.entities)
+}
+
+/// Converts IRC formatted text into plain text with entities.
+#[derive(Default)]
+struct Parser {
+    text: String,
+    entities: Vec<MessageEntity>,
+    /// UTF-16 length of `text`
+    offset: usize,
+    /// Offsets where bold, italics, underline, strikethrough and monospace started
+    open: [Option<usize>; 5],
+    foreground: Option<String>,
+    background: Option<String>,
+    /// Offset where the same foreground and background colors started
+    hidden: Option<usize>,
+}
+
+impl Parser {
+    const KINDS: [EntityKind; 5] = [
+        EntityKind::Bold,
+        EntityKind::Italic,
+        EntityKind::Underline,
+        EntityKind::Strikethrough,
+        EntityKind::Code,
+    ];
+
+    fn line(&mut self, line: &str) {
+        let mut pos = 0;
+        while let Some(c) = line[pos..].chars().next() {
+            pos += c.len_utf8();
+            match c {
+                '\x02' => self.toggle(0),
+                '\x1D' => self.toggle(1),
+                '\x1F' => self.toggle(2),
+                '\x1E' => self.toggle(3),
+                '\x11' => self.toggle(4),
+                '\x03' | '\x04' => {
+                    let (foreground, background, len) = color(&line[pos..], c == '\x04');
+                    pos += len;
+                    if foreground.is_none() {
+                        self.background = None;
+                    } else if background.is_some() {
+                        self.background = background;
+                    }
+                    self.foreground = foreground;
+                    self.update_hidden();
+                }
+                '\x0F' => self.reset(),
+                '\x16' | '\r' => {}
+                c => self.push(c),
+            }
+        }
+        self.reset();
+    }
+
+    fn push(&mut self, c: char) {
+        self.text.push(c);
+        self.offset += c.len_utf16();
+    }
+
+    fn toggle(&mut self, index: usize) {
+        match self.open[index].take() {
+            Some(start) => self.add_entity(Self::KINDS[index].clone(), start),
+            None => self.open[index] = Some(self.offset),
+        }
+    }
+
+    fn update_hidden(&mut self) {
+        let hidden = self.foreground.is_some() && self.foreground == self.background;
+        match (self.hidden, hidden) {
+            (None, true) => self.hidden = Some(self.offset),
+            (Some(start), false) => {
+                self.hidden = None;
+                self.add_entity(EntityKind::Spoiler, start);
+            }
+            _ => {}
+        }
+    }
+
+    /// Closes all styles and colors.
+    fn reset(&mut self) {
+        for index in 0..self.open.len() {
+            if self.open[index].is_some() {
+                self.toggle(index);
+            }
+        }
+        self.foreground = None;
+        self.background = None;
+        self.update_hidden();
+    }
+
+    fn add_entity(&mut self, kind: EntityKind, start: usize) {
+        if start < self.offset {
+            self.entities
+                .push(MessageEntity::new(kind, start, self.offset - start));
+        }
+    }
+}
+
+/// Parses the colors after `\x03`, like `4` and `04,01`, or after `\x04` if `hex` is `true`, like `FF0000,000000`.
+///
+/// Returns the foreground, the background and the length. The foreground is `None` if the colors are reset.
+fn color(text: &str, hex: bool) -> (Option<String>, Option<String>, usize) {
+    let Some((foreground, len)) = color_component(text, hex) else {
+        return (None, None, 0);
+    };
+    match text[len..]
+        .strip_prefix(',')
+        .and_then(|rest| color_component(rest, hex))
+    {
+        Some((background, background_len)) => {
+            (Some(foreground), Some(background), len + 1 + background_len)
+        }
+        None => (Some(foreground), None, len),
+    }
+}
+
+/// Parses a color at the start of the text, and returns it as a comparable string and the length.
+fn color_component(text: &str, hex: bool) -> Option<(String, usize)> {
+    let bytes = text.as_bytes();
+    if hex {
+        let code = bytes.get(..6)?;
+        code.iter()
+            .all(u8::is_ascii_hexdigit)
+            .then(|| (format!("#{}", text[..6].to_ascii_lowercase()), 6))
+    } else {
+        let len = bytes
+            .iter()
+            .take(2)
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        (len > 0).then(|| (text[..len].parse::<u8>().unwrap().to_string(), len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::parse;
+
+    fn to_irc(text: &str) -> String {
+        parse(text).to_irc(&IrcOption::new())
+    }
+
+    #[test]
+    fn test_to_irc() {
+        assert_eq!(
+            to_irc("*a* _b_ **c** __d__ ~~e~~ `f`"),
+            "\x1Da\x1D \x1Db\x1D \x02c\x02 \x1Fd\x1F \x1Ee\x1E \x11f\x11"
+        );
+        assert_eq!(
+            bold(vec![plain("a"), bold("b"), italics_star("")]).to_irc(&IrcOption::new()),
+            "\x02ab\x02"
+        );
+        assert_eq!(
+            plain("a\x02b\x0304c\r\nd").to_irc(&IrcOption::new()),
+            "ab04c\nd"
+        );
+    }
+
+    #[test]
+    fn test_line_breaks_to_irc() {
+        assert_eq!(
+            bold(vec![plain("a\nb"), underline("c\n")]).to_irc(&IrcOption::new()),
+            "\x02a\x02\n\x02b\x1Fc\x1F\x02\n"
+        );
+        assert_eq!(
+            MarkdownDocument::new(vec![
+                plain("a "),
+                multi_line_code("\nfn main() {\n}\n", Some("rs".to_string())),
+                plain("\nb "),
+                block_quote(vec![plain("c\n\n"), bold("d")]),
+                plain("e"),
+            ])
+            .to_irc(&IrcOption::new()),
+            "a \n\x11fn main() {\x11\n\x11}\x11\nb \n> c\n>\n> \x02d\x02\ne"
+        );
+    }
+
+    #[test]
+    fn test_spoiler_to_irc() {
+        let ast = parse("a ||**b**|| c");
+
+        assert_eq!(ast.to_irc(&IrcOption::new()), "a ||\x02b\x02|| c");
+        assert_eq!(
+            ast.to_irc(&IrcOption::new().spoiler(SpoilerFallback::Hidden)),
+            "a \x02\x0301,01b\x03\x02 c"
+        );
+        assert_eq!(
+            ast.to_irc(&IrcOption::new().spoiler(SpoilerFallback::Plain)),
+            "a \x02b\x02 c"
+        );
+        assert_eq!(
+            ast.to_irc(&IrcOption::new().spoiler(SpoilerFallback::Omit)),
+            "a  c"
+        );
+        assert_eq!(
+            MarkdownDocument::new(vec![spoiler("a"), plain("1")])
+                .to_irc(&IrcOption::new().spoiler(SpoilerFallback::Hidden)),
+            "\x0301,01a\x03\x02\x021"
+        );
+    }
+
+    #[test]
+    fn test_parse_irc() {
+        assert_eq!(
+            parse_irc("\x02a\x02 \x1Db\x1D \x1Fc\x1F \x1Ed\x1E \x11e\x11"),
+            MarkdownDocument::new(vec![
+                bold("a"),
+                plain(" "),
+                italics_star("b"),
+                plain(" "),
+                underline("c"),
+                plain(" "),
+                strikethrough("d"),
+                plain(" "),
+                one_line_code("e"),
+            ])
+        );
+        assert_eq!(
+            parse_irc("\x02a\x1Db\x02c\x0Fd\x1Fe\nf\x1F"),
+            MarkdownDocument::new(vec![
+                bold(vec![plain("a"), italics_star("b")]),
+                italics_star("c"),
+                plain("d"),
+                underline("e"),
+                plain("\nf"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_irc_colors() {
+        assert_eq!(
+            parse_irc("\x0304a\x03 \x034,5b\x0399,99c\x03d \x03,5e"),
+            MarkdownDocument::new(vec![plain("a b"), spoiler("c"), plain("d ,5e"),])
+        );
+        assert_eq!(
+            parse_irc("\x0301,01a\x0302b\x0302\x0401ABCD,01abcdc\x0Fd \x0400ff00e"),
+            MarkdownDocument::new(vec![spoiler("a"), plain("b"), spoiler("c"), plain("d e"),])
+        );
+        assert_eq!(
+            parse_irc("\x16a\x03123"),
+            MarkdownDocument::new(vec![plain("a3")])
+        );
+    }
+
+    #[test]
+    fn test_color() {
+        assert_eq!(color("04text", false), (Some("4".to_string()), None, 2));
+        assert_eq!(
+            color("4,12text", false),
+            (Some("4".to_string()), Some("12".to_string()), 4)
+        );
+        assert_eq!(color("4,text", false), (Some("4".to_string()), None, 1));
+        assert_eq!(color(",4text", false), (None, None, 0));
+        assert_eq!(
+            color("FF0000,00000g", true),
+            (Some("#ff0000".to_string()), None, 6)
+        );
+    }
+
+    #[test]
+    fn test_irc_round_trip() {
+        let option = IrcOption::new().spoiler(SpoilerFallback::Hidden);
+        let ast = MarkdownDocument::new(vec![
+            bold(vec![plain("a "), italics_star("b")]),
+            plain(" 1 "),
+            spoiler(vec![plain("c "), underline("d")]),
+            plain("2\n"),
+            strikethrough("e"),
+            plain(" "),
+            one_line_code("f"),
+        ]);
+
+        assert_eq!(parse_irc(&ast.to_irc(&option)), ast);
+    }
+}
@@ -0,0 +1,178 @@
+//! Depth-first iteration over an AST
+//!
+//! [`iter`](crate::iter) module provides [`Iter`], which is returned by [`MarkdownDocument::iter()`].
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::ast::MarkdownElement;
+//! use discord_md::parse;
+//!
+//! let ast = parse("**bold ||secret||** public");
+//!
+//! let hidden = ast
+//!     .iter()
+//!     .filter(|node| matches!(node.element(), MarkdownElement::Plain(_)))
+//!     .filter(|node| node.has_ancestor(|e| matches!(e, MarkdownElement::Spoiler(_))))
+//!     .map(|node| node.element().to_string())
+//!     .collect::<Vec<_>>();
+//!
+//! assert_eq!(hidden, vec!["secret"]);
+//! ```
+
+use crate::ast::{MarkdownDocument, MarkdownElement};
+use std::slice;
+
+/// A markdown element yielded by [`Iter`], together with its ancestors.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Node<'a> {
+    element: &'a MarkdownElement,
+    ancestors: Vec<&'a MarkdownElement>,
+}
+
+impl<'a> Node<'a> {
+    /// Returns the element.
+    pub fn element(&self) -> &'a MarkdownElement {
+        self.element
+    }
+
+    /// Returns the ancestors of the element, outermost first.
+    ///
+    /// Top-level elements have no ancestors.
+    pub fn ancestors(&self) -> &[&'a MarkdownElement] {
+        &self.ancestors
+    }
+
+    /// Returns the parent of the element, or `None` if it is a top-level element.
+    pub fn parent(&self) -> Option<&'a MarkdownElement> {
+        self.ancestors.last().copied()
+    }
+
+    /// Returns the number of ancestors of the element.
+    pub fn depth(&self) -> usize {
+        self.ancestors.len()
+    }
+
+    /// Returns `true` if any ancestor of the element satisfies the predicate.
+    pub fn has_ancestor(&self, predicate: impl Fn(&MarkdownElement) -> bool) -> bool {
+        self.ancestors.iter().any(|ancestor| predicate(ancestor))
+    }
+}
+
+/// A depth-first iterator over every element of a [`MarkdownDocument`] in document order.
+///
+/// Each element is yielded before its children.
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    stack: Vec<slice::Iter<'a, MarkdownElement>>,
+    ancestors: Vec<&'a MarkdownElement>,
+}
+
+impl<'a> Iter<'a> {
+    pub(crate) fn new(document: &'a MarkdownDocument) -> Self {
+        Self {
+            stack: vec![document.content().get().iter()],
+            ancestors: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Node<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.last_mut()?.next() {
+                Some(element) => {
+                    let node = Node {
+                        element,
+                        ancestors: self.ancestors.clone(),
+                    };
+                    if let Some(children) = element.children() {
+                        self.stack.push(children.get().iter());
+                        self.ancestors.push(element);
+                    }
+                    return Some(node);
+                }
+                None => {
+                    self.stack.pop();
+                    self.ancestors.pop();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::*;
+    use crate::builder::*;
+
+    #[test]
+    fn test_iter() {
+        let ast = MarkdownDocument::new(vec![
+            plain("a"),
+            bold(vec![
+                spoiler(vec![plain("b"), one_line_code("c")]),
+                plain("d"),
+            ]),
+            block_quote(vec![underline("e")]),
+            multi_line_code("f", None),
+        ]);
+
+        let nodes = ast
+            .iter()
+            .map(|node| {
+                let ancestors = node.ancestors().iter().map(|e| e.kind()).collect();
+                (node.element().kind(), ancestors)
+            })
+            .collect::<Vec<(_, Vec<_>)>>();
+
+        assert_eq!(
+            nodes,
+            vec![
+                (ElementKind::Plain, vec![]),
+                (ElementKind::Bold, vec![]),
+                (ElementKind::Spoiler, vec![ElementKind::Bold]),
+                (
+                    ElementKind::Plain,
+                    vec![ElementKind::Bold, ElementKind::Spoiler]
+                ),
+                (
+                    ElementKind::OneLineCode,
+                    vec![ElementKind::Bold, ElementKind::Spoiler]
+                ),
+                (ElementKind::Plain, vec![ElementKind::Bold]),
+                (ElementKind::BlockQuote, vec![]),
+                (ElementKind::Underline, vec![ElementKind::BlockQuote]),
+                (
+                    ElementKind::Plain,
+                    vec![ElementKind::BlockQuote, ElementKind::Underline]
+                ),
+                (ElementKind::MultiLineCode, vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_iter_empty() {
+        assert_eq!(MarkdownDocument::new(vec![]).iter().count(), 0);
+        assert_eq!(MarkdownDocument::new(vec![bold(vec![])]).iter().count(), 1);
+    }
+
+    #[test]
+    fn test_node() {
+        let ast = MarkdownDocument::new(vec![italics_star(vec![strikethrough("x")])]);
+        let nodes = ast.iter().collect::<Vec<_>>();
+
+        assert_eq!(nodes[0].depth(), 0);
+        assert_eq!(nodes[0].parent(), None);
+        assert!(!nodes[0].has_ancestor(|_| true));
+
+        assert_eq!(nodes[2].element(), &plain("x"));
+        assert_eq!(nodes[2].depth(), 2);
+        assert_eq!(nodes[2].parent(), Some(&strikethrough("x")));
+        assert!(nodes[2].has_ancestor(|e| matches!(e, MarkdownElement::ItalicsStar(_))));
+        assert!(!nodes[2].has_ancestor(|e| matches!(e, MarkdownElement::Bold(_))));
+    }
+}
@@ -0,0 +1,187 @@
+//! Measuring messages the way Discord does
+//!
+//! [`length`](crate::length) module provides functions to count characters of messages,
+//! so that the length can be checked before sending a message.
+//!
+//! Discord limits the content of a message to [`MESSAGE_LIMIT`] characters, counted in Unicode code points.
+//! For instance, `👍` counts as one character, even though it takes two UTF-16 code units and four bytes.
+//! The limit applies to the markdown text, including delimiters like `**`.
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::length::{char_count, MESSAGE_LIMIT};
+//! use discord_md::parse;
+//!
+//! let ast = parse("**👍** <:party:123456789012345678>");
+//!
+//! assert_eq!(char_count("👍"), 1);
+//! assert_eq!(ast.markdown_len(), 33);
+//! assert_eq!(ast.visible_len(), 3);
+//! assert!(ast.markdown_len() <= MESSAGE_LIMIT);
+//! ```
+
+use crate::ast::{MarkdownDocument, MarkdownElement, MultiLineCode, OneLineCode, Plain, Visitor};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while1},
+    combinator::{opt, recognize},
+    sequence::{delimited, tuple},
+    IResult,
+};
+
+/// The maximum number of characters in the content of a message.
+pub const MESSAGE_LIMIT: usize = 2000;
+
+/// Counts characters of text the way Discord does, i.e. in Unicode code points.
+pub fn char_count(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Counts characters of plain text as displayed in Discord.
+///
+/// A custom emoji like `<:name:id>` and a mention like `<@id>` are displayed as one unit, so they count as one character.
+/// Other text is counted by [`char_count`].
+pub fn visible_char_count(text: &str) -> usize {
+    units(text).count()
+}
+
+/// Splits text into the smallest units that must not be split.
+///
+/// Each unit is a custom emoji, a mention, or a single character.
+pub(crate) fn units(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let c = rest.chars().next()?;
+        let len = match atomic_token(rest) {
+            Ok((_, token)) => token.len(),
+            Err(_) => c.len_utf8(),
+        };
+        let (unit, tail) = rest.split_at(len);
+        rest = tail;
+        Some(unit)
+    })
+}
+
+/// Parses a custom emoji or a mention.
+///
+/// - Custom emoji: `<:name:id>`, `<a:name:id>`
+/// - Mention of a user: `<@id>`, `<@!id>`
+/// - Mention of a channel: `<#id>`
+/// - Mention of a role: `<@&id>`
+fn atomic_token(i: &str) -> IResult<&str, &str> {
+    let id = |i| take_while1(|c: char| c.is_ascii_digit())(i);
+    let name = |i| take_while1(|c: char| c.is_ascii_alphanumeric() || c == '_')(i);
+
+    recognize(delimited(
+        tag("<"),
+        alt((
+            recognize(tuple((opt(tag("a")), tag(":"), name, tag(":"), id))),
+            recognize(tuple((tag("@"), opt(alt((tag("!"), tag("&")))), id))),
+            recognize(tuple((tag("#"), id))),
+        )),
+        tag(">"),
+    ))(i)
+}
+
+/// Sums up the visible length of every text in a document.
+#[derive(Default)]
+struct VisibleLength(usize);
+
+impl Visitor for VisibleLength {
+    fn visit_plain(&mut self, node: &Plain) {
+        self.0 += visible_char_count(node.content());
+    }
+
+    fn visit_one_line_code(&mut self, node: &OneLineCode) {
+        // Code is displayed as it is, so emoji and mentions are not rendered.
+        self.0 += char_count(node.content());
+    }
+
+    fn visit_multi_line_code(&mut self, node: &MultiLineCode) {
+        self.0 += char_count(node.content());
+    }
+}
+
+/// Computes the visible length of a document. See [`MarkdownDocument::visible_len()`].
+pub(crate) fn visible_len(document: &MarkdownDocument) -> usize {
+    let mut visitor = VisibleLength::default();
+    visitor.visit_document(document);
+    visitor.0
+}
+
+/// Computes the visible length of an element.
+pub(crate) fn element_visible_len(element: &MarkdownElement) -> usize {
+    let mut visitor = VisibleLength::default();
+    visitor.visit_element(element);
+    visitor.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+
+    #[test]
+    fn test_char_count() {
+        assert_eq!(char_count(""), 0);
+        assert_eq!(char_count("abc"), 3);
+        assert_eq!(char_count("日本語"), 3);
+        assert_eq!(char_count("👍"), 1);
+        assert_eq!(char_count("👨‍👩‍👧"), 5);
+    }
+
+    #[test]
+    fn test_visible_char_count() {
+        assert_eq!(visible_char_count("hi <:wave:123>!"), 5);
+        assert_eq!(visible_char_count("<a:dance:123><@1><@!2><#3><@&4>"), 5);
+        assert_eq!(visible_char_count("<@> <:a:> <x:a:1> <#a>"), 22);
+    }
+
+    #[test]
+    fn test_units() {
+        assert_eq!(
+            units("a<:b:1>👍<@!2>").collect::<Vec<_>>(),
+            vec!["a", "<:b:1>", "👍", "<@!2>"]
+        );
+        assert_eq!(units("<:b:1").collect::<Vec<_>>().len(), 5);
+        assert_eq!(units("").count(), 0);
+    }
+
+    #[test]
+    fn test_atomic_token() {
+        assert_eq!(atomic_token("<:name_1:123> x"), Ok((" x", "<:name_1:123>")));
+        assert_eq!(atomic_token("<a:name:123>"), Ok(("", "<a:name:123>")));
+        assert_eq!(atomic_token("<@123>"), Ok(("", "<@123>")));
+        assert_eq!(atomic_token("<@!123>"), Ok(("", "<@!123>")));
+        assert_eq!(atomic_token("<@&123>"), Ok(("", "<@&123>")));
+        assert_eq!(atomic_token("<#123>"), Ok(("", "<#123>")));
+        assert!(atomic_token("<@abc>").is_err());
+        assert!(atomic_token("<:name:>").is_err());
+        assert!(atomic_token("<@123").is_err());
+    }
+
+    #[test]
+    fn test_visible_len() {
+        let ast = MarkdownDocument::new(vec![
+            plain("hi "),
+            bold(vec![plain("<@1>"), spoiler("!")]),
+            one_line_code("<@1>"),
+            multi_line_code("\ncode\n", Some("rs".to_string())),
+            block_quote("q"),
+        ]);
+
+        assert_eq!(visible_len(&ast), 3 + 1 + 1 + 4 + 6 + 1);
+        assert_eq!(visible_len(&MarkdownDocument::new(vec![])), 0);
+    }
+
+    #[test]
+    fn test_element_visible_len() {
+        assert_eq!(element_visible_len(&plain("<@1> hi")), 4);
+        assert_eq!(
+            element_visible_len(&bold(vec![plain("a"), spoiler("bc")])),
+            3
+        );
+        assert_eq!(element_visible_len(&one_line_code("<@1>")), 4);
+    }
+}
@@ -12,6 +12,7 @@
 //! # Parsing
 //!
 //! [`parse`] parses a markdown document and returns an AST.
+//! [`try_parse`] does the same, but returns a [`ParseError`] instead of panicking.
 //!
 //! ## Example
 //!
@@ -145,12 +146,64 @@
 //! - Intraword emphasis may not be handled properly. The parser treats `foo_bar_baz` as emphasis, while Discord's parser does not.
 //! - Escaping sequence will be treated as plain text.
 
+pub mod ansi;
 pub mod ast;
 pub mod builder;
+pub mod delimiter;
+pub mod diff;
+pub mod error;
 pub mod generate;
+pub mod gfm;
+pub mod html;
+pub mod incremental;
+pub mod irc;
+pub mod iter;
+pub mod length;
+pub mod matrix;
+mod normalize;
 mod parser;
+pub mod select;
+pub mod semantic;
+#[cfg(feature = "serde")]
+mod serialization;
+pub mod slack;
+pub mod split;
+pub mod stream;
+pub mod telegram;
+pub mod truncate;
 
 use ast::MarkdownDocument;
+use error::ParseError;
+
+/// Struct that allows to alter [`try_parse_with_option()`]'s behaviour.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::{try_parse_with_option, ParseOption};
+///
+/// let option = ParseOption::new().max_length(Some(2000));
+///
+/// assert!(try_parse_with_option("**short message**", &option).is_ok());
+/// assert!(try_parse_with_option(&"a".repeat(2001), &option).is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ParseOption {
+    /// Maximum length of the input in bytes, or `None` for no limit
+    pub max_length: Option<usize>,
+}
+
+impl ParseOption {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn max_length(mut self, value: Option<usize>) -> Self {
+        self.max_length = value;
+        self
+    }
+}
 
 /// Parses a markdown document and returns AST.
 ///
@@ -193,14 +246,77 @@ use ast::MarkdownDocument;
 /// - Nested emphasis, like `*italics **bold italics** italics*`, may not be parsed properly.
 /// - Intraword emphasis may not be handled properly. The parser treats `foo_bar_baz` as emphasis, while Discord's parser does not.
 /// - Escaping sequence will be treated as plain text.
+///
+/// # Panics
+///
+/// Panics if the parser hits an internal inconsistency. Use [`try_parse`] to handle it as an error instead.
 pub fn parse(msg: &str) -> MarkdownDocument {
     // Since there are no invalid markdown document, parsing should never fails.
-    let (rest, doc) = parser::markdown_document(msg).unwrap();
+    try_parse(msg).unwrap_or_else(|err| panic!("failed to parse markdown: {}", err))
+}
+
+/// Parses a markdown document and returns AST, or an error if the parser fails.
+///
+/// Every string is a valid markdown document, so this function only fails if the parser has a bug.
+/// Use [`try_parse_with_option`] to limit the input length.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::ast::*;
+/// use discord_md::try_parse;
+///
+/// assert_eq!(
+///     try_parse("`code`"),
+///     Ok(MarkdownDocument::new(vec![
+///         MarkdownElement::OneLineCode(Box::new(OneLineCode::new("code")))
+///     ]))
+/// );
+/// ```
+pub fn try_parse(msg: &str) -> Result<MarkdownDocument, ParseError> {
+    try_parse_with_option(msg, &ParseOption::new())
+}
 
-    // All input should be consumed.
-    assert!(rest.is_empty());
+/// Parses a markdown document with the given option and returns AST, or an error if the parser fails.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::error::ParseError;
+/// use discord_md::{try_parse_with_option, ParseOption};
+///
+/// let option = ParseOption::new().max_length(Some(8));
+///
+/// assert!(try_parse_with_option("**bold**", &option).is_ok());
+/// assert_eq!(
+///     try_parse_with_option("**bold!**", &option),
+///     Err(ParseError::LimitExceeded { limit: 8, length: 9 })
+/// );
+/// ```
+pub fn try_parse_with_option(
+    msg: &str,
+    option: &ParseOption,
+) -> Result<MarkdownDocument, ParseError> {
+    if let Some(limit) = option.max_length {
+        if msg.len() > limit {
+            return Err(ParseError::LimitExceeded {
+                limit,
+                length: msg.len(),
+            });
+        }
+    }
 
-    doc
+    match parser::markdown_document(msg) {
+        // All input should be consumed.
+        Ok(("", doc)) => Ok(doc),
+        Ok((rest, _)) => Err(ParseError::Internal {
+            offset: msg.len() - rest.len(),
+        }),
+        Err(nom::Err::Error(err) | nom::Err::Failure(err)) => Err(ParseError::Internal {
+            offset: msg.len() - err.input.len(),
+        }),
+        Err(nom::Err::Incomplete(_)) => Err(ParseError::Internal { offset: msg.len() }),
+    }
 }
 
 #[cfg(test)]
@@ -262,4 +378,58 @@ const cond = a > b || c < d || e === f;
             ))])
         );
     }
+
+    #[test]
+    fn test_parse_crlf() {
+        let message = "**crlf**\r\n```js\r\nconst a = 1;\r\n```";
+        assert_eq!(
+            parse(message),
+            MarkdownDocument::new(vec![
+                MarkdownElement::Bold(Box::new(Bold::new(vec![MarkdownElement::Plain(Box::new(
+                    Plain::new("crlf")
+                ))]))),
+                MarkdownElement::Plain(Box::new(Plain::new("\r\n"))),
+                MarkdownElement::MultiLineCode(Box::new(MultiLineCode::new(
+                    "\r\nconst a = 1;\r\n",
+                    Some("js".to_string())
+                ))),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_try_parse() {
+        assert_eq!(
+            try_parse("**bold**"),
+            Ok(MarkdownDocument::new(vec![MarkdownElement::Bold(
+                Box::new(Bold::new(vec![MarkdownElement::Plain(Box::new(
+                    Plain::new("bold")
+                ))]))
+            )]))
+        );
+        assert_eq!(try_parse(""), Ok(MarkdownDocument::new(vec![])));
+    }
+
+    #[test]
+    fn test_try_parse_with_option() {
+        let option = ParseOption::new().max_length(Some(4));
+
+        assert_eq!(
+            try_parse_with_option("text", &option),
+            Ok(MarkdownDocument::new(vec![MarkdownElement::Plain(
+                Box::new(Plain::new("text"))
+            )]))
+        );
+        assert_eq!(
+            try_parse_with_option("text!", &option),
+            Err(ParseError::LimitExceeded {
+                limit: 4,
+                length: 5
+            })
+        );
+        assert_eq!(
+            try_parse_with_option(&"a".repeat(10000), &ParseOption::new()).map(|_| ()),
+            Ok(())
+        );
+    }
 }
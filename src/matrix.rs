@@ -0,0 +1,846 @@
+//! Converts between an AST and Matrix's `org.matrix.custom.html` format
+//!
+//! [`matrix`](crate::matrix) module provides [`ToMatrix`] trait, which converts a markdown component into
+//! the `body` and `formatted_body` of a Matrix message,
+//! and [`parse_formatted_body()`], which converts `formatted_body` back into an AST.
+//!
+//! | Element | `formatted_body` |
+//! |---|---|
+//! | [`ItalicsStar`], [`ItalicsUnderscore`](crate::ast::ItalicsUnderscore) | `<em>` |
+//! | [`Bold`] | `<strong>` |
+//! | [`Underline`] | `<u>` |
+//! | [`Strikethrough`] | `<del>` |
+//! | [`Spoiler`] | `<span data-mx-spoiler>` |
+//! | [`OneLineCode`] | `<code>` |
+//! | [`MultiLineCode`] | `<pre><code class="language-x">` |
+//! | [`BlockQuote`] | `<blockquote>` |
+//!
+//! The `body` is the plain text fallback for clients that do not render HTML.
+//! It is Discord's markdown text, whose spoilers are replaced with [`MatrixOption::spoiler_placeholder`],
+//! so that the fallback does not reveal them.
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::matrix::{parse_formatted_body, MatrixOption, ToMatrix};
+//! use discord_md::parse;
+//!
+//! let ast = parse("**hi** ||<secret>||");
+//! let message = ast.to_matrix(&MatrixOption::new());
+//!
+//! assert_eq!(message.body, "**hi** [Spoiler]");
+//! assert_eq!(
+//!     message.formatted_body,
+//!     "<strong>hi</strong> <span data-mx-spoiler>&lt;secret&gt;</span>"
+//! );
+//! assert_eq!(parse_formatted_body(&message.formatted_body), ast);
+//! ```
+
+use crate::ast::{
+    BlockQuote, Bold, Fold, ItalicsStar, MarkdownDocument, MarkdownElement,
+    MarkdownElementCollection, MultiLineCode, OneLineCode, Plain, Spoiler, Strikethrough,
+    Underline,
+};
+use crate::gfm::parser::entity;
+use crate::html::escape;
+use crate::normalize::merge_plain;
+
+/// The value of the `format` field of a message with [`MatrixMessage::formatted_body`].
+pub const FORMAT: &str = "org.matrix.custom.html";
+
+/// Struct that allows to alter [`to_matrix()`](ToMatrix::to_matrix())'s behaviour.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::matrix::{MatrixOption, ToMatrix};
+/// use discord_md::parse;
+///
+/// let ast = parse("the answer is ||42||");
+///
+/// assert_eq!(ast.to_matrix(&MatrixOption::new()).body, "the answer is [Spoiler]");
+/// assert_eq!(ast.to_matrix(&MatrixOption::new().spoiler_placeholder(None)).body, "the answer is ||42||");
+/// ```
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct MatrixOption {
+    /// Text that replaces spoilers in `body`, which is `[Spoiler]` by default.
+    /// If `None`, spoilers are kept in `body` as Discord's markdown.
+    pub spoiler_placeholder: Option<String>,
+}
+
+impl Default for MatrixOption {
+    fn default() -> Self {
+        Self {
+            spoiler_placeholder: Some("[Spoiler]".to_string()),
+        }
+    }
+}
+
+impl MatrixOption {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn spoiler_placeholder(mut self, value: Option<String>) -> Self {
+        self.spoiler_placeholder = value;
+        self
+    }
+}
+
+/// The text fields of a Matrix message, returned by [`to_matrix()`](ToMatrix::to_matrix()).
+///
+/// The `format` field of the message must be [`FORMAT`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatrixMessage {
+    /// The plain text fallback
+    pub body: String,
+
+    /// The HTML body
+    pub formatted_body: String,
+}
+
+/// A trait for converting a markdown component into a Matrix message.
+pub trait ToMatrix {
+    /// Returns the component as `body` and `formatted_body` of a Matrix message.
+    fn to_matrix(&self, option: &MatrixOption) -> MatrixMessage;
+}
+
+impl ToMatrix for MarkdownDocument {
+    fn to_matrix(&self, option: &MatrixOption) -> MatrixMessage {
+        self.content().to_matrix(option)
+    }
+}
+
+impl ToMatrix for MarkdownElementCollection {
+    fn to_matrix(&self, option: &MatrixOption) -> MatrixMessage {
+        let body = match &option.spoiler_placeholder {
+            Some(placeholder) => SpoilerPlaceholder(placeholder)
+                .fold_element_collection(self.clone())
+                .to_string(),
+            None => self.to_string(),
+        };
+
+        MatrixMessage {
+            body,
+            formatted_body: formatted_body(self.get()),
+        }
+    }
+}
+
+impl ToMatrix for MarkdownElement {
+    fn to_matrix(&self, option: &MatrixOption) -> MatrixMessage {
+        MarkdownElementCollection::new(vec![self.clone()]).to_matrix(option)
+    }
+}
+
+/// A folder that replaces every spoiler with a placeholder.
+struct SpoilerPlaceholder<'a>(&'a str);
+
+impl Fold for SpoilerPlaceholder<'_> {
+    fn fold_spoiler(&mut self, _node: Spoiler) -> Option<MarkdownElement> {
+        Some(Plain::new(self.0).into())
+    }
+}
+
+/// Renders elements as `formatted_body`.
+///
+/// A block element starts and ends a line, so a line break next to it is not rendered as `<br>`.
+fn formatted_body(elements: &[MarkdownElement]) -> String {
+    let is_block = |element: Option<&MarkdownElement>| {
+        matches!(
+            element,
+            Some(MarkdownElement::MultiLineCode(_) | MarkdownElement::BlockQuote(_))
+        )
+    };
+
+    let mut html = String::new();
+    for (index, element) in elements.iter().enumerate() {
+        match element {
+            MarkdownElement::Plain(x) => {
+                let mut text = x.content().replace("\r\n", "\n");
+                if index > 0 && is_block(elements.get(index - 1)) && text.starts_with('\n') {
+                    text.remove(0);
+                }
+                if is_block(elements.get(index + 1)) && text.ends_with('\n') {
+                    text.pop();
+                }
+                html.push_str(&escape(&text).replace('\n', "<br>"));
+            }
+            MarkdownElement::ItalicsStar(x) => html.push_str(&tag("em", x.content())),
+            MarkdownElement::ItalicsUnderscore(x) => html.push_str(&tag("em", x.content())),
+            MarkdownElement::Bold(x) => html.push_str(&tag("strong", x.content())),
+            MarkdownElement::Underline(x) => html.push_str(&tag("u", x.content())),
+            MarkdownElement::Strikethrough(x) => html.push_str(&tag("del", x.content())),
+            MarkdownElement::Spoiler(x) => html.push_str(&format!(
+                "<span data-mx-spoiler>{}</span>",
+                formatted_body(x.content().get())
+            )),
+            MarkdownElement::OneLineCode(x) => {
+                html.push_str(&format!("<code>{}</code>", escape(x.content())));
+            }
+            MarkdownElement::MultiLineCode(x) => {
+                let content = x.content();
+                let content = content
+                    .strip_prefix("\r\n")
+                    .or_else(|| content.strip_prefix('\n'))
+                    .unwrap_or(content);
+                match x.language() {
+                    Some(language) => html.push_str(&format!(
+                        "<pre><code class=\"language-{}\">{}</code></pre>",
+                        escape(language),
+                        escape(content)
+                    )),
+                    None => html.push_str(&format!("<pre><code>{}</code></pre>", escape(content))),
+                }
+            }
+            MarkdownElement::BlockQuote(x) => html.push_str(&tag("blockquote", x.content())),
+        }
+    }
+    html
+}
+
+fn tag(name: &str, content: &MarkdownElementCollection) -> String {
+    format!("<{}>{}</{}>", name, formatted_body(content.get()), name)
+}
+
+/// Parses `formatted_body` of a Matrix message into an AST.
+///
+/// Formatting tags are converted into elements as [the module documentation](crate::matrix) shows,
+/// as well as their aliases `<i>`, `<b>`, `<s>` and `<strike>`.
+/// Other tags of the HTML subset Matrix allows are degraded into text.
+///
+/// - `<br>`, and the end of a block like `<p>` and `<li>`, are converted into line breaks.
+/// - Headings are converted into bold text.
+/// - Items of lists are prefixed with `• ` or their numbers.
+/// - Links are converted into masked links, like `[label](url)`.
+/// - Images are replaced with their `alt` text.
+/// - `<hr>` is converted into `---`.
+/// - Reply fallbacks in `<mx-reply>` are removed.
+/// - Other tags are removed, keeping their content.
+///
+/// Whitespace in the source is collapsed into one space, and dropped at the start and the end of a line,
+/// except in `<pre>`, as browsers do.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::matrix::parse_formatted_body;
+///
+/// let ast = parse_formatted_body(
+///     "<mx-reply><blockquote>quoted reply</blockquote></mx-reply>\
+///      <h1>Title</h1><p>See <a href=\"https://example.com\">this</a></p><ul><li>a</li><li>b</li></ul>",
+/// );
+///
+/// assert_eq!(ast.to_string(), "**Title**\nSee [this](https://example.com)\n• a\n• b");
+/// ```
+pub fn parse_formatted_body(html: &str) -> MarkdownDocument {
+    let nodes = tree(html);
+    let mut builder = Builder::default();
+    builder.nodes(&nodes, false);
+    MarkdownDocument::new(builder.finish())
+}
+
+/// Attributes of an element, with lowercase names and decoded values.
+type Attributes = Vec<(String, String)>;
+
+/// A node of an HTML tree.
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Node {
+    Text(String),
+    Element {
+        name: String,
+        attributes: Attributes,
+        children: Vec<Node>,
+    },
+}
+
+/// An element which is not closed yet, with its name, attributes and children.
+type OpenElement = (String, Attributes, Vec<Node>);
+
+impl Node {
+    fn attribute(&self, key: &str) -> Option<&str> {
+        match self {
+            Node::Element { attributes, .. } => attributes
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.as_str()),
+            Node::Text(_) => None,
+        }
+    }
+
+    /// Returns the text content of the node.
+    fn text(&self) -> String {
+        match self {
+            Node::Text(text) => text.clone(),
+            Node::Element { children, .. } => children.iter().map(Node::text).collect(),
+        }
+    }
+}
+
+/// Elements which cannot have children.
+const VOID_ELEMENTS: [&str; 3] = ["br", "hr", "img"];
+
+/// Parses HTML into a tree. Unclosed tags are closed at the end of their parents, and stray closing tags are ignored.
+fn tree(html: &str) -> Vec<Node> {
+    // The root and open elements, with their names and attributes.
+    let mut stack: Vec<OpenElement> = vec![(String::new(), vec![], vec![])];
+    let mut pos = 0;
+
+    while pos < html.len() {
+        let rest = &html[pos..];
+
+        if rest.starts_with("<!--") {
+            pos += rest.find("-->").map_or(rest.len(), |end| end + 3);
+        } else if let Some((name, len)) = closing_tag(rest) {
+            pos += len;
+            if let Some(index) = stack.iter().rposition(|(open, _, _)| *open == name) {
+                while stack.len() > index {
+                    close(&mut stack);
+                }
+            }
+        } else if let Some((name, attributes, self_closing, len)) = opening_tag(rest) {
+            pos += len;
+            if self_closing || VOID_ELEMENTS.contains(&name.as_str()) {
+                let element = Node::Element {
+                    name,
+                    attributes,
+                    children: vec![],
+                };
+                stack.last_mut().unwrap().2.push(element);
+            } else {
+                stack.push((name, attributes, vec![]));
+            }
+        } else {
+            // Text continues until the next tag. A `<` which does not start a tag is text.
+            let first = rest.chars().next().map_or(1, char::len_utf8);
+            let end = rest[first..]
+                .find('<')
+                .map_or(rest.len(), |end| end + first);
+            let text = decode(&rest[..end]);
+            let children = &mut stack.last_mut().unwrap().2;
+            match children.last_mut() {
+                Some(Node::Text(last)) => last.push_str(&text),
+                _ => children.push(Node::Text(text)),
+            }
+            pos += end;
+        }
+    }
+
+    while stack.len() > 1 {
+        close(&mut stack);
+    }
+    stack.pop().unwrap().2
+}
+
+/// Closes the innermost open element.
+fn close(stack: &mut Vec<OpenElement>) {
+    let (name, attributes, children) = stack.pop().unwrap();
+    stack.last_mut().unwrap().2.push(Node::Element {
+        name,
+        attributes,
+        children,
+    });
+}
+
+/// Parses a closing tag like `</b>`, and returns the lowercase name and the length.
+fn closing_tag(text: &str) -> Option<(String, usize)> {
+    let rest = text.strip_prefix("</")?;
+    let name_len = tag_name_len(rest);
+    let end = rest.find('>')?;
+    (name_len > 0 && rest[name_len..end].trim().is_empty())
+        .then(|| (rest[..name_len].to_ascii_lowercase(), end + 3))
+}
+
+/// Parses an opening tag like `<a href="x">`, and returns the lowercase name, the attributes,
+/// whether it is self-closing, and the length.
+fn opening_tag(text: &str) -> Option<(String, Attributes, bool, usize)> {
+    let rest = text.strip_prefix('<')?;
+    let name_len = tag_name_len(rest);
+    if name_len == 0 {
+        return None;
+    }
+    let name = rest[..name_len].to_ascii_lowercase();
+
+    let mut attributes = Vec::new();
+    let mut pos = name_len;
+    loop {
+        let trimmed = rest[pos..].trim_start();
+        pos = rest.len() - trimmed.len();
+
+        if let Some(after) = trimmed.strip_prefix("/>") {
+            return Some((name, attributes, true, text.len() - after.len()));
+        }
+        if let Some(after) = trimmed.strip_prefix('>') {
+            return Some((name, attributes, false, text.len() - after.len()));
+        }
+
+        let key_len = trimmed
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+            .unwrap_or(trimmed.len());
+        if key_len == 0 {
+            // A stray `/` or an unterminated tag.
+            if trimmed.starts_with('/') {
+                pos += 1;
+                continue;
+            }
+            return None;
+        }
+        let key = trimmed[..key_len].to_ascii_lowercase();
+        pos += key_len;
+
+        let after_key = rest[pos..].trim_start();
+        let value = if let Some(value) = after_key.strip_prefix('=') {
+            let value = value.trim_start();
+            let (raw, len) = match value.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let end = value[1..].find(quote)?;
+                    (&value[1..1 + end], end + 2)
+                }
+                _ => {
+                    let end = value
+                        .find(|c: char| c.is_whitespace() || c == '>')
+                        .unwrap_or(value.len());
+                    (&value[..end], end)
+                }
+            };
+            pos = rest.len() - value.len() + len;
+            decode(raw)
+        } else {
+            String::new()
+        };
+        attributes.push((key, value));
+    }
+}
+
+/// Returns the length of the tag name at the start of the text.
+fn tag_name_len(text: &str) -> usize {
+    if !text.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return 0;
+    }
+    text.find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .unwrap_or(text.len())
+}
+
+/// Decodes entity references in text.
+fn decode(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut pos = 0;
+    while let Some(offset) = text[pos..].find('&') {
+        let start = pos + offset;
+        decoded.push_str(&text[pos..start]);
+        match entity(&text[start..]) {
+            Some((c, len)) => {
+                decoded.push(c);
+                pos = start + len;
+            }
+            None => {
+                decoded.push('&');
+                pos = start + 1;
+            }
+        }
+    }
+    decoded.push_str(&text[pos..]);
+    decoded
+}
+
+/// Builds elements from an HTML tree.
+#[derive(Default)]
+struct Builder {
+    elements: Vec<MarkdownElement>,
+    /// `true` if a block has ended, so the next content starts a new line.
+    block_ended: bool,
+    /// `true` if the elements are the content of styled text, which may start in the middle of a line.
+    inline: bool,
+}
+
+impl Builder {
+    /// Adds nodes. `preformatted` is `true` inside `<pre>`, where line breaks are kept.
+    fn nodes(&mut self, nodes: &[Node], preformatted: bool) {
+        for node in nodes {
+            self.node(node, preformatted);
+        }
+    }
+
+    fn node(&mut self, node: &Node, preformatted: bool) {
+        let (name, children) = match node {
+            Node::Text(text) if preformatted => return self.text(text),
+            Node::Text(text) => return self.collapsed_text(text),
+            Node::Element { name, children, .. } => (name.as_str(), children),
+        };
+
+        match name {
+            "em" | "i" => self.styled(children, |c| ItalicsStar::new(c).into()),
+            "strong" | "b" => self.styled(children, |c| Bold::new(c).into()),
+            "u" => self.styled(children, |c| Underline::new(c).into()),
+            "del" | "s" | "strike" => self.styled(children, |c| Strikethrough::new(c).into()),
+            "span" if node.attribute("data-mx-spoiler").is_some() => {
+                self.styled(children, |c| Spoiler::new(c).into())
+            }
+            "code" => self.push(OneLineCode::new(node.text()).into()),
+            "pre" => {
+                // The language is given as the class of `<code>` in `<pre>`.
+                let language = children.iter().find_map(|child| {
+                    child
+                        .attribute("class")?
+                        .split_whitespace()
+                        .find_map(|class| class.strip_prefix("language-"))
+                        .map(String::from)
+                });
+                let mut content = format!("\n{}", node.text());
+                if !content.ends_with('\n') {
+                    content.push('\n');
+                }
+                self.block(|builder| builder.push(MultiLineCode::new(content, language).into()));
+            }
+            "blockquote" => self.block(|builder| {
+                let mut inner = Builder::default();
+                inner.nodes(children, preformatted);
+                builder.push(BlockQuote::new(inner.finish()).into());
+            }),
+            "br" => {
+                self.new_line();
+                self.block_ended = false;
+            }
+            "hr" => self.block(|builder| builder.text("---")),
+            "img" => {
+                if let Some(alt) = node.attribute("alt").or_else(|| node.attribute("title")) {
+                    self.text(alt);
+                }
+            }
+            "a" => match node.attribute("href") {
+                Some(href) if node.text() != href => {
+                    self.text("[");
+                    self.nodes(children, preformatted);
+                    self.text(&format!("]({})", href));
+                }
+                _ => self.nodes(children, preformatted),
+            },
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block(|builder| builder.styled(children, |c| Bold::new(c).into()))
+            }
+            "ul" | "ol" => {
+                let start = node
+                    .attribute("start")
+                    .and_then(|start| start.parse::<usize>().ok())
+                    .unwrap_or(1);
+                let items = children
+                    .iter()
+                    .filter(|child| matches!(child, Node::Element { name, .. } if name == "li"));
+                for (index, item) in items.enumerate() {
+                    self.block(|builder| {
+                        if name == "ol" {
+                            builder.text(&format!("{}. ", start.saturating_add(index)));
+                        } else {
+                            builder.text("• ");
+                        }
+                        if let Node::Element { children, .. } = item {
+                            builder.nodes(children, preformatted);
+                        }
+                    });
+                }
+            }
+            "p" | "div" | "li" | "tr" | "details" | "summary" | "table" | "thead" | "tbody" => {
+                self.block(|builder| builder.nodes(children, preformatted))
+            }
+            "td" | "th" => {
+                self.nodes(children, preformatted);
+                self.text(" ");
+            }
+            "mx-reply" => {}
+            _ => self.nodes(children, preformatted),
+        }
+    }
+
+    /// Adds styled text, unless its content is empty.
+    fn styled(
+        &mut self,
+        children: &[Node],
+        element: impl FnOnce(Vec<MarkdownElement>) -> MarkdownElement,
+    ) {
+        let mut inner = Builder {
+            inline: true,
+            ..Default::default()
+        };
+        inner.nodes(children, false);
+        let content = inner.finish();
+        if !content.is_empty() {
+            self.push(element(content));
+        }
+    }
+
+    /// Adds a block, which starts and ends a line.
+    fn block(&mut self, content: impl FnOnce(&mut Self)) {
+        if !self.at_line_start() {
+            self.new_line();
+        }
+        self.block_ended = false;
+        content(self);
+        self.block_ended = true;
+    }
+
+    fn text(&mut self, text: &str) {
+        if !text.is_empty() {
+            self.push(Plain::new(text).into());
+        }
+    }
+
+    /// Adds text outside `<pre>`, where a run of whitespace is rendered as one space,
+    /// and whitespace at the start of a line, like between `</p>` and `<p>`, is not rendered.
+    fn collapsed_text(&mut self, text: &str) {
+        let mut skip_space = self.block_ended
+            || (self.at_line_start() && !(self.inline && self.elements.is_empty()))
+            || matches!(self.elements.last(), Some(MarkdownElement::Plain(plain)) if plain.content().ends_with(' '));
+
+        let mut collapsed = String::with_capacity(text.len());
+        for c in text.chars() {
+            if !c.is_ascii_whitespace() {
+                collapsed.push(c);
+                skip_space = false;
+            } else if !skip_space {
+                collapsed.push(' ');
+                skip_space = true;
+            }
+        }
+        self.text(&collapsed);
+    }
+
+    /// Ends the line, removing spaces at its end.
+    fn new_line(&mut self) {
+        if let Some(MarkdownElement::Plain(plain)) = self.elements.last_mut() {
+            let len = plain.content().trim_end_matches(' ').len();
+            plain.content_mut().truncate(len);
+        }
+        self.elements.push(Plain::new("\n").into());
+    }
+
+    fn push(&mut self, element: MarkdownElement) {
+        if std::mem::take(&mut self.block_ended) && !self.at_line_start() {
+            self.new_line();
+        }
+        self.elements.push(element);
+    }
+
+    fn at_line_start(&self) -> bool {
+        match self.elements.last() {
+            None => true,
+            Some(MarkdownElement::Plain(plain)) => plain.content().ends_with('\n'),
+            Some(_) => false,
+        }
+    }
+
+    fn finish(self) -> Vec<MarkdownElement> {
+        merge_plain(self.elements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::parse;
+
+    fn formatted(text: &str) -> String {
+        parse(text).to_matrix(&MatrixOption::new()).formatted_body
+    }
+
+    #[test]
+    fn test_to_matrix() {
+        assert_eq!(
+            formatted("*a* _b_ **c** __d__ ~~e~~ ||f|| `g`"),
+            "<em>a</em> <em>b</em> <strong>c</strong> <u>d</u> <del>e</del> <span data-mx-spoiler>f</span> <code>g</code>"
+        );
+        assert_eq!(formatted("a\nb & <c>"), "a<br>b &amp; &lt;c&gt;");
+        assert_eq!(
+            formatted("a\n```rs\nfn main() {}\n```\nb"),
+            "a<pre><code class=\"language-rs\">fn main() {}\n</code></pre>b"
+        );
+    }
+
+    #[test]
+    fn test_block_quote_to_matrix() {
+        let ast = MarkdownDocument::new(vec![
+            plain("a\n"),
+            block_quote(vec![plain("b\n"), bold("c")]),
+            plain("\n\nd"),
+        ]);
+        let message = ast.to_matrix(&MatrixOption::new());
+
+        assert_eq!(
+            message.formatted_body,
+            "a<blockquote>b<br><strong>c</strong></blockquote><br>d"
+        );
+        assert_eq!(message.body, "a\n> b\n> **c**\n\nd");
+    }
+
+    #[test]
+    fn test_body() {
+        let ast = parse("a ||**b**|| c");
+
+        assert_eq!(ast.to_matrix(&MatrixOption::new()).body, "a [Spoiler] c");
+        assert_eq!(
+            ast.to_matrix(&MatrixOption::new().spoiler_placeholder(Some("▒".to_string())))
+                .body,
+            "a ▒ c"
+        );
+        assert_eq!(
+            spoiler("x")
+                .to_matrix(&MatrixOption::new().spoiler_placeholder(None))
+                .body,
+            "||x||"
+        );
+    }
+
+    #[test]
+    fn test_parse_formatted_body() {
+        assert_eq!(
+            parse_formatted_body(
+                "<em>a</em><i>b</i><STRONG>c</STRONG><b>d</b><u>e</u><del>f</del><s>g</s><strike>h</strike>"
+            ),
+            MarkdownDocument::new(vec![
+                italics_star("a"),
+                italics_star("b"),
+                bold("c"),
+                bold("d"),
+                underline("e"),
+                strikethrough("f"),
+                strikethrough("g"),
+                strikethrough("h"),
+            ])
+        );
+        assert_eq!(
+            parse_formatted_body(
+                "<span data-mx-spoiler=\"reason\">a</span> <span data-mx-color=\"#f00\">b</span> <code>&lt;c&gt;</code>"
+            ),
+            MarkdownDocument::new(vec![
+                spoiler("a"),
+                plain(" b "),
+                one_line_code("<c>"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_formatted_body_blocks() {
+        assert_eq!(
+            parse_formatted_body(
+                "a<pre><code class=\"language-rust\">fn main() {}\n</code></pre>b<blockquote><p>c</p>\n<p>d</p></blockquote>e"
+            ),
+            MarkdownDocument::new(vec![
+                plain("a\n"),
+                multi_line_code("\nfn main() {}\n", Some("rust".to_string())),
+                plain("\nb\n"),
+                block_quote("c\nd"),
+                plain("\ne"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_formatted_body_whitespace_between_blocks() {
+        assert_eq!(
+            parse_formatted_body("<p>a</p>\n<p>b</p>\n"),
+            MarkdownDocument::new(vec![plain("a\nb")])
+        );
+        assert_eq!(
+            parse_formatted_body("\n<ul>\n  <li>\n    a <b>b</b>\n  </li>\n</ul>\n<p> c</p>"),
+            MarkdownDocument::new(vec![plain("• a "), bold("b"), plain("\nc")])
+        );
+        assert_eq!(
+            parse_formatted_body("a<b> b</b>"),
+            MarkdownDocument::new(vec![plain("a"), bold(" b")])
+        );
+    }
+
+    #[test]
+    fn test_parse_formatted_body_non_ascii() {
+        assert_eq!(
+            parse_formatted_body("é"),
+            MarkdownDocument::new(vec![plain("é")])
+        );
+        assert_eq!(
+            parse_formatted_body("<n>👍</n><b>日本</b>é<a"),
+            MarkdownDocument::new(vec![plain("👍"), bold("日本"), plain("é<a")])
+        );
+    }
+
+    #[test]
+    fn test_parse_formatted_body_degraded() {
+        assert_eq!(
+            parse_formatted_body(
+                "<ol start=\"3\"><li>a</li><li><a href=\"https://b\">https://b</a></li></ol><hr><img src=\"mxc://x\" alt=\"img\"><table><tr><th>h</th></tr></table>"
+            )
+            .to_string(),
+            "3. a\n4. https://b\n---\nimg\nh "
+        );
+        assert_eq!(
+            parse_formatted_body("a<br/>b<!-- c --> 1 < 2 &amp;&amp; <unknown>d</unknown>")
+                .to_string(),
+            "a\nb 1 < 2 && d"
+        );
+        assert_eq!(
+            parse_formatted_body("<ol start=\"18446744073709551615\"><li>a</li><li>b</li></ol>")
+                .to_string(),
+            "18446744073709551615. a\n18446744073709551615. b"
+        );
+    }
+
+    #[test]
+    fn test_tree() {
+        assert_eq!(
+            tree("<b>a<i>b</b>c</i><br>"),
+            vec![
+                Node::Element {
+                    name: "b".to_string(),
+                    attributes: vec![],
+                    children: vec![
+                        Node::Text("a".to_string()),
+                        Node::Element {
+                            name: "i".to_string(),
+                            attributes: vec![],
+                            children: vec![Node::Text("b".to_string())],
+                        },
+                    ],
+                },
+                Node::Text("c".to_string()),
+                Node::Element {
+                    name: "br".to_string(),
+                    attributes: vec![],
+                    children: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_opening_tag() {
+        assert_eq!(
+            opening_tag("<a href=\"x&amp;y\" data-x=z disabled>"),
+            Some((
+                "a".to_string(),
+                vec![
+                    ("href".to_string(), "x&y".to_string()),
+                    ("data-x".to_string(), "z".to_string()),
+                    ("disabled".to_string(), String::new()),
+                ],
+                false,
+                36
+            ))
+        );
+        assert_eq!(
+            opening_tag("<br/>"),
+            Some(("br".to_string(), vec![], true, 5))
+        );
+        assert_eq!(opening_tag("< b>"), None);
+        assert_eq!(opening_tag("<a href=\"x"), None);
+    }
+
+    #[test]
+    fn test_matrix_round_trip() {
+        let ast = parse("*a* **b** __c__ ~~d~~ ||e|| `f`\ng & <h>");
+        let message = ast.to_matrix(&MatrixOption::new());
+
+        assert_eq!(parse_formatted_body(&message.formatted_body), ast);
+    }
+}
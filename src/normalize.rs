@@ -0,0 +1,566 @@
+//! Normalization of an AST, used by [`MarkdownDocument::normalize()`] and [`Semantic`](crate::semantic::Semantic).
+
+use crate::ast::{
+    BlockQuote, Bold, ElementKind, Fold, ItalicsStar, ItalicsUnderscore, MarkdownDocument,
+    MarkdownElement, MarkdownElementCollection, MultiLineCode, OneLineCode, Plain, Spoiler,
+    Strikethrough, Underline,
+};
+use crate::delimiter::Delimiter;
+
+/// Normalizes a document. See [`MarkdownDocument::normalize()`] for the rules.
+pub fn normalize(document: MarkdownDocument) -> MarkdownDocument {
+    Normalizer.fold_document(document)
+}
+
+/// Rebuilds a document in the canonical form, where documents rendered identically in Discord are equal.
+///
+/// Italics text wrapped in `_` is replaced with italics text wrapped in `*`, and then the document is normalized.
+/// Normalization may wrap italics text in `_` again where `*` would be parsed differently, like in `Bold(ItalicsStar(x))`.
+pub fn canonicalize(document: MarkdownDocument) -> MarkdownDocument {
+    normalize(Canonicalizer.fold_document(document))
+}
+
+/// A folder that replaces every [`ItalicsUnderscore`] with [`ItalicsStar`].
+struct Canonicalizer;
+
+impl Fold for Canonicalizer {
+    fn fold_italics_underscore(&mut self, node: ItalicsUnderscore) -> Option<MarkdownElement> {
+        let content = self.fold_element_collection(node.into_content());
+        Some(ItalicsStar::new(content).into())
+    }
+}
+
+/// A folder that rebuilds an AST in the normal form.
+struct Normalizer;
+
+impl Normalizer {
+    /// Normalizes styled text of the given kind.
+    ///
+    /// Returns no elements if the content is empty after normalization,
+    /// and may return more than one element as [`delimit()`] does.
+    fn fold_styled(
+        &mut self,
+        kind: ElementKind,
+        content: Vec<MarkdownElement>,
+    ) -> Vec<MarkdownElement> {
+        let content = self.fold_element_collection(content.into()).into_inner();
+
+        // Children of the same kind are redundant, e.g. bold text in bold text.
+        let flattened = content
+            .into_iter()
+            .flat_map(|child| {
+                if child.kind() == kind {
+                    into_children(child)
+                } else {
+                    vec![child]
+                }
+            })
+            .collect();
+
+        let merged = merge_plain(flattened);
+        if merged.is_empty() {
+            vec![]
+        } else {
+            delimit(kind, merged)
+        }
+    }
+}
+
+impl Fold for Normalizer {
+    fn fold_element_collection(
+        &mut self,
+        node: MarkdownElementCollection,
+    ) -> MarkdownElementCollection {
+        let folded = node
+            .into_inner()
+            .into_iter()
+            .flat_map(|element| {
+                let kind = element.kind();
+                if is_styled(kind) {
+                    self.fold_styled(kind, into_children(element))
+                } else {
+                    self.fold_element(element).into_iter().collect()
+                }
+            })
+            .collect();
+
+        merge_plain(folded).into()
+    }
+
+    fn fold_plain(&mut self, node: Plain) -> Option<MarkdownElement> {
+        if node.content().is_empty() {
+            None
+        } else {
+            Some(node.into())
+        }
+    }
+
+    fn fold_one_line_code(&mut self, node: OneLineCode) -> Option<MarkdownElement> {
+        if node.content().is_empty() {
+            None
+        } else {
+            Some(node.into())
+        }
+    }
+
+    fn fold_multi_line_code(&mut self, node: MultiLineCode) -> Option<MarkdownElement> {
+        if node.content().is_empty() {
+            None
+        } else {
+            Some(node.into())
+        }
+    }
+
+    fn fold_block_quote(&mut self, node: BlockQuote) -> Option<MarkdownElement> {
+        // Nested block quotes are rendered differently, so they are not flattened.
+        let content = self.fold_element_collection(node.into_content());
+        if content.get().is_empty() {
+            None
+        } else {
+            Some(BlockQuote::new(content).into())
+        }
+    }
+}
+
+/// Builds styled text that renders the same and is parsed back into the same AST, if possible.
+///
+/// The parser ends styled text at the first closing delimiter after the opening one,
+/// so `Bold(ItalicsStar(x))` generated as `***x***` is parsed as `Bold("*x")` and `"*"`.
+/// If the closing delimiter would be read early, the styled text is rewritten in the following order:
+///
+/// 1. Italics text uses the other delimiter.
+/// 2. Adjacent italics children using the character of the delimiter are merged,
+///    and the last child is changed to use the other delimiter, like `Bold(ItalicsUnderscore(x))`.
+/// 3. Descendants rendered in the same style, like italics text in italics text, are unwrapped.
+/// 4. Italics text is moved into each child,
+///    like `ItalicsStar(Bold(x), Underline(y))` into `Bold(ItalicsStar(x))` and `Underline(ItalicsStar(y))`.
+///
+/// The children must be normalized and not empty.
+fn delimit(kind: ElementKind, children: Vec<MarkdownElement>) -> Vec<MarkdownElement> {
+    let kinds = match other_italics(kind) {
+        Some(other) => vec![kind, other],
+        None => vec![kind],
+    };
+    let closing =
+        |children: &[MarkdownElement]| kinds.iter().copied().find(|&kind| closes(kind, children));
+
+    if let Some(kind) = closing(&children) {
+        return vec![styled(kind, children)];
+    }
+    let children = separate(kind, children);
+    if let Some(kind) = closing(&children) {
+        return vec![styled(kind, children)];
+    }
+    let children = separate(kind, unwrap_same_style(kind, children));
+    match closing(&children) {
+        Some(kind) => vec![styled(kind, children)],
+        None if kinds.len() == 2 => move_italics(kind, children),
+        None => vec![styled(kind, children)],
+    }
+}
+
+/// Returns `true` if the parser reads the closing delimiter of styled text right after its content.
+fn closes(kind: ElementKind, children: &[MarkdownElement]) -> bool {
+    let delimiter = delimiter(kind).as_str();
+    let content = children.iter().map(ToString::to_string).collect::<String>();
+    format!("{}{}", content, delimiter).find(delimiter) == Some(content.len())
+}
+
+/// Merges adjacent italics text whose delimiter is the first character of the delimiter of `kind`,
+/// and changes the delimiter of the last child if it is such italics text.
+fn separate(kind: ElementKind, children: Vec<MarkdownElement>) -> Vec<MarkdownElement> {
+    let conflicting = match delimiter(kind).as_str().chars().next() {
+        Some('*') => ElementKind::ItalicsStar,
+        Some('_') => ElementKind::ItalicsUnderscore,
+        _ => return children,
+    };
+
+    let mut separated = merge_adjacent(conflicting, children);
+    if let Some(last) = separated.pop() {
+        if last.kind() == conflicting {
+            separated.extend(swap_italics(last));
+        } else {
+            separated.push(last);
+        }
+    }
+    separated
+}
+
+/// Merges adjacent italics text of the given kind in the children and their descendants,
+/// since their delimiters are read as another delimiter, like `**` of `*a**b*`.
+fn merge_adjacent(italics: ElementKind, children: Vec<MarkdownElement>) -> Vec<MarkdownElement> {
+    let mut merged: Vec<MarkdownElement> = Vec::with_capacity(children.len());
+
+    for child in children {
+        let child_kind = child.kind();
+        let children = if is_styled(child_kind) && child_kind != italics {
+            delimit(child_kind, merge_adjacent(italics, into_children(child)))
+        } else {
+            vec![child]
+        };
+
+        for child in children {
+            match merged.last() {
+                Some(last) if last.kind() == italics && child.kind() == italics => {
+                    let mut content = into_children(merged.pop().expect("there is the last child"));
+                    content.extend(into_children(child));
+                    merged.extend(delimit(italics, merge_plain(content)));
+                }
+                _ => merged.push(child),
+            }
+        }
+    }
+
+    merged
+}
+
+/// Rewrites italics text so that it does not end with its delimiter, rendering the same.
+///
+/// The delimiter is changed, unwrapping italics text inside it if necessary.
+/// If the other delimiter cannot be read correctly either, the last child is split off,
+/// and italics text is moved into it if it is styled text, like `Bold(ItalicsStar(x))` from `ItalicsStar(Bold(x))`.
+fn swap_italics(italics: MarkdownElement) -> Vec<MarkdownElement> {
+    let kind = italics.kind();
+    let other = other_italics(kind).expect("the element is italics text");
+    let mut children = into_children(italics);
+
+    if closes(other, &children) {
+        return vec![styled(other, children)];
+    }
+    let unwrapped = unwrap_same_style(other, children.clone());
+    if closes(other, &unwrapped) {
+        return vec![styled(other, unwrapped)];
+    }
+
+    let last = children.pop().expect("italics text is not empty");
+    let mut split = if children.is_empty() {
+        vec![]
+    } else {
+        delimit(kind, children)
+    };
+    split.extend(move_italics(other, unwrap_same_style(other, vec![last])));
+    split
+}
+
+/// Moves italics text into each child, which renders the same as italics text of the children.
+///
+/// Children which are not styled text are wrapped in italics text one by one.
+fn move_italics(kind: ElementKind, children: Vec<MarkdownElement>) -> Vec<MarkdownElement> {
+    let other = other_italics(kind).expect("the kind is italics text");
+
+    children
+        .into_iter()
+        .flat_map(|child| {
+            let child_kind = child.kind();
+            if is_styled(child_kind) && other_italics(child_kind).is_none() {
+                let italics = delimit(kind, into_children(child));
+                delimit(child_kind, italics)
+            } else {
+                let child = vec![child];
+                let kind = [kind, other]
+                    .into_iter()
+                    .find(|&kind| closes(kind, &child))
+                    .unwrap_or(kind);
+                vec![styled(kind, child)]
+            }
+        })
+        .collect()
+}
+
+/// Unwraps descendants rendered in the same style as `kind`, which are redundant.
+fn unwrap_same_style(kind: ElementKind, children: Vec<MarkdownElement>) -> Vec<MarkdownElement> {
+    let unwrapped = children
+        .into_iter()
+        .flat_map(|child| {
+            let child_kind = child.kind();
+            if !is_styled(child_kind) {
+                return vec![child];
+            }
+
+            let grandchildren = unwrap_same_style(kind, into_children(child));
+            let same_style = child_kind == kind
+                || (other_italics(kind).is_some() && other_italics(child_kind).is_some());
+            if same_style {
+                grandchildren
+            } else if grandchildren.is_empty() {
+                vec![]
+            } else {
+                delimit(child_kind, grandchildren)
+            }
+        })
+        .collect();
+
+    merge_plain(unwrapped)
+}
+
+/// Returns the kind of italics text using the other delimiter, or `None` if `kind` is not italics text.
+fn other_italics(kind: ElementKind) -> Option<ElementKind> {
+    match kind {
+        ElementKind::ItalicsStar => Some(ElementKind::ItalicsUnderscore),
+        ElementKind::ItalicsUnderscore => Some(ElementKind::ItalicsStar),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `kind` is styled text, which is wrapped in delimiters and contains other elements.
+fn is_styled(kind: ElementKind) -> bool {
+    matches!(
+        kind,
+        ElementKind::ItalicsStar
+            | ElementKind::ItalicsUnderscore
+            | ElementKind::Bold
+            | ElementKind::Underline
+            | ElementKind::Strikethrough
+            | ElementKind::Spoiler
+    )
+}
+
+/// Returns the delimiter of styled text.
+fn delimiter(kind: ElementKind) -> Delimiter {
+    match kind {
+        ElementKind::ItalicsStar => Delimiter::ItalicsStar,
+        ElementKind::ItalicsUnderscore => Delimiter::ItalicsUnderscore,
+        ElementKind::Bold => Delimiter::Bold,
+        ElementKind::Underline => Delimiter::Underline,
+        ElementKind::Strikethrough => Delimiter::Strikethrough,
+        ElementKind::Spoiler => Delimiter::Spoiler,
+        kind => unreachable!("{} is not styled text", kind),
+    }
+}
+
+/// Builds styled text of the given kind.
+fn styled(kind: ElementKind, children: Vec<MarkdownElement>) -> MarkdownElement {
+    match kind {
+        ElementKind::ItalicsStar => ItalicsStar::new(children).into(),
+        ElementKind::ItalicsUnderscore => ItalicsUnderscore::new(children).into(),
+        ElementKind::Bold => Bold::new(children).into(),
+        ElementKind::Underline => Underline::new(children).into(),
+        ElementKind::Strikethrough => Strikethrough::new(children).into(),
+        ElementKind::Spoiler => Spoiler::new(children).into(),
+        kind => unreachable!("{} is not styled text", kind),
+    }
+}
+
+/// Merges adjacent plain text and removes empty plain text.
+pub(crate) fn merge_plain(elements: Vec<MarkdownElement>) -> Vec<MarkdownElement> {
+    let mut merged: Vec<MarkdownElement> = Vec::with_capacity(elements.len());
+
+    for element in elements {
+        match (merged.last_mut(), element) {
+            (_, MarkdownElement::Plain(plain)) if plain.content().is_empty() => {}
+            (Some(MarkdownElement::Plain(last)), MarkdownElement::Plain(plain)) => {
+                last.content_mut().push_str(plain.content());
+            }
+            (_, element) => merged.push(element),
+        }
+    }
+
+    merged
+}
+
+/// Returns the children of styled text, or the element itself if it cannot contain other elements.
+fn into_children(element: MarkdownElement) -> Vec<MarkdownElement> {
+    let content = match element {
+        MarkdownElement::ItalicsStar(x) => x.into_content(),
+        MarkdownElement::ItalicsUnderscore(x) => x.into_content(),
+        MarkdownElement::Bold(x) => x.into_content(),
+        MarkdownElement::Underline(x) => x.into_content(),
+        MarkdownElement::Strikethrough(x) => x.into_content(),
+        MarkdownElement::Spoiler(x) => x.into_content(),
+        MarkdownElement::BlockQuote(x) => x.into_content(),
+        element => return vec![element],
+    };
+
+    content.into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::parse;
+
+    #[test]
+    fn test_merge_plain() {
+        assert_eq!(
+            normalize(MarkdownDocument::new(vec![
+                plain("a"),
+                plain(""),
+                plain("b"),
+                bold("c"),
+                plain("d"),
+                plain("e"),
+            ])),
+            MarkdownDocument::new(vec![plain("ab"), bold("c"), plain("de")])
+        );
+    }
+
+    #[test]
+    fn test_remove_empty() {
+        assert_eq!(
+            normalize(MarkdownDocument::new(vec![
+                bold(vec![]),
+                plain("a"),
+                spoiler(vec![underline(""), plain("")]),
+                one_line_code(""),
+                plain("b"),
+                multi_line_code("", Some("js".to_string())),
+                block_quote(vec![]),
+            ])),
+            MarkdownDocument::new(vec![plain("ab")])
+        );
+    }
+
+    #[test]
+    fn test_flatten_nesting() {
+        assert_eq!(
+            normalize(MarkdownDocument::new(vec![bold(vec![bold("x")])])),
+            MarkdownDocument::new(vec![bold("x")])
+        );
+        assert_eq!(
+            normalize(MarkdownDocument::new(vec![spoiler(vec![
+                plain("a"),
+                spoiler(vec![plain("b"), spoiler("c")]),
+                italics_star("d"),
+            ])])),
+            MarkdownDocument::new(vec![spoiler(vec![plain("abc"), italics_star("d")])])
+        );
+        assert_eq!(
+            normalize(MarkdownDocument::new(vec![italics_star(vec![
+                italics_underscore("x")
+            ])])),
+            MarkdownDocument::new(vec![italics_star(vec![italics_underscore("x")])])
+        );
+        assert_eq!(
+            normalize(MarkdownDocument::new(vec![block_quote(vec![block_quote(
+                "x"
+            )])])),
+            MarkdownDocument::new(vec![block_quote(vec![block_quote("x")])])
+        );
+    }
+
+    #[test]
+    fn test_idempotent() {
+        let once = normalize(MarkdownDocument::new(vec![
+            plain("a"),
+            underline(vec![underline(vec![plain("b"), plain("c")]), bold(vec![])]),
+            plain("d"),
+        ]));
+
+        assert_eq!(
+            once,
+            MarkdownDocument::new(vec![plain("a"), underline("bc"), plain("d")])
+        );
+        assert_eq!(normalize(once.clone()), once);
+    }
+
+    #[test]
+    fn test_parse_output_is_normalized() {
+        let messages = [
+            include_str!("../tests/example.md"),
+            "*a* **b** ***c*** ****d****",
+            "__a ___b_ c__ ~~~~ |||| `` ```a``` ``````",
+            "plain **bold __underline ||spoiler||__**",
+        ];
+
+        for message in messages {
+            assert_eq!(normalize(parse(message)), parse(message));
+        }
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        assert_eq!(
+            canonicalize(MarkdownDocument::new(vec![
+                italics_underscore("a"),
+                plain("b"),
+                plain("c"),
+                bold(vec![italics_underscore(vec![italics_star("d")])]),
+            ])),
+            MarkdownDocument::new(vec![
+                italics_star("a"),
+                plain("bc"),
+                bold(vec![italics_underscore("d")]),
+            ])
+        );
+        assert_eq!(
+            canonicalize(parse("_a_ *a*")),
+            MarkdownDocument::new(vec![italics_star("a"), plain(" "), italics_star("a")])
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let ast = normalize(MarkdownDocument::new(vec![
+            plain("hello"),
+            plain(" "),
+            bold(vec![bold("world")]),
+            strikethrough(vec![]),
+            plain("!"),
+            spoiler(vec![one_line_code("code"), spoiler(" and more")]),
+        ]));
+
+        assert_eq!(parse(&ast.to_string()), ast);
+    }
+
+    #[test]
+    fn test_rewrite_nesting() {
+        assert_eq!(
+            normalize(MarkdownDocument::new(vec![bold(vec![italics_star("x")])])),
+            MarkdownDocument::new(vec![bold(vec![italics_underscore("x")])])
+        );
+        assert_eq!(
+            normalize(MarkdownDocument::new(vec![underline(vec![
+                italics_underscore("a"),
+                italics_underscore("b"),
+            ])])),
+            MarkdownDocument::new(vec![underline(vec![italics_star("ab")])])
+        );
+        assert_eq!(
+            normalize(MarkdownDocument::new(vec![italics_star(vec![
+                bold("a"),
+                underline("b"),
+            ])])),
+            MarkdownDocument::new(vec![
+                bold(vec![italics_underscore("a")]),
+                underline(vec![italics_star("b")]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_round_trip_nesting() {
+        fn styled(kind: usize, content: Vec<MarkdownElement>) -> MarkdownElement {
+            match kind {
+                0 => italics_star(content),
+                1 => italics_underscore(content),
+                2 => bold(content),
+                3 => underline(content),
+                4 => strikethrough(content),
+                _ => spoiler(content),
+            }
+        }
+
+        // Pairs of elements nested once, and single elements nested up to three times.
+        let mut sequences = vec![vec![plain("x")]];
+        for depth in 0..3 {
+            let mut elements = vec![plain("x")];
+            for sequence in &sequences {
+                elements.extend((0..6).map(|kind| styled(kind, sequence.clone())));
+            }
+            sequences = elements.iter().map(|a| vec![a.clone()]).collect();
+            if depth == 0 {
+                for a in &elements {
+                    sequences.extend(elements.iter().map(|b| vec![a.clone(), b.clone()]));
+                }
+            }
+        }
+
+        for sequence in sequences {
+            let ast = normalize(MarkdownDocument::new(sequence));
+
+            assert_eq!(parse(&ast.to_string()), ast, "{}", ast);
+            assert_eq!(normalize(ast.clone()), ast);
+        }
+    }
+}
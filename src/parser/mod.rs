@@ -10,10 +10,11 @@ use crate::ast::{
     MarkdownElementCollection, MultiLineCode, OneLineCode, Plain, Spoiler, Strikethrough,
     Underline,
 };
+use crate::delimiter::Delimiter;
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::{alphanumeric1, newline},
+    character::complete::{alphanumeric1, line_ending},
     combinator::{map, map_parser, opt, peek, rest},
     multi::many0,
     sequence::{delimited, pair, terminated},
@@ -32,7 +33,7 @@ fn markdown_element_collection(i: &str) -> IResult<&str, MarkdownElementCollecti
 }
 
 /// Parses a markdown element.
-fn markdown_element(i: &str) -> IResult<&str, MarkdownElement> {
+pub fn markdown_element(i: &str) -> IResult<&str, MarkdownElement> {
     alt((markdown_element_not_plain, markdown_element_plain))(i)
 }
 
@@ -138,11 +139,13 @@ fn one_line_code(i: &str) -> IResult<&str, OneLineCode> {
 }
 
 /// Parses a multiline code block.
+///
+/// The language is recognized if it is followed by either `\n` or `\r\n`.
 fn multi_line_code(i: &str) -> IResult<&str, MultiLineCode> {
     map(
         map_parser(
             delimited(tag("```"), take_before1(tag("```")), tag("```")),
-            pair(opt(terminated(alphanumeric1, peek(newline))), rest),
+            pair(opt(terminated(alphanumeric1, peek(line_ending))), rest),
         ),
         |(lang, content): (Option<&str>, &str)| {
             MultiLineCode::new(content, lang.map(|x| x.to_string()))
@@ -150,6 +153,40 @@ fn multi_line_code(i: &str) -> IResult<&str, MultiLineCode> {
     )(i)
 }
 
+/// Finds opening delimiters that are never closed.
+///
+/// Returns the byte offset and the kind of each unterminated delimiter, outermost first.
+/// These are the delimiters that the parsers above fail to match and leave as plain text.
+/// The content after an unterminated code delimiter is not scanned, as it is not parsed as markdown.
+pub fn unterminated_delimiters(i: &str) -> Vec<(usize, Delimiter)> {
+    let mut unterminated = Vec::new();
+    let mut rest = i;
+
+    while let Some(c) = rest.chars().next() {
+        if let Ok((r, _)) = markdown_element_not_plain(rest) {
+            rest = r;
+            continue;
+        }
+
+        let offset = i.len() - rest.len();
+        match Delimiter::LONGEST_FIRST
+            .into_iter()
+            .find(|d| rest.starts_with(d.as_str()))
+        {
+            Some(delimiter) => {
+                unterminated.push((offset, delimiter));
+                if delimiter.is_code() {
+                    break;
+                }
+                rest = &rest[delimiter.as_str().len()..];
+            }
+            None => rest = &rest[c.len_utf8()..],
+        }
+    }
+
+    unterminated
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -586,6 +623,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_multi_line_code_crlf_ok() {
+        assert_eq!(
+            multi_line_code("```\r\nhello\r\nworld\r\n```"),
+            Ok(("", MultiLineCode::new("\r\nhello\r\nworld\r\n", None)))
+        );
+        assert_eq!(
+            multi_line_code("```js\r\nhello\r\n```"),
+            Ok((
+                "",
+                MultiLineCode::new("\r\nhello\r\n", Some("js".to_string()))
+            ))
+        );
+        assert_eq!(
+            multi_line_code("```js\rhello```"),
+            Ok(("", MultiLineCode::new("js\rhello", None)))
+        );
+    }
+
+    #[test]
+    fn test_unterminated_delimiters() {
+        assert_eq!(unterminated_delimiters(""), vec![]);
+        assert_eq!(unterminated_delimiters("**closed** text"), vec![]);
+        assert_eq!(
+            unterminated_delimiters("**open"),
+            vec![(0, Delimiter::Bold)]
+        );
+        assert_eq!(
+            unterminated_delimiters("*a* ||b __c"),
+            vec![(4, Delimiter::Spoiler), (8, Delimiter::Underline)]
+        );
+        assert_eq!(
+            unterminated_delimiters("~~a `b **c"),
+            vec![(0, Delimiter::Strikethrough), (4, Delimiter::OneLineCode)]
+        );
+        assert_eq!(
+            unterminated_delimiters("_a ```js\n**"),
+            vec![
+                (0, Delimiter::ItalicsUnderscore),
+                (3, Delimiter::MultiLineCode)
+            ]
+        );
+        assert_eq!(
+            unterminated_delimiters("**bold*"),
+            vec![(0, Delimiter::Bold), (6, Delimiter::ItalicsStar)]
+        );
+    }
+
     #[test]
     fn test_multi_line_code_with_lang_err() {
         assert_eq!(
@@ -0,0 +1,316 @@
+//! Selectors for finding elements
+//!
+//! [`select`](crate::select) module provides [`Selector`], a small selector language to find elements in an AST.
+//! Use it through [`MarkdownDocument::select()`](crate::ast::MarkdownDocument::select()).
+//!
+//! # Syntax
+//!
+//! A selector is a list of element kinds separated by combinators, like CSS selectors.
+//!
+//! - An element kind is the snake case name of [`ElementKind`], such as `bold` and `one_line_code`.
+//!   `*` matches any kind.
+//! - `a b` matches `b` which is a descendant of `a`.
+//! - `a > b` matches `b` which is a direct child of `a`.
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::builder::*;
+//! use discord_md::parse;
+//!
+//! let ast = parse("||**`spoiled code`**|| `visible code`");
+//!
+//! assert_eq!(
+//!     ast.select("spoiler one_line_code").unwrap(),
+//!     vec![&one_line_code("spoiled code")]
+//! );
+//! assert_eq!(
+//!     ast.select("spoiler > one_line_code").unwrap(),
+//!     Vec::<&discord_md::ast::MarkdownElement>::new()
+//! );
+//! ```
+
+use crate::ast::{ElementKind, MarkdownElement};
+use crate::error::SelectorError;
+use crate::iter::Node;
+use std::str::FromStr;
+
+/// A parsed selector. See the [module documentation](crate::select) for the syntax.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Selector {
+    /// Each step and the combinator between it and the previous step.
+    /// The combinator of the first step is always [`Combinator::Descendant`].
+    steps: Vec<(Combinator, Option<ElementKind>)>,
+}
+
+/// How two steps of a selector are related.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+impl Selector {
+    /// Parses a selector.
+    pub fn parse(selector: &str) -> Result<Self, SelectorError> {
+        let mut steps = Vec::new();
+        let mut combinator = Combinator::Descendant;
+        let mut pending_child = None;
+
+        let mut chars = selector.char_indices().peekable();
+        while let Some((offset, c)) = chars.next() {
+            if c.is_whitespace() {
+                continue;
+            }
+
+            if c == '>' {
+                if steps.is_empty() || pending_child.is_some() {
+                    return Err(SelectorError::UnexpectedCombinator { offset });
+                }
+                combinator = Combinator::Child;
+                pending_child = Some(offset);
+                continue;
+            }
+
+            let kind = if c == '*' {
+                None
+            } else if is_name_char(c) {
+                let mut end = offset + c.len_utf8();
+                while let Some(&(next, c)) = chars.peek() {
+                    if !is_name_char(c) {
+                        break;
+                    }
+                    end = next + c.len_utf8();
+                    chars.next();
+                }
+
+                let name = &selector[offset..end];
+                Some(
+                    ElementKind::from_name(name).ok_or_else(|| SelectorError::UnknownKind {
+                        name: name.to_string(),
+                        offset,
+                    })?,
+                )
+            } else {
+                return Err(SelectorError::UnexpectedCharacter {
+                    character: c,
+                    offset,
+                });
+            };
+
+            // A step must be followed by whitespace, `>` or the end, e.g. `bold*` is not a selector.
+            if let Some(&(offset, c)) = chars.peek() {
+                if !c.is_whitespace() && c != '>' {
+                    return Err(SelectorError::UnexpectedCharacter {
+                        character: c,
+                        offset,
+                    });
+                }
+            }
+
+            steps.push((combinator, kind));
+            combinator = Combinator::Descendant;
+            pending_child = None;
+        }
+
+        if let Some(offset) = pending_child {
+            return Err(SelectorError::UnexpectedCombinator { offset });
+        }
+        if steps.is_empty() {
+            return Err(SelectorError::Empty);
+        }
+
+        Ok(Self { steps })
+    }
+
+    /// Returns `true` if the node yielded by [`MarkdownDocument::iter()`](crate::ast::MarkdownDocument::iter())
+    /// matches the selector.
+    pub fn matches(&self, node: &Node) -> bool {
+        let mut chain = node.ancestors().to_vec();
+        chain.push(node.element());
+
+        matches_chain(&self.steps, &chain)
+    }
+}
+
+impl FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Selector::parse(s)
+    }
+}
+
+/// Returns `true` if the character can be a part of an element kind name.
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_lowercase() || c == '_'
+}
+
+/// Returns `true` if the last element of `chain` matches the last step,
+/// and its ancestors in `chain` match the rest of the steps.
+fn matches_chain(steps: &[(Combinator, Option<ElementKind>)], chain: &[&MarkdownElement]) -> bool {
+    let ((combinator, kind), rest_steps) = match steps.split_last() {
+        Some(x) => x,
+        None => return true,
+    };
+    let (element, ancestors) = match chain.split_last() {
+        Some(x) => x,
+        None => return false,
+    };
+
+    if kind.is_some_and(|kind| kind != element.kind()) {
+        return false;
+    }
+    if rest_steps.is_empty() {
+        return true;
+    }
+
+    match combinator {
+        Combinator::Child => matches_chain(rest_steps, ancestors),
+        Combinator::Descendant => {
+            (1..=ancestors.len()).any(|end| matches_chain(rest_steps, &ancestors[..end]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::MarkdownDocument;
+    use crate::builder::*;
+
+    fn test_document() -> MarkdownDocument {
+        MarkdownDocument::new(vec![
+            spoiler(vec![bold("a"), underline(vec![bold("b")])]),
+            bold(vec![spoiler("c")]),
+            block_quote(vec![one_line_code("d")]),
+        ])
+    }
+
+    #[test]
+    fn test_parse_ok() {
+        assert_eq!(
+            Selector::parse("bold"),
+            Ok(Selector {
+                steps: vec![(Combinator::Descendant, Some(ElementKind::Bold))]
+            })
+        );
+        assert_eq!(
+            Selector::parse("  spoiler>bold  *  "),
+            Ok(Selector {
+                steps: vec![
+                    (Combinator::Descendant, Some(ElementKind::Spoiler)),
+                    (Combinator::Child, Some(ElementKind::Bold)),
+                    (Combinator::Descendant, None),
+                ]
+            })
+        );
+        assert_eq!(
+            "block_quote > one_line_code".parse::<Selector>(),
+            Ok(Selector {
+                steps: vec![
+                    (Combinator::Descendant, Some(ElementKind::BlockQuote)),
+                    (Combinator::Child, Some(ElementKind::OneLineCode)),
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_err() {
+        assert_eq!(Selector::parse(""), Err(SelectorError::Empty));
+        assert_eq!(Selector::parse("   "), Err(SelectorError::Empty));
+        assert_eq!(
+            Selector::parse("> bold"),
+            Err(SelectorError::UnexpectedCombinator { offset: 0 })
+        );
+        assert_eq!(
+            Selector::parse("bold >"),
+            Err(SelectorError::UnexpectedCombinator { offset: 5 })
+        );
+        assert_eq!(
+            Selector::parse("bold > > plain"),
+            Err(SelectorError::UnexpectedCombinator { offset: 7 })
+        );
+        assert_eq!(
+            Selector::parse("bold italics"),
+            Err(SelectorError::UnknownKind {
+                name: "italics".to_string(),
+                offset: 5
+            })
+        );
+        assert_eq!(
+            Selector::parse("Bold"),
+            Err(SelectorError::UnexpectedCharacter {
+                character: 'B',
+                offset: 0
+            })
+        );
+        assert_eq!(
+            Selector::parse("bold*"),
+            Err(SelectorError::UnexpectedCharacter {
+                character: '*',
+                offset: 4
+            })
+        );
+        assert_eq!(
+            Selector::parse("spoiler **"),
+            Err(SelectorError::UnexpectedCharacter {
+                character: '*',
+                offset: 9
+            })
+        );
+        assert_eq!(
+            Selector::parse("*bold"),
+            Err(SelectorError::UnexpectedCharacter {
+                character: 'b',
+                offset: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_select_descendant() {
+        let ast = test_document();
+
+        assert_eq!(
+            ast.select("spoiler bold").unwrap(),
+            vec![&bold("a"), &bold("b")]
+        );
+        assert_eq!(
+            ast.select("bold plain").unwrap(),
+            vec![&plain("a"), &plain("b"), &plain("c")]
+        );
+    }
+
+    #[test]
+    fn test_select_child() {
+        let ast = test_document();
+
+        assert_eq!(ast.select("spoiler > bold").unwrap(), vec![&bold("a")]);
+        assert_eq!(
+            ast.select("spoiler > underline > bold").unwrap(),
+            vec![&bold("b")]
+        );
+        assert_eq!(
+            ast.select("spoiler > * > plain").unwrap(),
+            vec![&plain("a")]
+        );
+        assert_eq!(
+            ast.select("spoiler * > plain").unwrap(),
+            vec![&plain("a"), &plain("b")]
+        );
+    }
+
+    #[test]
+    fn test_select_single() {
+        let ast = test_document();
+
+        assert_eq!(
+            ast.select("one_line_code").unwrap(),
+            vec![&one_line_code("d")]
+        );
+        assert_eq!(ast.select("*").unwrap().len(), ast.iter().count());
+        assert!(ast.select("multi_line_code").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+//! Comparing documents by rendered meaning
+//!
+//! [`semantic`](crate::semantic) module provides [`Semantic`], a document that is compared and hashed
+//! by how it is rendered in Discord instead of by its exact AST.
+//!
+//! The derived [`PartialEq`] of [`MarkdownDocument`] treats `*x*` and `_x_` as different,
+//! and also treats split plain text like `[Plain("a"), Plain("b")]` as different from `[Plain("ab")]`.
+//! [`Semantic`] considers them the same, so it can be used as a key of [`HashMap`](std::collections::HashMap) or [`HashSet`](std::collections::HashSet).
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::parse;
+//! use discord_md::semantic::Semantic;
+//! use std::collections::HashSet;
+//!
+//! let mut seen = HashSet::new();
+//!
+//! assert!(seen.insert(Semantic::new(parse("*hello* world"))));
+//! assert!(!seen.insert(Semantic::new(parse("_hello_ world"))));
+//! ```
+
+use crate::ast::MarkdownDocument;
+use crate::normalize::canonicalize;
+
+/// A document compared and hashed by rendered meaning.
+///
+/// Two documents are semantically equal if they are equal after the following canonicalization:
+///
+/// - Italics text wrapped in `_` is treated as italics text wrapped in `*`.
+/// - The document is normalized as [`MarkdownDocument::normalize()`] does,
+///   e.g. adjacent plain text is merged and empty styled text is removed.
+///
+/// The canonical form is computed once in [`Semantic::new()`], so comparing and hashing are as cheap as for [`MarkdownDocument`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Semantic(MarkdownDocument);
+
+impl Semantic {
+    /// Wraps a document in its canonical form.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use discord_md::ast::MarkdownDocument;
+    /// use discord_md::builder::*;
+    /// use discord_md::semantic::Semantic;
+    ///
+    /// let semantic = Semantic::new(MarkdownDocument::new(vec![italics_underscore("a"), plain("b"), plain("c")]));
+    ///
+    /// assert_eq!(
+    ///     semantic.document(),
+    ///     &MarkdownDocument::new(vec![italics_star("a"), plain("bc")])
+    /// );
+    /// ```
+    pub fn new(document: MarkdownDocument) -> Self {
+        Self(canonicalize(document))
+    }
+
+    /// Returns the document in the canonical form.
+    pub fn document(&self) -> &MarkdownDocument {
+        &self.0
+    }
+
+    /// Consumes the wrapper and returns the document in the canonical form.
+    pub fn into_document(self) -> MarkdownDocument {
+        self.0
+    }
+}
+
+impl From<MarkdownDocument> for Semantic {
+    fn from(value: MarkdownDocument) -> Self {
+        Semantic::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::parse;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    fn hash(value: &impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_semantic_eq() {
+        let pairs = [
+            ("*a*", "_a_"),
+            ("**_a_ b**", "***a* b**"),
+            ("~~*a*~~ ||b||", "~~_a_~~ ||b||"),
+            ("plain", "plain"),
+        ];
+
+        for (a, b) in pairs {
+            let a = Semantic::new(parse(a));
+            let b = Semantic::new(parse(b));
+            assert_eq!(a, b);
+            assert_eq!(hash(&a), hash(&b));
+        }
+    }
+
+    #[test]
+    fn test_semantic_ne() {
+        let pairs = [
+            ("*a*", "**a**"),
+            ("*a*", "a"),
+            ("`a`", "a"),
+            ("_a_ ", "*a*"),
+        ];
+
+        for (a, b) in pairs {
+            assert_ne!(Semantic::new(parse(a)), Semantic::new(parse(b)));
+        }
+    }
+
+    #[test]
+    fn test_semantic_split_plain() {
+        let a = Semantic::new(MarkdownDocument::new(vec![
+            plain("a"),
+            plain(""),
+            plain("b"),
+        ]));
+        let b = Semantic::from(MarkdownDocument::new(vec![plain("ab")]));
+
+        assert_eq!(a, b);
+        assert_eq!(hash(&a), hash(&b));
+        assert_eq!(a.into_document(), MarkdownDocument::new(vec![plain("ab")]));
+    }
+}
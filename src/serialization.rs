@@ -0,0 +1,146 @@
+//! Versioned serde representation of [`MarkdownDocument`].
+//!
+//! See the "Serialization" section of [`ast`](crate::ast) module for the format.
+
+use crate::ast::{MarkdownDocument, MarkdownElementCollection, SERDE_FORMAT_VERSION};
+use serde::de::Error as _;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+impl Serialize for MarkdownDocument {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("MarkdownDocument", 2)?;
+        state.serialize_field("version", &SERDE_FORMAT_VERSION)?;
+        state.serialize_field("content", self.content())?;
+        state.end()
+    }
+}
+
+/// The serialized form of [`MarkdownDocument`].
+#[derive(Deserialize)]
+#[serde(rename = "MarkdownDocument")]
+struct Repr {
+    version: u32,
+    content: MarkdownElementCollection,
+}
+
+impl<'de> Deserialize<'de> for MarkdownDocument {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = Repr::deserialize(deserializer)?;
+        if repr.version > SERDE_FORMAT_VERSION {
+            return Err(D::Error::custom(format!(
+                "unsupported format version {}, expected {} or older",
+                repr.version, SERDE_FORMAT_VERSION
+            )));
+        }
+        Ok(MarkdownDocument::new(repr.content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{ElementKind, MarkdownElement};
+    use crate::builder::*;
+    use crate::parse;
+    use serde_json::json;
+
+    #[test]
+    fn test_serialize() {
+        let ast = MarkdownDocument::new(vec![
+            plain("a "),
+            bold(vec![plain("b"), spoiler("c")]),
+            one_line_code("d"),
+            multi_line_code("e", None),
+            multi_line_code("f", Some("rs".to_string())),
+            block_quote("g"),
+        ]);
+
+        assert_eq!(
+            serde_json::to_value(&ast).unwrap(),
+            json!({
+                "version": 1,
+                "content": [
+                    { "type": "plain", "content": "a " },
+                    { "type": "bold", "content": [
+                        { "type": "plain", "content": "b" },
+                        { "type": "spoiler", "content": [{ "type": "plain", "content": "c" }] },
+                    ] },
+                    { "type": "one_line_code", "content": "d" },
+                    { "type": "multi_line_code", "content": "e" },
+                    { "type": "multi_line_code", "content": "f", "language": "rs" },
+                    { "type": "block_quote", "content": [{ "type": "plain", "content": "g" }] },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let ast: MarkdownDocument = serde_json::from_value(json!({
+            "version": 1,
+            "content": [
+                { "type": "italics_star", "content": [{ "type": "plain", "content": "a" }] },
+                { "type": "multi_line_code", "content": "b", "unknown": 0 },
+            ],
+            "unknown": "ignored"
+        }))
+        .unwrap();
+
+        assert_eq!(
+            ast,
+            MarkdownDocument::new(vec![italics_star("a"), multi_line_code("b", None)])
+        );
+    }
+
+    #[test]
+    fn test_deserialize_unsupported_version() {
+        let result = serde_json::from_value::<MarkdownDocument>(json!({
+            "version": 2,
+            "content": []
+        }));
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "unsupported format version 2, expected 1 or older"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_invalid() {
+        assert!(serde_json::from_value::<MarkdownDocument>(json!({ "content": [] })).is_err());
+        assert!(serde_json::from_value::<MarkdownDocument>(json!({
+            "version": 1,
+            "content": [{ "type": "heading", "content": "a" }]
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let ast = parse(include_str!("../tests/example.md"));
+        let json = serde_json::to_string(&ast).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<MarkdownDocument>(&json).unwrap(),
+            ast
+        );
+    }
+
+    #[test]
+    fn test_element_and_kind() {
+        assert_eq!(
+            serde_json::to_value(underline("x")).unwrap(),
+            json!({ "type": "underline", "content": [{ "type": "plain", "content": "x" }] })
+        );
+        assert_eq!(
+            serde_json::from_value::<MarkdownElement>(json!({ "type": "plain", "content": "x" }))
+                .unwrap(),
+            plain("x")
+        );
+
+        for kind in ElementKind::ALL {
+            assert_eq!(serde_json::to_value(kind).unwrap(), json!(kind.name()));
+        }
+    }
+}
@@ -0,0 +1,736 @@
+//! Converts between an AST and Slack's mrkdwn
+//!
+//! [`slack`](crate::slack) module provides [`ToSlack`] trait, which converts a markdown component to Slack's mrkdwn,
+//! and [`parse_slack()`], which converts mrkdwn into an AST.
+//!
+//! | Element | mrkdwn |
+//! |---|---|
+//! | [`ItalicsStar`](crate::ast::ItalicsStar), [`ItalicsUnderscore`] | `_x_` |
+//! | [`Bold`] | `*x*` |
+//! | [`Strikethrough`] | `~x~` |
+//! | [`Underline`] | See [`UnderlineFallback`] |
+//! | [`Spoiler`] | See [`SpoilerFallback`] |
+//! | [`OneLineCode`] | `` `x` `` |
+//! | [`MultiLineCode`] | ```` ```x``` ````, without the language |
+//! | [`BlockQuote`] | `> x` |
+//!
+//! Mentions and links in plain text are converted too.
+//!
+//! | Discord | mrkdwn |
+//! |---|---|
+//! | `<@id>`, `<@!id>` | `<@id>` |
+//! | `<#id>` | `<#id>` |
+//! | `<@&id>` | `<!subteam^id>` |
+//! | `@everyone`, `@here` | `<!everyone>`, `<!here>` |
+//! | `<:name:id>` | `:name:` |
+//! | `[label](url)` | `<url\|label>` |
+//!
+//! IDs of users, channels and roles differ between Discord and Slack,
+//! so they are converted by callbacks given to [`SlackOption`].
+//! A mention whose ID is not mapped is converted into plain text, like `@id`.
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::parse;
+//! use discord_md::slack::{parse_slack, SlackOption, ToSlack};
+//!
+//! let option = SlackOption::new().user(|id| (id == "80351110224678912").then(|| "U024BE7LH".to_string()));
+//!
+//! let ast = parse("**hey** <@80351110224678912>, see [docs](https://example.com)");
+//! assert_eq!(ast.to_slack(&option), "*hey* <@U024BE7LH>, see <https://example.com|docs>");
+//!
+//! let ast = parse_slack("_hi_ <@U0123|alice> & <https://example.com|docs>", &SlackOption::new());
+//! assert_eq!(ast.to_string(), "_hi_ @alice & [docs](https://example.com)");
+//! ```
+
+use crate::ast::{
+    BlockQuote, Bold, ItalicsUnderscore, MarkdownDocument, MarkdownElement,
+    MarkdownElementCollection, MultiLineCode, OneLineCode, Plain, Spoiler, Strikethrough,
+    Underline,
+};
+use crate::length::units;
+use std::fmt;
+use std::rc::Rc;
+
+/// A callback that maps an ID on the source platform to the ID on the target platform.
+///
+/// It returns `None` if the ID cannot be mapped.
+pub type IdMapping<'a> = Rc<dyn Fn(&str) -> Option<String> + 'a>;
+
+/// Struct that allows to alter the behaviour of [`to_slack()`](ToSlack::to_slack()) and [`parse_slack()`].
+///
+/// ID mapping callbacks map Discord IDs to Slack IDs in [`to_slack()`](ToSlack::to_slack()),
+/// and Slack IDs to Discord IDs in [`parse_slack()`].
+///
+/// # Example
+///
+/// ```
+/// use discord_md::parse;
+/// use discord_md::slack::{SlackOption, SpoilerFallback, ToSlack, UnderlineFallback};
+/// use std::collections::HashMap;
+///
+/// let channels = HashMap::from([("1", "C1")]);
+/// let option = SlackOption::new()
+///     .underline(UnderlineFallback::Italics)
+///     .spoiler(SpoilerFallback::Omit)
+///     .channel(|id| channels.get(id).map(|id| id.to_string()));
+///
+/// let ast = parse("__see__ <#1> <#2> ||secret||");
+/// assert_eq!(ast.to_slack(&option), "_see_ <#C1> #2 ");
+/// ```
+#[derive(Clone, Default)]
+#[non_exhaustive]
+pub struct SlackOption<'a> {
+    /// How underline text is converted, since mrkdwn does not have underline
+    pub underline: UnderlineFallback,
+
+    /// How spoilers are converted, since mrkdwn does not have spoilers
+    pub spoiler: SpoilerFallback,
+
+    /// Maps IDs of users
+    pub user: Option<IdMapping<'a>>,
+
+    /// Maps IDs of channels
+    pub channel: Option<IdMapping<'a>>,
+
+    /// Maps IDs of Discord roles and Slack user groups
+    pub role: Option<IdMapping<'a>>,
+}
+
+impl<'a> SlackOption<'a> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn underline(mut self, value: UnderlineFallback) -> Self {
+        self.underline = value;
+        self
+    }
+
+    pub fn spoiler(mut self, value: SpoilerFallback) -> Self {
+        self.spoiler = value;
+        self
+    }
+
+    pub fn user(mut self, value: impl Fn(&str) -> Option<String> + 'a) -> Self {
+        self.user = Some(Rc::new(value));
+        self
+    }
+
+    pub fn channel(mut self, value: impl Fn(&str) -> Option<String> + 'a) -> Self {
+        self.channel = Some(Rc::new(value));
+        self
+    }
+
+    pub fn role(mut self, value: impl Fn(&str) -> Option<String> + 'a) -> Self {
+        self.role = Some(Rc::new(value));
+        self
+    }
+}
+
+impl fmt::Debug for SlackOption<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlackOption")
+            .field("underline", &self.underline)
+            .field("spoiler", &self.spoiler)
+            .field("user", &self.user.is_some())
+            .field("channel", &self.channel.is_some())
+            .field("role", &self.role.is_some())
+            .finish()
+    }
+}
+
+/// How underline text is converted by [`to_slack()`](ToSlack::to_slack()).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum UnderlineFallback {
+    /// Keep the content without any style.
+    #[default]
+    Plain,
+
+    /// Convert into italics text.
+    Italics,
+}
+
+/// How spoilers are converted by [`to_slack()`](ToSlack::to_slack()).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum SpoilerFallback {
+    /// Keep the content surrounded by literal `||`, so that readers can tell it was a spoiler.
+    #[default]
+    Marker,
+
+    /// Keep the content without any indication.
+    Plain,
+
+    /// Remove the spoiler and its content.
+    Omit,
+}
+
+/// A trait for converting a markdown component into Slack's mrkdwn.
+pub trait ToSlack {
+    /// Returns the component as mrkdwn text.
+    fn to_slack(&self, option: &SlackOption) -> String;
+}
+
+impl ToSlack for MarkdownDocument {
+    fn to_slack(&self, option: &SlackOption) -> String {
+        self.content().to_slack(option)
+    }
+}
+
+impl ToSlack for MarkdownElementCollection {
+    fn to_slack(&self, option: &SlackOption) -> String {
+        self.get().iter().map(|c| c.to_slack(option)).collect()
+    }
+}
+
+impl ToSlack for MarkdownElement {
+    fn to_slack(&self, option: &SlackOption) -> String {
+        match self {
+            MarkdownElement::Plain(x) => x.to_slack(option),
+            MarkdownElement::ItalicsStar(x) => format!("_{}_", x.content().to_slack(option)),
+            MarkdownElement::ItalicsUnderscore(x) => {
+                format!("_{}_", x.content().to_slack(option))
+            }
+            MarkdownElement::Bold(x) => format!("*{}*", x.content().to_slack(option)),
+            MarkdownElement::Underline(x) => x.to_slack(option),
+            MarkdownElement::Strikethrough(x) => format!("~{}~", x.content().to_slack(option)),
+            MarkdownElement::Spoiler(x) => x.to_slack(option),
+            MarkdownElement::OneLineCode(x) => format!("`{}`", escape(x.content())),
+            MarkdownElement::MultiLineCode(x) => format!("```{}```", escape(x.content())),
+            MarkdownElement::BlockQuote(x) => x.to_slack(option),
+        }
+    }
+}
+
+impl ToSlack for Plain {
+    /// Returns the escaped text, converting mentions, custom emoji and masked links.
+    fn to_slack(&self, option: &SlackOption) -> String {
+        let text = self.content();
+        let mut output = String::with_capacity(text.len());
+        let mut offset = 0;
+
+        while let Some(unit) = units(&text[offset..]).next() {
+            let rest = &text[offset..];
+
+            if let Some((label, url, len)) = masked_link(rest) {
+                output.push_str(&format!("<{}|{}>", url, escape(label)));
+                offset += len;
+            } else if let Some(name) = ["@everyone", "@here"]
+                .iter()
+                .find(|name| rest.starts_with(*name))
+            {
+                output.push_str(&format!("<!{}>", &name[1..]));
+                offset += name.len();
+            } else {
+                if unit.len() > 1 && unit.starts_with('<') {
+                    output.push_str(&discord_token(unit, option));
+                } else {
+                    output.push_str(&escape(unit));
+                }
+                offset += unit.len();
+            }
+        }
+
+        output
+    }
+}
+
+impl ToSlack for Underline {
+    fn to_slack(&self, option: &SlackOption) -> String {
+        let content = self.content().to_slack(option);
+        match option.underline {
+            UnderlineFallback::Plain => content,
+            UnderlineFallback::Italics => format!("_{}_", content),
+        }
+    }
+}
+
+impl ToSlack for Spoiler {
+    fn to_slack(&self, option: &SlackOption) -> String {
+        match option.spoiler {
+            SpoilerFallback::Marker => format!("||{}||", self.content().to_slack(option)),
+            SpoilerFallback::Plain => self.content().to_slack(option),
+            SpoilerFallback::Omit => String::new(),
+        }
+    }
+}
+
+impl ToSlack for BlockQuote {
+    fn to_slack(&self, option: &SlackOption) -> String {
+        self.content()
+            .to_slack(option)
+            .split('\n')
+            .map(|line| format!("> {}", line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Converts a Discord mention or custom emoji into mrkdwn.
+fn discord_token(token: &str, option: &SlackOption) -> String {
+    let inside = &token[1..token.len() - 1];
+
+    if let Some(id) = inside.strip_prefix("@&") {
+        mention(&option.role, id, |id| format!("<!subteam^{}>", id), "@")
+    } else if let Some(id) = inside.strip_prefix('@') {
+        let id = id.strip_prefix('!').unwrap_or(id);
+        mention(&option.user, id, |id| format!("<@{}>", id), "@")
+    } else if let Some(id) = inside.strip_prefix('#') {
+        mention(&option.channel, id, |id| format!("<#{}>", id), "#")
+    } else {
+        // Custom emoji, like `<:name:id>` and `<a:name:id>`.
+        let name = inside.split(':').nth(1).unwrap_or_default();
+        format!(":{}:", name)
+    }
+}
+
+/// Maps the ID of a mention, or returns the ID as plain text if it cannot be mapped.
+fn mention(
+    mapping: &Option<IdMapping>,
+    id: &str,
+    format: impl Fn(&str) -> String,
+    prefix: &str,
+) -> String {
+    match mapping.as_ref().and_then(|mapping| mapping(id)) {
+        Some(mapped) => format(&mapped),
+        None => escape(&format!("{}{}", prefix, id)),
+    }
+}
+
+/// Parses a masked link of Discord, like `[label](https://example.com)`, and returns the label, the URL and the length.
+fn masked_link(text: &str) -> Option<(&str, &str, usize)> {
+    let (label, rest) = text.strip_prefix('[')?.split_once("](")?;
+    let url_len = rest.find(')')?;
+    let url = &rest[..url_len];
+
+    let valid_label = !label.is_empty() && !label.contains(['[', ']', '\n']);
+    let valid_url = (url.starts_with("https://") || url.starts_with("http://"))
+        && !url.contains(char::is_whitespace);
+    (valid_label && valid_url).then(|| (label, url, label.len() + url_len + 4))
+}
+
+/// Escapes characters that have special meanings in mrkdwn.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Parses Slack's mrkdwn into an AST.
+///
+/// Mentions are converted into Discord's mentions through the ID mapping callbacks of `option`.
+/// A mention whose ID is not mapped is converted into plain text, like `@name` if Slack gives the name, or `@id` otherwise.
+///
+/// | mrkdwn | Discord |
+/// |---|---|
+/// | `*x*` | [`Bold`] |
+/// | `_x_` | [`ItalicsUnderscore`] |
+/// | `~x~` | [`Strikethrough`] |
+/// | `` `x` `` | [`OneLineCode`] |
+/// | ```` ```x``` ```` | [`MultiLineCode`] |
+/// | `> x`, `>>> x` | [`BlockQuote`] |
+/// | `<@id>`, `<#id>`, `<!subteam^id>` | `<@id>`, `<#id>`, `<@&id>` |
+/// | `<!everyone>`, `<!channel>`, `<!here>` | `@everyone`, `@everyone`, `@here` |
+/// | `<url\|label>`, `<url>` | `[label](url)`, `url` |
+///
+/// # Example
+///
+/// ```
+/// use discord_md::slack::{parse_slack, SlackOption};
+///
+/// let option = SlackOption::new().user(|id| (id == "U1").then(|| "123".to_string()));
+/// let ast = parse_slack("&gt; *quoted* ~text~\n<@U1> <!here> <#C1|general>", &option);
+///
+/// assert_eq!(ast.to_string(), "> **quoted** ~~text~~\n<@123> @here #general");
+/// ```
+pub fn parse_slack(text: &str, option: &SlackOption) -> MarkdownDocument {
+    let text = text.replace("\r\n", "\n");
+    let mut elements = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut lines = text.split('\n');
+
+    let flush = |paragraph: &mut Vec<&str>, elements: &mut Vec<MarkdownElement>| {
+        if !paragraph.is_empty() {
+            elements.extend(inlines(&paragraph.join("\n"), option));
+            paragraph.clear();
+        }
+    };
+
+    while let Some(line) = lines.next() {
+        let quote = quote_content(line);
+        match quote {
+            Some((content, rest_quoted)) => {
+                let mut quoted = vec![content];
+                if rest_quoted {
+                    quoted.extend(lines.by_ref());
+                } else {
+                    while let Some(line) = lines.clone().next() {
+                        match quote_content(line) {
+                            Some((content, false)) => {
+                                quoted.push(content);
+                                lines.next();
+                            }
+                            _ => break,
+                        }
+                    }
+                }
+
+                if !paragraph.is_empty() {
+                    paragraph.push("");
+                    flush(&mut paragraph, &mut elements);
+                }
+                elements.push(BlockQuote::new(inlines(&quoted.join("\n"), option)).into());
+                // The line break after the block quote.
+                if lines.clone().next().is_some() {
+                    elements.push(Plain::new("\n").into());
+                }
+            }
+            None => paragraph.push(line),
+        }
+    }
+    flush(&mut paragraph, &mut elements);
+
+    MarkdownDocument::new(merge_plain(elements))
+}
+
+/// Returns the content of a block quote line, and whether the rest of the message is quoted by `>>>`.
+fn quote_content(line: &str) -> Option<(&str, bool)> {
+    for marker in [">>>", "&gt;&gt;&gt;"] {
+        if let Some(rest) = line.strip_prefix(marker) {
+            return Some((rest.strip_prefix(' ').unwrap_or(rest), true));
+        }
+    }
+    for marker in [">", "&gt;"] {
+        if let Some(rest) = line.strip_prefix(marker) {
+            return Some((rest.strip_prefix(' ').unwrap_or(rest), false));
+        }
+    }
+    None
+}
+
+/// Parses inline elements of mrkdwn.
+fn inlines(text: &str, option: &SlackOption) -> Vec<MarkdownElement> {
+    let mut elements: Vec<MarkdownElement> = Vec::new();
+    let mut pos = 0;
+
+    while let Some(c) = text[pos..].chars().next() {
+        let rest = &text[pos..];
+        let parsed: Option<(MarkdownElement, usize)> = match c {
+            '`' if rest.starts_with("```") => rest[3..].find("```").map(|end| {
+                let content = decode(&rest[3..3 + end]);
+                (MultiLineCode::new(content, None).into(), end + 6)
+            }),
+            '`' => rest[1..]
+                .find(['`', '\n'])
+                .filter(|&end| end > 0 && rest[1 + end..].starts_with('`'))
+                .map(|end| {
+                    let content = decode(&rest[1..1 + end]);
+                    (OneLineCode::new(content).into(), end + 2)
+                }),
+            '*' | '_' | '~' => styled(text, pos, c).map(|end| {
+                let content = inlines(&text[pos + 1..end], option);
+                let element = match c {
+                    '*' => Bold::new(content).into(),
+                    '_' => ItalicsUnderscore::new(content).into(),
+                    _ => Strikethrough::new(content).into(),
+                };
+                (element, end + 1 - pos)
+            }),
+            '<' => rest.find('>').map(|end| {
+                let text = angle_bracket(&rest[1..end], option);
+                (Plain::new(text).into(), end + 1)
+            }),
+            '&' => ["&amp;", "&lt;", "&gt;"]
+                .iter()
+                .find(|entity| rest.starts_with(*entity))
+                .map(|entity| (Plain::new(decode(entity)).into(), entity.len())),
+            _ => None,
+        };
+
+        match parsed {
+            Some((element, len)) => {
+                elements.push(element);
+                pos += len;
+            }
+            None => {
+                elements.push(Plain::new(c.to_string()).into());
+                pos += c.len_utf8();
+            }
+        }
+    }
+
+    merge_plain(elements)
+}
+
+/// Finds the closing character of `*x*`, `_x_` or `~x~` starting at `pos`.
+///
+/// Styles must not span lines, and must be surrounded by word boundaries.
+fn styled(text: &str, pos: usize, character: char) -> Option<usize> {
+    let is_boundary = |c: Option<char>| !c.is_some_and(char::is_alphanumeric);
+    let previous = text[..pos].chars().next_back();
+    if !is_boundary(previous) || !text[pos + 1..].starts_with(|c: char| !c.is_whitespace()) {
+        return None;
+    }
+
+    text[pos + 1..]
+        .char_indices()
+        .take_while(|&(_, c)| c != '\n')
+        .map(|(offset, c)| (pos + 1 + offset, c))
+        .find(|&(end, c)| {
+            c == character
+                && end > pos + 1
+                && text[..end]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|c| !c.is_whitespace())
+                && is_boundary(text[end + 1..].chars().next())
+        })
+        .map(|(end, _)| end)
+}
+
+/// Converts the content of `<...>`, i.e. a mention, a special command or a link, into Discord's text.
+fn angle_bracket(inside: &str, option: &SlackOption) -> String {
+    let (target, label) = match inside.split_once('|') {
+        Some((target, label)) => (target, Some(decode(label))),
+        None => (inside, None),
+    };
+    let unmapped = |prefix: &str, id: &str| match &label {
+        Some(label) if label.starts_with(prefix) => label.clone(),
+        Some(label) => format!("{}{}", prefix, label),
+        None => format!("{}{}", prefix, id),
+    };
+    let map =
+        |mapping: &Option<IdMapping>, id: &str| mapping.as_ref().and_then(|mapping| mapping(id));
+
+    if let Some(id) = target.strip_prefix('@') {
+        map(&option.user, id).map_or_else(|| unmapped("@", id), |id| format!("<@{}>", id))
+    } else if let Some(id) = target.strip_prefix('#') {
+        map(&option.channel, id).map_or_else(|| unmapped("#", id), |id| format!("<#{}>", id))
+    } else if let Some(id) = target.strip_prefix("!subteam^") {
+        map(&option.role, id).map_or_else(|| unmapped("@", id), |id| format!("<@&{}>", id))
+    } else if let Some(command) = target.strip_prefix('!') {
+        match command {
+            "everyone" | "channel" => "@everyone".to_string(),
+            "here" => "@here".to_string(),
+            // Other commands, like `<!date^...|fallback>`, are replaced with the fallback text.
+            _ => label.unwrap_or_else(|| command.to_string()),
+        }
+    } else {
+        let url = decode(target);
+        match label {
+            Some(label) if label != url => format!("[{}]({})", label, url),
+            _ => url,
+        }
+    }
+}
+
+/// Decodes characters escaped in mrkdwn.
+fn decode(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Merges adjacent plain text into one element.
+fn merge_plain(elements: Vec<MarkdownElement>) -> Vec<MarkdownElement> {
+    let mut merged: Vec<MarkdownElement> = Vec::with_capacity(elements.len());
+    for element in elements {
+        match (merged.last_mut(), element) {
+            (Some(MarkdownElement::Plain(last)), MarkdownElement::Plain(plain)) => {
+                last.content_mut().push_str(plain.content());
+            }
+            (_, element) => merged.push(element),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::parse;
+    use std::collections::HashMap;
+
+    fn to_slack(text: &str) -> String {
+        parse(text).to_slack(&SlackOption::new())
+    }
+
+    #[test]
+    fn test_styles_to_slack() {
+        assert_eq!(
+            to_slack("*a* _b_ **c** ~~d~~ `e` ```rs\nf```"),
+            "_a_ _b_ *c* ~d~ `e` ```\nf```"
+        );
+        assert_eq!(
+            bold(italics_star("a")).to_slack(&SlackOption::new()),
+            "*_a_*"
+        );
+        assert_eq!(
+            MarkdownDocument::new(vec![block_quote("a\nb"), plain("\nc")])
+                .to_slack(&SlackOption::new()),
+            "> a\n> b\nc"
+        );
+    }
+
+    #[test]
+    fn test_fallbacks_to_slack() {
+        let ast = parse("__a__ ||b||");
+
+        assert_eq!(ast.to_slack(&SlackOption::new()), "a ||b||");
+        assert_eq!(
+            ast.to_slack(
+                &SlackOption::new()
+                    .underline(UnderlineFallback::Italics)
+                    .spoiler(SpoilerFallback::Plain)
+            ),
+            "_a_ b"
+        );
+        assert_eq!(
+            ast.to_slack(&SlackOption::new().spoiler(SpoilerFallback::Omit)),
+            "a "
+        );
+    }
+
+    #[test]
+    fn test_plain_to_slack() {
+        assert_eq!(
+            to_slack("a < b && `<c>`"),
+            "a &lt; b &amp;&amp; `&lt;c&gt;`"
+        );
+        assert_eq!(
+            to_slack("<:wave:123> <a:dance:456> @everyone @here"),
+            ":wave: :dance: <!everyone> <!here>"
+        );
+        assert_eq!(
+            to_slack("[a & b](https://a.example?x=1&y=2) [c](not a url)"),
+            "<https://a.example?x=1&y=2|a &amp; b> [c](not a url)"
+        );
+    }
+
+    #[test]
+    fn test_mentions_to_slack() {
+        let users = HashMap::from([("1", "U1")]);
+        let option = SlackOption::new()
+            .user(|id| users.get(id).map(|id| id.to_string()))
+            .channel(|id| Some(format!("C{}", id)))
+            .role(|id| Some(format!("S{}", id)));
+
+        assert_eq!(
+            parse("<@1> <@!1> <@2> <#3> <@&4>").to_slack(&option),
+            "<@U1> <@U1> @2 <#C3> <!subteam^S4>"
+        );
+        assert_eq!(
+            parse("<@1> <#3> <@&4>").to_slack(&SlackOption::new()),
+            "@1 #3 @4"
+        );
+    }
+
+    #[test]
+    fn test_parse_slack_styles() {
+        let option = SlackOption::new();
+
+        assert_eq!(
+            parse_slack("*a* _b_ ~c~ `d` *_e_*", &option),
+            MarkdownDocument::new(vec![
+                bold("a"),
+                plain(" "),
+                italics_underscore("b"),
+                plain(" "),
+                strikethrough("c"),
+                plain(" "),
+                one_line_code("d"),
+                plain(" "),
+                bold(italics_underscore("e")),
+            ])
+        );
+        assert_eq!(
+            parse_slack("snake_case_name 2*3*4 * a * *b\nc*", &option),
+            MarkdownDocument::new(vec![plain("snake_case_name 2*3*4 * a * *b\nc*")])
+        );
+        assert_eq!(
+            parse_slack("```\nfn main() {}\n``` `&lt;`", &option),
+            MarkdownDocument::new(vec![
+                multi_line_code("\nfn main() {}\n", None),
+                plain(" "),
+                one_line_code("<"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_slack_block_quote() {
+        let option = SlackOption::new();
+
+        assert_eq!(
+            parse_slack("a\n> b\n&gt; *c*\nd", &option),
+            MarkdownDocument::new(vec![
+                plain("a\n"),
+                block_quote(vec![plain("b\n"), bold("c")]),
+                plain("\nd"),
+            ])
+        );
+        assert_eq!(
+            parse_slack(">>> a\nb", &option),
+            MarkdownDocument::new(vec![block_quote("a\nb")])
+        );
+    }
+
+    #[test]
+    fn test_parse_slack_angle_brackets() {
+        let channels = HashMap::from([("C1", "1")]);
+        let option = SlackOption::new()
+            .channel(|id| channels.get(id).map(|id| id.to_string()))
+            .role(|id| Some(id.trim_start_matches('S').to_string()));
+
+        assert_eq!(
+            parse_slack(
+                "<#C1|general> <#C2|random> <#C3> <!subteam^S4|@team> <@U5>",
+                &option
+            )
+            .to_string(),
+            "<#1> #random #C3 <@&4> @U5"
+        );
+        assert_eq!(
+            parse_slack(
+                "<!channel> <!everyone> <!here> <!date^1392734382^{date}|Feb 18, 2014>",
+                &option
+            )
+            .to_string(),
+            "@everyone @everyone @here Feb 18, 2014"
+        );
+        assert_eq!(
+            parse_slack(
+                "<https://a.example?x=1&amp;y=2|a &amp; b> <https://b.example> <mailto:c@d.example|c@d.example>",
+                &option
+            )
+            .to_string(),
+            "[a & b](https://a.example?x=1&y=2) https://b.example [c@d.example](mailto:c@d.example)"
+        );
+    }
+
+    #[test]
+    fn test_slack_round_trip() {
+        let ast = parse("**a** _b_ ~~c~~ `d` <@1> <#2>");
+        let option = SlackOption::new()
+            .user(|id| Some(id.to_string()))
+            .channel(|id| Some(id.to_string()));
+
+        assert_eq!(
+            parse_slack(&ast.to_slack(&option), &option),
+            parse("**a** _b_ ~~c~~ `d` <@1> <#2>")
+        );
+    }
+
+    #[test]
+    fn test_masked_link() {
+        assert_eq!(
+            masked_link("[a](https://b) c"),
+            Some(("a", "https://b", 14))
+        );
+        assert_eq!(masked_link("[](https://b)"), None);
+        assert_eq!(masked_link("[a](b)"), None);
+        assert_eq!(masked_link("[a]"), None);
+    }
+}
@@ -0,0 +1,457 @@
+//! Splitting long documents into several messages
+//!
+//! [`split`](crate::split) module provides [`split()`], which splits a document into chunks that fit in a message
+//! without breaking formatting.
+//! Styles open at the end of a chunk are closed there and reopened at the start of the next chunk.
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::parse;
+//! use discord_md::split::{split, SplitOption};
+//!
+//! let ast = parse("**first line\nsecond line**\n```rs\nfn main() {}\n```");
+//!
+//! assert_eq!(
+//!     split(&ast, &SplitOption::new().limit(25)),
+//!     vec!["**first line**", "**second line**", "```rs\nfn main() {}\n```"]
+//! );
+//! ```
+
+use crate::ast::{MarkdownDocument, MarkdownElement};
+use crate::generate::{ToMarkdownString, ToMarkdownStringOption};
+use crate::length::{char_count, units, MESSAGE_LIMIT};
+use std::borrow::Cow;
+
+/// Struct that allows to alter [`split()`]'s behaviour.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::length::MESSAGE_LIMIT;
+/// use discord_md::split::SplitOption;
+///
+/// assert_eq!(SplitOption::new().limit, MESSAGE_LIMIT);
+/// assert_eq!(SplitOption::new().limit(100).limit, 100);
+/// ```
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SplitOption {
+    /// Maximum number of characters in a chunk, counted the way Discord does
+    pub limit: usize,
+}
+
+impl Default for SplitOption {
+    fn default() -> Self {
+        Self {
+            limit: MESSAGE_LIMIT,
+        }
+    }
+}
+
+impl SplitOption {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn limit(mut self, value: usize) -> Self {
+        self.limit = value;
+        self
+    }
+}
+
+/// Splits a document into markdown texts, each of which is within [`SplitOption::limit`] characters.
+///
+/// - A document that fits in the limit is returned as one chunk. An empty document returns no chunks.
+/// - A chunk ends at the last line break if possible, otherwise at the last whitespace outside code.
+///   Whitespace at the end of a chunk is removed outside code, since each message starts on a new line.
+///   If there is neither of them, the chunk ends right before the first character that does not fit.
+/// - Styles open at the end of a chunk are closed, and reopened at the start of the next chunk.
+///   A multiline code block is reopened with its language.
+/// - Custom emoji and mentions, like `<:name:id>` and `<@id>`, are never split.
+///
+/// The limit should be large enough to contain the delimiters of the most deeply nested styles.
+/// Otherwise, a chunk may exceed the limit, since at least one character must be put in each chunk.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::parse;
+/// use discord_md::split::{split, SplitOption};
+///
+/// let ast = parse("||spoiler with long text||");
+///
+/// assert_eq!(
+///     split(&ast, &SplitOption::new().limit(16)),
+///     vec!["||spoiler with||", "||long text||"]
+/// );
+/// ```
+pub fn split(document: &MarkdownDocument, option: &SplitOption) -> Vec<String> {
+    if document.markdown_len() <= option.limit {
+        let text = document.to_markdown_string(&ToMarkdownStringOption::new());
+        return if text.is_empty() { vec![] } else { vec![text] };
+    }
+
+    let flattened = Flattened::new(document);
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < flattened.units.len() {
+        let (chunk, next) = flattened.chunk(start, option.limit);
+        if !chunk.trim().is_empty() {
+            chunks.push(chunk);
+        }
+        start = next;
+    }
+    chunks
+}
+
+/// Markup wrapped around a sequence of units, such as `**` of bold text.
+#[derive(Debug)]
+struct Wrapper {
+    /// Markup placed before the first unit of the element.
+    opener: String,
+    /// Markup placed before the first unit in a chunk other than the first unit of the element.
+    reopener: String,
+    closer: &'static str,
+    is_code: bool,
+    first_unit: usize,
+}
+
+/// A piece of text that must not be split, together with the wrappers around it.
+#[derive(Debug)]
+struct Unit<'a> {
+    text: Cow<'a, str>,
+    /// Indices of the wrappers, outermost first.
+    wrappers: Vec<usize>,
+}
+
+impl Unit<'_> {
+    fn is_line_break(&self) -> bool {
+        self.text.starts_with('\n')
+    }
+
+    fn is_whitespace(&self) -> bool {
+        self.text.chars().all(char::is_whitespace)
+    }
+}
+
+/// A position where a chunk can end.
+struct Break {
+    /// Byte offset of the end of the chunk.
+    offset: usize,
+    /// Wrappers open at the end of the chunk.
+    open: Vec<usize>,
+    /// Index of the unit the next chunk starts from.
+    next: usize,
+}
+
+/// A document flattened into a sequence of units.
+#[derive(Debug)]
+struct Flattened<'a> {
+    wrappers: Vec<Wrapper>,
+    units: Vec<Unit<'a>>,
+}
+
+impl<'a> Flattened<'a> {
+    fn new(document: &'a MarkdownDocument) -> Self {
+        let mut flattened = Self {
+            wrappers: Vec::new(),
+            units: Vec::new(),
+        };
+        flattened.push_elements(document.content().get(), &mut Vec::new(), 0);
+        flattened
+    }
+
+    fn push_elements(
+        &mut self,
+        elements: &'a [MarkdownElement],
+        stack: &mut Vec<usize>,
+        quotes: usize,
+    ) {
+        for element in elements {
+            self.push_element(element, stack, quotes);
+        }
+    }
+
+    fn push_element(
+        &mut self,
+        element: &'a MarkdownElement,
+        stack: &mut Vec<usize>,
+        quotes: usize,
+    ) {
+        let (delimiter, children) = match element {
+            MarkdownElement::Plain(x) => {
+                for unit in units(x.content()) {
+                    self.push_unit(unit, stack, quotes);
+                }
+                return;
+            }
+            MarkdownElement::OneLineCode(x) => {
+                self.push_wrapper("`".to_string(), "`".to_string(), "`", true, stack);
+                self.push_code(x.content(), stack, quotes);
+                stack.pop();
+                return;
+            }
+            MarkdownElement::MultiLineCode(x) => {
+                let opener = format!("```{}", x.language().unwrap_or(""));
+                let reopener = format!("{}\n", opener);
+                self.push_wrapper(opener, reopener, "```", true, stack);
+                self.push_code(x.content(), stack, quotes);
+                stack.pop();
+                return;
+            }
+            MarkdownElement::BlockQuote(x) => {
+                // The generator places the prefix at the start of every line, so the block quote is never closed.
+                self.push_wrapper("> ".to_string(), "> ".to_string(), "", false, stack);
+                self.push_elements(x.content().get(), stack, quotes + 1);
+                stack.pop();
+                return;
+            }
+            MarkdownElement::ItalicsStar(x) => ("*", x.content()),
+            MarkdownElement::ItalicsUnderscore(x) => ("_", x.content()),
+            MarkdownElement::Bold(x) => ("**", x.content()),
+            MarkdownElement::Underline(x) => ("__", x.content()),
+            MarkdownElement::Strikethrough(x) => ("~~", x.content()),
+            MarkdownElement::Spoiler(x) => ("||", x.content()),
+        };
+
+        self.push_wrapper(
+            delimiter.to_string(),
+            delimiter.to_string(),
+            delimiter,
+            false,
+            stack,
+        );
+        self.push_elements(children.get(), stack, quotes);
+        stack.pop();
+    }
+
+    fn push_wrapper(
+        &mut self,
+        opener: String,
+        reopener: String,
+        closer: &'static str,
+        is_code: bool,
+        stack: &mut Vec<usize>,
+    ) {
+        stack.push(self.wrappers.len());
+        self.wrappers.push(Wrapper {
+            opener,
+            reopener,
+            closer,
+            is_code,
+            first_unit: self.units.len(),
+        });
+    }
+
+    fn push_code(&mut self, content: &'a str, stack: &[usize], quotes: usize) {
+        for (offset, c) in content.char_indices() {
+            self.push_unit(&content[offset..offset + c.len_utf8()], stack, quotes);
+        }
+    }
+
+    fn push_unit(&mut self, text: &'a str, stack: &[usize], quotes: usize) {
+        let text = if text == "\n" && quotes > 0 {
+            Cow::Owned(format!("\n{}", "> ".repeat(quotes)))
+        } else {
+            Cow::Borrowed(text)
+        };
+        self.units.push(Unit {
+            text,
+            wrappers: stack.to_vec(),
+        });
+    }
+
+    fn closers(&self, open: &[usize]) -> String {
+        open.iter()
+            .rev()
+            .map(|&w| self.wrappers[w].closer)
+            .collect()
+    }
+
+    /// Builds a chunk starting from the unit at `start`, and returns it with the index of the next unit.
+    fn chunk(&self, start: usize, limit: usize) -> (String, usize) {
+        let mut text = String::new();
+        let mut len = 0;
+        let mut open: &[usize] = &[];
+        let mut line_break: Option<Break> = None;
+        let mut word_break: Option<Break> = None;
+
+        for (index, unit) in self.units.iter().enumerate().skip(start) {
+            let common = open
+                .iter()
+                .zip(&unit.wrappers)
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            let mut transition = self.closers(&open[common..]);
+            for &w in &unit.wrappers[common..] {
+                let wrapper = &self.wrappers[w];
+                transition.push_str(if wrapper.first_unit == index {
+                    &wrapper.opener
+                } else {
+                    &wrapper.reopener
+                });
+            }
+
+            let added = char_count(&transition) + char_count(&unit.text);
+            let closing = char_count(&self.closers(&unit.wrappers));
+
+            if index > start {
+                let here = || Break {
+                    offset: text.len(),
+                    open: open.to_vec(),
+                    next: index + 1,
+                };
+                let (this_line_break, this_word_break) = if unit.is_line_break() {
+                    let mut end = here();
+                    // Do not leave `\r` of `\r\n` at the end of the chunk.
+                    if text.ends_with('\r') {
+                        end.offset -= 1;
+                    }
+                    (Some(end), None)
+                } else if unit.is_whitespace()
+                    && !unit.wrappers.iter().any(|&w| self.wrappers[w].is_code)
+                {
+                    (None, Some(here()))
+                } else {
+                    (None, None)
+                };
+
+                if len + added + closing > limit {
+                    let end = this_line_break
+                        .or(line_break)
+                        .or(this_word_break)
+                        .or(word_break)
+                        .unwrap_or(Break {
+                            offset: text.len(),
+                            open: open.to_vec(),
+                            next: index,
+                        });
+                    text.truncate(end.offset);
+                    if !end.open.iter().any(|&w| self.wrappers[w].is_code) {
+                        text.truncate(text.trim_end().len());
+                    }
+                    text.push_str(&self.closers(&end.open));
+                    return (text, end.next);
+                }
+
+                line_break = this_line_break.or(line_break);
+                word_break = this_word_break.or(word_break);
+            }
+
+            text.push_str(&transition);
+            text.push_str(&unit.text);
+            len += added;
+            open = &unit.wrappers;
+        }
+
+        text.push_str(&self.closers(open));
+        (text, self.units.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::delimiter::unterminated_delimiters;
+    use crate::parse;
+
+    fn split_with_limit(document: &MarkdownDocument, limit: usize) -> Vec<String> {
+        split(document, &SplitOption::new().limit(limit))
+    }
+
+    #[test]
+    fn test_split_fits() {
+        let ast = parse("**bold** text");
+        assert_eq!(split_with_limit(&ast, 13), vec!["**bold** text"]);
+        assert_eq!(split(&ast, &SplitOption::new()), vec!["**bold** text"]);
+        assert_eq!(
+            split_with_limit(&MarkdownDocument::new(vec![]), 10),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_split_prefers_line_break() {
+        let ast = parse("aaa bbb\nccc ddd");
+        assert_eq!(split_with_limit(&ast, 12), vec!["aaa bbb", "ccc ddd"]);
+        assert_eq!(split_with_limit(&ast, 10), vec!["aaa bbb", "ccc ddd"]);
+        assert_eq!(split_with_limit(&ast, 5), vec!["aaa", "bbb", "ccc", "ddd"]);
+    }
+
+    #[test]
+    fn test_split_crlf() {
+        let ast = parse("aaa\r\nbbb");
+        assert_eq!(split_with_limit(&ast, 5), vec!["aaa", "bbb"]);
+    }
+
+    #[test]
+    fn test_split_hard() {
+        let ast = parse("abcdefgh");
+        assert_eq!(split_with_limit(&ast, 3), vec!["abc", "def", "gh"]);
+
+        let ast = parse("**abcdef**");
+        assert_eq!(split_with_limit(&ast, 7), vec!["**abc**", "**def**"]);
+    }
+
+    #[test]
+    fn test_split_reopen_styles() {
+        let ast = parse("__under ~~strike *italics* text~~__ end");
+        assert_eq!(
+            split_with_limit(&ast, 20),
+            vec![
+                "__under ~~strike~~__",
+                "__~~*italics*~~__",
+                "__~~text~~__ end",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_code_block() {
+        let ast = parse("```rs\nlet a = 1;\nlet b = 2;\n```");
+        assert_eq!(
+            split_with_limit(&ast, 20),
+            vec!["```rs\nlet a = 1;```", "```rs\nlet b = 2;\n```"]
+        );
+
+        let ast = MarkdownDocument::new(vec![one_line_code("a b c d")]);
+        assert_eq!(split_with_limit(&ast, 5), vec!["`a b`", "` c `", "`d`"]);
+    }
+
+    #[test]
+    fn test_split_block_quote() {
+        let ast = MarkdownDocument::new(vec![block_quote(vec![bold("aa\nbb\ncc")])]);
+        assert_eq!(ast.to_string(), "> **aa\n> bb\n> cc**");
+        assert_eq!(
+            split_with_limit(&ast, 13),
+            vec!["> **aa\n> bb**", "> **cc**"]
+        );
+    }
+
+    #[test]
+    fn test_split_atomic_units() {
+        let ast = parse("ab<:emoji:123>👍");
+        assert_eq!(split_with_limit(&ast, 12), vec!["ab", "<:emoji:123>", "👍"]);
+    }
+
+    #[test]
+    fn test_split_small_limit() {
+        let ast = parse("**abc**");
+        assert_eq!(split_with_limit(&ast, 2), vec!["**a**", "**b**", "**c**"]);
+    }
+
+    #[test]
+    fn test_split_chunks_are_valid() {
+        let ast = parse(include_str!("../tests/example.md"));
+
+        for limit in [20, 50, 100, 300] {
+            for chunk in split_with_limit(&ast, limit) {
+                assert!(char_count(&chunk) <= limit, "{:?} exceeds {}", chunk, limit);
+                assert_eq!(unterminated_delimiters(&chunk), vec![], "{:?}", chunk);
+            }
+        }
+    }
+}
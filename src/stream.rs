@@ -0,0 +1,319 @@
+//! Parser for partially received text
+//!
+//! [`stream`](crate::stream) module provides [`StreamingParser`], which accepts text chunk by chunk
+//! and reports the formatting state in the middle of the stream.
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::builder::*;
+//! use discord_md::ast::MarkdownDocument;
+//! use discord_md::delimiter::Delimiter;
+//! use discord_md::stream::StreamingParser;
+//!
+//! let mut parser = StreamingParser::new();
+//! parser.push("Here is **the ans");
+//!
+//! assert_eq!(parser.open_delimiters(), vec![Delimiter::Bold]);
+//! assert_eq!(
+//!     parser.snapshot().unwrap(),
+//!     MarkdownDocument::new(vec![plain("Here is "), bold("the ans")])
+//! );
+//!
+//! parser.push("wer**.");
+//!
+//! assert_eq!(parser.open_delimiters(), vec![]);
+//! assert_eq!(parser.text(), "Here is **the answer**.");
+//! ```
+
+use crate::ast::{MarkdownDocument, MarkdownElement};
+use crate::delimiter::{auto_close, unterminated_delimiters, Delimiter};
+use crate::error::ParseError;
+use crate::{parser, try_parse};
+
+/// A push-based parser that accepts text chunk by chunk.
+///
+/// Top-level elements that no further text can change are parsed only once,
+/// so each chunk costs time proportional to the text after the last of them.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub struct StreamingParser {
+    text: String,
+    /// Top-level elements at the start of the text, which stay the same whatever text is appended.
+    stable: Vec<MarkdownElement>,
+    /// Byte length of the text parsed into `stable`.
+    stable_len: usize,
+}
+
+impl StreamingParser {
+    /// Creates a streaming parser without any text.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Appends a chunk of text.
+    pub fn push(&mut self, chunk: &str) {
+        self.text.push_str(chunk);
+        self.advance();
+    }
+
+    /// Moves top-level elements that appended text can no longer change into `stable`.
+    ///
+    /// An element is stable if it ends before the first unterminated delimiter,
+    /// which may be parsed differently once its closing delimiter is received, and is not the last element.
+    fn advance(&mut self) {
+        let tail = &self.text[self.stable_len..];
+        let limit = unterminated_delimiters(tail)
+            .first()
+            .map_or(tail.len(), |&(offset, _)| offset);
+
+        let mut rest = tail;
+        while let Ok((r, element)) = parser::markdown_element(rest) {
+            if r.is_empty() || r.len() == rest.len() || tail.len() - r.len() > limit {
+                break;
+            }
+            self.stable.push(element);
+            rest = r;
+        }
+        self.stable_len += tail.len() - rest.len();
+    }
+
+    /// Returns the text after the stable elements.
+    fn tail(&self) -> &str {
+        &self.text[self.stable_len..]
+    }
+
+    /// Parses the text after the stable elements, and prepends them to the result.
+    fn parse_tail(&self, tail: &str) -> Result<MarkdownDocument, ParseError> {
+        let parsed = try_parse(tail).map_err(|err| match err {
+            ParseError::Internal { offset } => ParseError::Internal {
+                offset: self.stable_len + offset,
+            },
+            err => err,
+        })?;
+
+        let mut elements = self.stable.clone();
+        elements.extend(parsed.into_content().into_inner());
+        Ok(MarkdownDocument::new(elements))
+    }
+
+    /// Returns the text received so far.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the delimiters which are opened but not closed yet, outermost first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use discord_md::delimiter::Delimiter;
+    /// use discord_md::stream::StreamingParser;
+    ///
+    /// let mut parser = StreamingParser::new();
+    /// parser.push("||spoiler with ```rust\nfn main() {");
+    ///
+    /// assert_eq!(
+    ///     parser.open_delimiters(),
+    ///     vec![Delimiter::Spoiler, Delimiter::MultiLineCode]
+    /// );
+    /// ```
+    pub fn open_delimiters(&self) -> Vec<Delimiter> {
+        // The stable elements have no unterminated delimiters.
+        unterminated_delimiters(self.tail())
+            .into_iter()
+            .map(|(_, delimiter)| delimiter)
+            .collect()
+    }
+
+    /// Parses the text received so far as if every open delimiter were closed at the end.
+    ///
+    /// The text is repaired with [`auto_close`] before parsing. The result is suitable for showing an interim version of the message.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use discord_md::ast::MarkdownDocument;
+    /// use discord_md::builder::*;
+    /// use discord_md::stream::StreamingParser;
+    ///
+    /// let mut parser = StreamingParser::new();
+    /// parser.push("```js\nconsole.log(1);\n`");
+    ///
+    /// assert_eq!(
+    ///     parser.snapshot().unwrap(),
+    ///     MarkdownDocument::new(vec![
+    ///         multi_line_code("\nconsole.log(1);\n", Some("js".to_string()))
+    ///     ])
+    /// );
+    /// ```
+    pub fn snapshot(&self) -> Result<MarkdownDocument, ParseError> {
+        // Delimiters closed by `auto_close` are all after the stable elements.
+        self.parse_tail(&auto_close(self.tail()))
+    }
+
+    /// Parses the text received so far as it is, without closing open delimiters.
+    pub fn document(&self) -> Result<MarkdownDocument, ParseError> {
+        self.parse_tail(self.tail())
+    }
+
+    /// Consumes the parser and returns the text received so far.
+    pub fn into_text(self) -> String {
+        self.text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+
+    #[test]
+    fn test_push() {
+        let mut parser = StreamingParser::new();
+        assert_eq!(parser.text(), "");
+
+        parser.push("**he");
+        parser.push("llo**");
+        assert_eq!(parser.text(), "**hello**");
+        assert_eq!(parser.into_text(), "**hello**");
+    }
+
+    #[test]
+    fn test_open_delimiters() {
+        let mut parser = StreamingParser::new();
+        assert_eq!(parser.open_delimiters(), vec![]);
+
+        parser.push("__under ~~strike");
+        assert_eq!(
+            parser.open_delimiters(),
+            vec![Delimiter::Underline, Delimiter::Strikethrough]
+        );
+
+        parser.push("~~");
+        assert_eq!(parser.open_delimiters(), vec![Delimiter::Underline]);
+
+        parser.push(" `code");
+        assert_eq!(
+            parser.open_delimiters(),
+            vec![Delimiter::Underline, Delimiter::OneLineCode]
+        );
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut parser = StreamingParser::new();
+        assert_eq!(parser.snapshot(), Ok(MarkdownDocument::new(vec![])));
+
+        parser.push("**bold ||spoil");
+        assert_eq!(
+            parser.snapshot(),
+            Ok(MarkdownDocument::new(vec![bold(vec![
+                plain("bold "),
+                spoiler("spoil")
+            ])]))
+        );
+
+        // A partially received closer is completed.
+        parser.push("er|");
+        assert_eq!(
+            parser.snapshot(),
+            Ok(MarkdownDocument::new(vec![bold(vec![
+                plain("bold "),
+                spoiler("spoiler")
+            ])]))
+        );
+
+        parser.push("|** done");
+        assert_eq!(
+            parser.snapshot(),
+            Ok(MarkdownDocument::new(vec![
+                bold(vec![plain("bold "), spoiler("spoiler")]),
+                plain(" done")
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_snapshot_dangling_delimiter() {
+        let mut parser = StreamingParser::new();
+        parser.push("text **");
+        assert_eq!(
+            parser.snapshot(),
+            Ok(MarkdownDocument::new(vec![plain("text ")]))
+        );
+    }
+
+    #[test]
+    fn test_stable_elements() {
+        let mut parser = StreamingParser::new();
+        parser.push("**a** b *c");
+        assert_eq!(parser.stable, vec![bold("a")]);
+        assert_eq!(parser.tail(), " b *c");
+
+        parser.push("* d");
+        assert_eq!(
+            parser.stable,
+            vec![bold("a"), plain(" b "), italics_star("c")]
+        );
+        assert_eq!(parser.tail(), " d");
+
+        // Nothing after an unterminated delimiter is stable.
+        parser.push(" __e **f** g");
+        assert_eq!(parser.tail(), " d __e **f** g");
+    }
+
+    #[test]
+    fn test_same_as_parsing_whole_text() {
+        let tokens = ["*", "**", "_", "__", "||", "|", "`", "```", "a"];
+
+        // Every sequence of up to 4 tokens, received one token at a time.
+        let mut level = vec![vec![]];
+        for _ in 0..4 {
+            level = level
+                .iter()
+                .flat_map(|chunks: &Vec<&str>| {
+                    tokens.iter().map(move |&token| {
+                        let mut chunks = chunks.clone();
+                        chunks.push(token);
+                        chunks
+                    })
+                })
+                .collect();
+
+            for chunks in &level {
+                let mut parser = StreamingParser::new();
+                for chunk in chunks {
+                    parser.push(chunk);
+                }
+
+                let text = chunks.concat();
+                assert_eq!(parser.document(), try_parse(&text), "{:?}", chunks);
+                assert_eq!(
+                    parser.snapshot(),
+                    try_parse(&auto_close(&text)),
+                    "{:?}",
+                    chunks
+                );
+                assert_eq!(
+                    parser.open_delimiters(),
+                    unterminated_delimiters(&text)
+                        .into_iter()
+                        .map(|(_, delimiter)| delimiter)
+                        .collect::<Vec<_>>(),
+                    "{:?}",
+                    chunks
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_document() {
+        let mut parser = StreamingParser::new();
+        parser.push("**bold");
+        assert_eq!(
+            parser.document(),
+            Ok(MarkdownDocument::new(vec![plain("**bold")]))
+        );
+    }
+}
@@ -0,0 +1,729 @@
+//! Converts between an AST and Telegram's message formats
+//!
+//! [`telegram`](crate::telegram) module provides [`ToTelegram`] trait, which converts a markdown component into
+//! Telegram's MarkdownV2 text or plain text with a list of [`MessageEntity`],
+//! and [`from_entities()`], which converts plain text with entities back into an AST.
+//!
+//! | Element | MarkdownV2 | Entity type |
+//! |---|---|---|
+//! | [`ItalicsStar`], [`ItalicsUnderscore`](crate::ast::ItalicsUnderscore) | `_x_` | `italic` |
+//! | [`Bold`] | `*x*` | `bold` |
+//! | [`Underline`] | `__x__` | `underline` |
+//! | [`Strikethrough`] | `~x~` | `strikethrough` |
+//! | [`Spoiler`] | `\|\|x\|\|` | `spoiler` |
+//! | [`OneLineCode`] | `` `x` `` | `code` |
+//! | [`MultiLineCode`] | ```` ```lang x``` ```` | `pre` with `language` |
+//! | [`BlockQuote`] | `>x` | `blockquote` |
+//!
+//! Offsets and lengths of entities are measured in UTF-16 code units, as Telegram's Bot API specifies.
+//! With `serde` feature, [`MessageEntity`] is serialized in the same JSON format as the Bot API.
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::parse;
+//! use discord_md::telegram::{from_entities, EntityKind, MessageEntity, ToTelegram};
+//!
+//! let ast = parse("**👍 bold** ||v1.0||");
+//!
+//! assert_eq!(ast.to_markdown_v2(), "*👍 bold* ||v1\\.0||");
+//!
+//! let message = ast.to_entities();
+//! assert_eq!(message.text, "👍 bold v1.0");
+//! assert_eq!(
+//!     message.entities,
+//!     vec![
+//!         MessageEntity::new(EntityKind::Bold, 0, 7),
+//!         MessageEntity::new(EntityKind::Spoiler, 8, 4),
+//!     ]
+//! );
+//!
+//! assert_eq!(from_entities(&message.text, &message.entities), ast);
+//! ```
+
+use crate::ast::{
+    BlockQuote, Bold, ItalicsStar, MarkdownDocument, MarkdownElement, MarkdownElementCollection,
+    MultiLineCode, OneLineCode, Plain, Spoiler, Strikethrough, Underline,
+};
+
+/// Plain text and entities of a Telegram message, returned by [`to_entities()`](ToTelegram::to_entities()).
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TelegramMessage {
+    /// The text of the message, without any markup
+    pub text: String,
+
+    /// Styles of the text, sorted by the offset
+    pub entities: Vec<MessageEntity>,
+}
+
+/// A style of a range of text, like Telegram's `MessageEntity`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MessageEntity {
+    /// The type of the entity
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub kind: EntityKind,
+
+    /// The offset in UTF-16 code units to the start of the entity
+    pub offset: usize,
+
+    /// The length of the entity in UTF-16 code units
+    pub length: usize,
+}
+
+impl MessageEntity {
+    pub fn new(kind: EntityKind, offset: usize, length: usize) -> Self {
+        Self {
+            kind,
+            offset,
+            length,
+        }
+    }
+}
+
+/// The type of a [`MessageEntity`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum EntityKind {
+    Bold,
+    Italic,
+    Underline,
+    Strikethrough,
+    Spoiler,
+    Code,
+    Pre {
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        language: Option<String>,
+    },
+    Blockquote,
+    /// A link with a label, converted into a masked link like `[label](url)` by [`from_entities()`]
+    TextLink {
+        url: String,
+    },
+    /// Other types, such as `mention` and `hashtag`, whose text is kept as it is by [`from_entities()`]
+    #[cfg_attr(feature = "serde", serde(other))]
+    Other,
+}
+
+/// A trait for converting a markdown component into Telegram's message formats.
+pub trait ToTelegram {
+    /// Returns the component as MarkdownV2 text, escaping characters as Telegram requires.
+    fn to_markdown_v2(&self) -> String;
+
+    /// Returns the component as plain text with entities.
+    fn to_entities(&self) -> TelegramMessage;
+}
+
+impl ToTelegram for MarkdownDocument {
+    fn to_markdown_v2(&self) -> String {
+        self.content().to_markdown_v2()
+    }
+
+    fn to_entities(&self) -> TelegramMessage {
+        self.content().to_entities()
+    }
+}
+
+impl ToTelegram for MarkdownElementCollection {
+    fn to_markdown_v2(&self) -> String {
+        let mut writer = MarkdownV2Writer::default();
+        writer.elements(self.get());
+        writer.output
+    }
+
+    fn to_entities(&self) -> TelegramMessage {
+        let mut writer = EntityWriter::default();
+        writer.elements(self.get());
+        writer.message
+    }
+}
+
+impl ToTelegram for MarkdownElement {
+    fn to_markdown_v2(&self) -> String {
+        let mut writer = MarkdownV2Writer::default();
+        writer.element(self);
+        writer.output
+    }
+
+    fn to_entities(&self) -> TelegramMessage {
+        let mut writer = EntityWriter::default();
+        writer.element(self);
+        writer.message
+    }
+}
+
+/// Writes MarkdownV2 text.
+#[derive(Default)]
+struct MarkdownV2Writer {
+    output: String,
+    /// `true` if the output ends with a delimiter, rather than text.
+    after_delimiter: bool,
+}
+
+impl MarkdownV2Writer {
+    fn elements(&mut self, elements: &[MarkdownElement]) {
+        for element in elements {
+            self.element(element);
+        }
+    }
+
+    fn element(&mut self, element: &MarkdownElement) {
+        match element {
+            MarkdownElement::Plain(x) => self.text(&escape(x.content())),
+            MarkdownElement::ItalicsStar(x) => self.styled(x.content(), "_"),
+            MarkdownElement::ItalicsUnderscore(x) => self.styled(x.content(), "_"),
+            MarkdownElement::Bold(x) => self.styled(x.content(), "*"),
+            MarkdownElement::Underline(x) => self.styled(x.content(), "__"),
+            MarkdownElement::Strikethrough(x) => self.styled(x.content(), "~"),
+            MarkdownElement::Spoiler(x) => self.styled(x.content(), "||"),
+            MarkdownElement::OneLineCode(x) => {
+                self.text(&format!("`{}`", escape_code(x.content())));
+            }
+            MarkdownElement::MultiLineCode(x) => {
+                let mut content = escape_code(pre_content(x.content()));
+                if !content.is_empty() && !content.ends_with('\n') {
+                    content.push('\n');
+                }
+                self.text(&format!(
+                    "```{}\n{}```",
+                    x.language().unwrap_or_default(),
+                    content
+                ));
+            }
+            MarkdownElement::BlockQuote(x) => {
+                let mut writer = MarkdownV2Writer::default();
+                writer.elements(x.content().get());
+                let quoted = writer
+                    .output
+                    .split('\n')
+                    .map(|line| format!(">{}", line))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                self.text(&quoted);
+            }
+        }
+    }
+
+    fn styled(&mut self, content: &MarkdownElementCollection, delimiter: &str) {
+        self.delimiter(delimiter);
+        self.elements(content.get());
+        self.delimiter(delimiter);
+    }
+
+    /// Writes a delimiter. Adjacent delimiters like `_` and `__` are ambiguous,
+    /// so they are separated by `\r`, which Telegram ignores.
+    fn delimiter(&mut self, delimiter: &str) {
+        if self.after_delimiter && self.output.ends_with(&delimiter[..1]) {
+            self.output.push('\r');
+        }
+        self.output.push_str(delimiter);
+        self.after_delimiter = true;
+    }
+
+    fn text(&mut self, text: &str) {
+        self.output.push_str(text);
+        self.after_delimiter = false;
+    }
+}
+
+/// Writes plain text and entities.
+#[derive(Default)]
+struct EntityWriter {
+    message: TelegramMessage,
+    /// The length of the text in UTF-16 code units.
+    len: usize,
+}
+
+impl EntityWriter {
+    fn elements(&mut self, elements: &[MarkdownElement]) {
+        for element in elements {
+            self.element(element);
+        }
+    }
+
+    fn element(&mut self, element: &MarkdownElement) {
+        match element {
+            MarkdownElement::Plain(x) => self.text(x.content()),
+            MarkdownElement::ItalicsStar(x) => self.styled(EntityKind::Italic, x.content()),
+            MarkdownElement::ItalicsUnderscore(x) => self.styled(EntityKind::Italic, x.content()),
+            MarkdownElement::Bold(x) => self.styled(EntityKind::Bold, x.content()),
+            MarkdownElement::Underline(x) => self.styled(EntityKind::Underline, x.content()),
+            MarkdownElement::Strikethrough(x) => {
+                self.styled(EntityKind::Strikethrough, x.content())
+            }
+            MarkdownElement::Spoiler(x) => self.styled(EntityKind::Spoiler, x.content()),
+            MarkdownElement::OneLineCode(x) => {
+                self.entity(EntityKind::Code, |writer| writer.text(x.content()));
+            }
+            MarkdownElement::MultiLineCode(x) => {
+                let kind = EntityKind::Pre {
+                    language: x.language().map(String::from),
+                };
+                self.entity(kind, |writer| writer.text(pre_content(x.content())));
+            }
+            MarkdownElement::BlockQuote(x) => self.styled(EntityKind::Blockquote, x.content()),
+        }
+    }
+
+    fn styled(&mut self, kind: EntityKind, content: &MarkdownElementCollection) {
+        self.entity(kind, |writer| writer.elements(content.get()));
+    }
+
+    /// Writes the content, and adds an entity covering it unless it is empty.
+    ///
+    /// The entity is added before entities in the content, so that entities are sorted by the offset.
+    fn entity(&mut self, kind: EntityKind, content: impl FnOnce(&mut Self)) {
+        let index = self.message.entities.len();
+        let offset = self.len;
+        self.message
+            .entities
+            .push(MessageEntity::new(kind, offset, 0));
+
+        content(self);
+
+        if self.len == offset {
+            self.message.entities.remove(index);
+        } else {
+            self.message.entities[index].length = self.len - offset;
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        self.message.text.push_str(text);
+        self.len += text.encode_utf16().count();
+    }
+}
+
+/// Returns the content of a code block without the line breaks next to the delimiters.
+fn pre_content(content: &str) -> &str {
+    let content = content
+        .strip_prefix("\r\n")
+        .or_else(|| content.strip_prefix('\n'))
+        .unwrap_or(content);
+    content
+        .strip_suffix("\r\n")
+        .or_else(|| content.strip_suffix('\n'))
+        .unwrap_or(content)
+}
+
+/// Escapes every character that MarkdownV2 reserves outside code.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "_*[]()~`>#+-=|{}.!\\".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escapes characters that MarkdownV2 reserves inside code.
+fn escape_code(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('`', "\\`")
+}
+
+/// Converts plain text with Telegram's entities into an AST.
+///
+/// Offsets and lengths of entities are measured in UTF-16 code units.
+/// Entities may be given in any order, and may overlap partially; an entity crossing the end of another entity is split there.
+/// Entities out of the text, and ranges splitting a surrogate pair, are clamped to the nearest character boundary.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::ast::MarkdownDocument;
+/// use discord_md::builder::*;
+/// use discord_md::telegram::{from_entities, EntityKind, MessageEntity};
+///
+/// let entities = vec![
+///     MessageEntity::new(EntityKind::Bold, 0, 7),
+///     MessageEntity::new(EntityKind::Italic, 5, 6),
+///     MessageEntity::new(EntityKind::TextLink { url: "https://example.com".to_string() }, 12, 4),
+/// ];
+///
+/// assert_eq!(
+///     from_entities("bold italic link", &entities),
+///     MarkdownDocument::new(vec![
+///         bold(vec![plain("bold "), italics_star("it")]),
+///         italics_star("alic"),
+///         plain(" [link](https://example.com)"),
+///     ])
+/// );
+/// ```
+pub fn from_entities(text: &str, entities: &[MessageEntity]) -> MarkdownDocument {
+    // Byte offset of each UTF-16 offset, where the last one is the end of the text.
+    let mut boundaries = Vec::with_capacity(text.len() + 1);
+    for (offset, c) in text.char_indices() {
+        boundaries.resize(boundaries.len() + c.len_utf16(), offset);
+    }
+    boundaries.push(text.len());
+    let byte_offset = |utf16: usize| boundaries[utf16.min(boundaries.len() - 1)];
+
+    let spans = entities
+        .iter()
+        .map(|entity| Span {
+            start: byte_offset(entity.offset),
+            end: byte_offset(entity.offset.saturating_add(entity.length)),
+            kind: &entity.kind,
+        })
+        .filter(|span| span.start < span.end)
+        .collect();
+
+    MarkdownDocument::new(build(text, 0, text.len(), spans))
+}
+
+/// A range of an entity in bytes.
+#[derive(Clone)]
+struct Span<'a> {
+    start: usize,
+    end: usize,
+    kind: &'a EntityKind,
+}
+
+/// Builds elements of the text from `start` to `end`, which contains the spans.
+fn build(text: &str, start: usize, end: usize, mut spans: Vec<Span>) -> Vec<MarkdownElement> {
+    let mut elements = Vec::new();
+    let mut cursor = start;
+
+    loop {
+        // Outer spans come first, so that inner spans are built as their children.
+        spans.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+        if spans.is_empty() {
+            break;
+        }
+        let span = spans.remove(0);
+
+        if cursor < span.start {
+            elements.push(Plain::new(&text[cursor..span.start]).into());
+        }
+
+        let mut children = Vec::new();
+        let mut rest = Vec::new();
+        for other in spans {
+            if other.start >= span.end {
+                rest.push(other);
+            } else if other.end <= span.end {
+                children.push(other);
+            } else {
+                children.push(Span {
+                    end: span.end,
+                    ..other.clone()
+                });
+                rest.push(Span {
+                    start: span.end,
+                    ..other
+                });
+            }
+        }
+        spans = rest;
+
+        elements.extend(entity_elements(text, &span, children));
+        cursor = span.end;
+    }
+
+    if cursor < end {
+        elements.push(Plain::new(&text[cursor..end]).into());
+    }
+    merge_plain(elements)
+}
+
+/// Builds elements of an entity.
+fn entity_elements(text: &str, span: &Span, children: Vec<Span>) -> Vec<MarkdownElement> {
+    let content = &text[span.start..span.end];
+    let inner = || build(text, span.start, span.end, children.clone());
+
+    let element: MarkdownElement = match span.kind {
+        EntityKind::Bold => Bold::new(inner()).into(),
+        EntityKind::Italic => ItalicsStar::new(inner()).into(),
+        EntityKind::Underline => Underline::new(inner()).into(),
+        EntityKind::Strikethrough => Strikethrough::new(inner()).into(),
+        EntityKind::Spoiler => Spoiler::new(inner()).into(),
+        EntityKind::Code => OneLineCode::new(content).into(),
+        EntityKind::Pre { language } => {
+            let mut code = format!("\n{}", content);
+            if !code.ends_with('\n') {
+                code.push('\n');
+            }
+            MultiLineCode::new(code, language.clone()).into()
+        }
+        EntityKind::Blockquote => BlockQuote::new(inner()).into(),
+        EntityKind::TextLink { url } => {
+            let mut elements = vec![Plain::new("[").into()];
+            elements.extend(inner());
+            elements.push(Plain::new(format!("]({})", url)).into());
+            return merge_plain(elements);
+        }
+        EntityKind::Other => return inner(),
+    };
+    vec![element]
+}
+
+/// Merges adjacent plain text into one element.
+fn merge_plain(elements: Vec<MarkdownElement>) -> Vec<MarkdownElement> {
+    let mut merged: Vec<MarkdownElement> = Vec::with_capacity(elements.len());
+    for element in elements {
+        match (merged.last_mut(), element) {
+            (Some(MarkdownElement::Plain(last)), MarkdownElement::Plain(plain)) => {
+                last.content_mut().push_str(plain.content());
+            }
+            (_, element) => merged.push(element),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::parse;
+
+    fn entity(kind: EntityKind, offset: usize, length: usize) -> MessageEntity {
+        MessageEntity::new(kind, offset, length)
+    }
+
+    #[test]
+    fn test_to_markdown_v2() {
+        assert_eq!(
+            parse("*a* _b_ **c** __d__ ~~e~~ ||f||").to_markdown_v2(),
+            "_a_ _b_ *c* __d__ ~e~ ||f||"
+        );
+        assert_eq!(
+            MarkdownDocument::new(vec![
+                one_line_code("a\\b`c"),
+                plain(" "),
+                multi_line_code("\nlet x = `1`;\n", Some("rs".to_string())),
+            ])
+            .to_markdown_v2(),
+            "`a\\\\b\\`c` ```rs\nlet x = \\`1\\`;\n```"
+        );
+        assert_eq!(
+            MarkdownDocument::new(vec![
+                plain("a\n"),
+                block_quote(vec![plain("b\n"), bold("c")]),
+                plain("\nd"),
+            ])
+            .to_markdown_v2(),
+            "a\n>b\n>*c*\nd"
+        );
+    }
+
+    #[test]
+    fn test_to_markdown_v2_ambiguous_delimiters() {
+        assert_eq!(italics_star(underline("a")).to_markdown_v2(), "_\r__a__\r_");
+        assert_eq!(
+            MarkdownDocument::new(vec![bold("a"), bold("b")]).to_markdown_v2(),
+            "*a*\r*b*"
+        );
+        assert_eq!(
+            MarkdownDocument::new(vec![bold("a"), italics_star("b")]).to_markdown_v2(),
+            "*a*_b_"
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("_*[]()~`>#+-=|{}.!\\"),
+            "\\_\\*\\[\\]\\(\\)\\~\\`\\>\\#\\+\\-\\=\\|\\{\\}\\.\\!\\\\"
+        );
+        assert_eq!(escape("a b"), "a b");
+        assert_eq!(escape_code("`\\*"), "\\`\\\\*");
+    }
+
+    #[test]
+    fn test_to_entities() {
+        let message = parse("a **b _c_** `d` ```rs\ne\n```").to_entities();
+
+        assert_eq!(message.text, "a b c d e");
+        assert_eq!(
+            message.entities,
+            vec![
+                entity(EntityKind::Bold, 2, 3),
+                entity(EntityKind::Italic, 4, 1),
+                entity(EntityKind::Code, 6, 1),
+                entity(
+                    EntityKind::Pre {
+                        language: Some("rs".to_string())
+                    },
+                    8,
+                    1
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_entities_utf16() {
+        let message = parse("👍 ||日本|| __😀x__").to_entities();
+
+        assert_eq!(message.text, "👍 日本 😀x");
+        assert_eq!(
+            message.entities,
+            vec![
+                entity(EntityKind::Spoiler, 3, 2),
+                entity(EntityKind::Underline, 6, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_entities_block_quote() {
+        let message =
+            MarkdownDocument::new(vec![block_quote(vec![plain("a "), spoiler("b")])]).to_entities();
+
+        assert_eq!(message.text, "a b");
+        assert_eq!(
+            message.entities,
+            vec![
+                entity(EntityKind::Blockquote, 0, 3),
+                entity(EntityKind::Spoiler, 2, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_entities_empty() {
+        let message = MarkdownDocument::new(vec![bold(vec![]), plain("a")]).to_entities();
+
+        assert_eq!(message.text, "a");
+        assert_eq!(message.entities, vec![]);
+    }
+
+    #[test]
+    fn test_from_entities() {
+        assert_eq!(
+            from_entities(
+                "a b c",
+                &[
+                    entity(EntityKind::Italic, 4, 1),
+                    entity(EntityKind::Bold, 2, 3),
+                ]
+            ),
+            MarkdownDocument::new(vec![
+                plain("a "),
+                bold(vec![plain("b "), italics_star("c")]),
+            ])
+        );
+        assert_eq!(
+            from_entities(
+                "q\ncode",
+                &[
+                    entity(EntityKind::Blockquote, 0, 1),
+                    entity(EntityKind::Pre { language: None }, 2, 4),
+                ]
+            ),
+            MarkdownDocument::new(vec![
+                block_quote("q"),
+                plain("\n"),
+                multi_line_code("\ncode\n", None),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_from_entities_overlap() {
+        assert_eq!(
+            from_entities(
+                "abcd",
+                &[
+                    entity(EntityKind::Bold, 0, 3),
+                    entity(EntityKind::Strikethrough, 1, 3),
+                ]
+            ),
+            MarkdownDocument::new(vec![
+                bold(vec![plain("a"), strikethrough("bc")]),
+                strikethrough("d"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_from_entities_utf16() {
+        assert_eq!(
+            from_entities(
+                "👍 a 😀",
+                &[
+                    entity(EntityKind::Code, 3, 1),
+                    entity(EntityKind::Spoiler, 5, 2),
+                    // Out of the text
+                    entity(EntityKind::Bold, 7, 10),
+                ]
+            ),
+            MarkdownDocument::new(vec![
+                plain("👍 "),
+                one_line_code("a"),
+                plain(" "),
+                spoiler("😀")
+            ])
+        );
+    }
+
+    #[test]
+    fn test_from_entities_other() {
+        assert_eq!(
+            from_entities(
+                "@user #tag link",
+                &[
+                    entity(EntityKind::Other, 0, 5),
+                    entity(EntityKind::Bold, 6, 4),
+                    entity(
+                        EntityKind::TextLink {
+                            url: "https://a".to_string()
+                        },
+                        11,
+                        4
+                    ),
+                ]
+            ),
+            MarkdownDocument::new(vec![
+                plain("@user "),
+                bold("#tag"),
+                plain(" [link](https://a)"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_entities_round_trip() {
+        let ast = parse("plain *it* **bold __under__** ~~s~~ ||👍|| `c` ```js\nx\n```");
+        let message = ast.to_entities();
+
+        assert_eq!(from_entities(&message.text, &message.entities), ast);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_entity_serde() {
+        let entities = vec![
+            entity(EntityKind::Bold, 0, 4),
+            entity(
+                EntityKind::Pre {
+                    language: Some("rust".to_string()),
+                },
+                5,
+                3,
+            ),
+            entity(EntityKind::Pre { language: None }, 9, 1),
+        ];
+        let json = r#"[{"type":"bold","offset":0,"length":4},{"type":"pre","language":"rust","offset":5,"length":3},{"type":"pre","offset":9,"length":1}]"#;
+
+        assert_eq!(serde_json::to_string(&entities).unwrap(), json);
+        assert_eq!(
+            serde_json::from_str::<Vec<MessageEntity>>(json).unwrap(),
+            entities
+        );
+        assert_eq!(
+            serde_json::from_str::<MessageEntity>(r#"{"type":"mention","offset":0,"length":5}"#)
+                .unwrap(),
+            entity(EntityKind::Other, 0, 5)
+        );
+    }
+}
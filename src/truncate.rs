@@ -0,0 +1,294 @@
+//! Truncating documents with an ellipsis
+//!
+//! [`truncate`](crate::truncate) module provides [`truncate()`], which keeps the first visible characters of a document
+//! without cutting inside markup, e.g. for embed fields and notification previews.
+//!
+//! # Example
+//!
+//! ```
+//! use discord_md::parse;
+//! use discord_md::truncate::{truncate, TruncateOption};
+//!
+//! let ast = parse("**Release notes**: ||the new feature|| is here");
+//!
+//! assert_eq!(
+//!     truncate(&ast, 20, &TruncateOption::new()).to_string(),
+//!     "**Release notes**: ||the …||"
+//! );
+//! ```
+
+use crate::ast::{
+    BlockQuote, Bold, ItalicsStar, ItalicsUnderscore, MarkdownDocument, MarkdownElement,
+    MultiLineCode, OneLineCode, Plain, Spoiler, Strikethrough, Underline,
+};
+use crate::length::{element_visible_len, units, visible_char_count, visible_len};
+
+/// Struct that allows to alter [`truncate()`]'s behaviour.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::parse;
+/// use discord_md::truncate::{truncate, TruncateOption};
+///
+/// let ast = parse("a long message");
+///
+/// assert_eq!(truncate(&ast, 8, &TruncateOption::new()).to_string(), "a long …");
+/// assert_eq!(truncate(&ast, 8, &TruncateOption::new().ellipsis("...")).to_string(), "a lon...");
+/// ```
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct TruncateOption {
+    /// Text appended to truncated documents, which is `…` by default
+    pub ellipsis: String,
+}
+
+impl Default for TruncateOption {
+    fn default() -> Self {
+        Self {
+            ellipsis: "…".to_string(),
+        }
+    }
+}
+
+impl TruncateOption {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn ellipsis(mut self, value: impl Into<String>) -> Self {
+        self.ellipsis = value.into();
+        self
+    }
+}
+
+/// Truncates a document to at most `max_len` visible characters, including the ellipsis.
+///
+/// Characters are counted as [`MarkdownDocument::visible_len()`] does.
+///
+/// - A document within `max_len` is returned as it is, without the ellipsis.
+/// - Styled text is truncated as a whole element, so its delimiters are always paired.
+/// - The ellipsis is appended inside the innermost element open at the cut, including code.
+///   If the cut falls between elements, the ellipsis is appended after the last kept element.
+/// - Custom emoji and mentions, like `<:name:id>` and `<@id>`, are never split, and neither are characters.
+/// - An ellipsis longer than `max_len` is shortened to its first `max_len` characters,
+///   so `max_len = 0` returns an empty document.
+///
+/// # Example
+///
+/// ```
+/// use discord_md::parse;
+/// use discord_md::truncate::{truncate, TruncateOption};
+///
+/// let ast = parse("*hi <@123>, `let x = 1;`*");
+/// let option = TruncateOption::new();
+///
+/// assert_eq!(truncate(&ast, 5, &option).to_string(), "*hi <@123>…*");
+/// assert_eq!(truncate(&ast, 10, &option).to_string(), "*hi <@123>, `let…`*");
+/// ```
+pub fn truncate(
+    document: &MarkdownDocument,
+    max_len: usize,
+    option: &TruncateOption,
+) -> MarkdownDocument {
+    if visible_len(document) <= max_len {
+        return document.clone();
+    }
+
+    let ellipsis: String = units(&option.ellipsis).take(max_len).collect();
+    let mut truncator = Truncator {
+        budget: max_len - visible_char_count(&ellipsis),
+        ellipsis: &ellipsis,
+    };
+    MarkdownDocument::new(truncator.elements(document.content().get()))
+}
+
+/// Truncates elements which exceed the budget.
+struct Truncator<'a> {
+    /// Number of visible characters that can still be kept.
+    budget: usize,
+    ellipsis: &'a str,
+}
+
+impl Truncator<'_> {
+    /// Keeps elements within the budget, and then cuts the first element exceeding it.
+    fn elements(&mut self, elements: &[MarkdownElement]) -> Vec<MarkdownElement> {
+        let mut kept = Vec::new();
+
+        for element in elements {
+            let len = element_visible_len(element);
+            if len <= self.budget {
+                self.budget -= len;
+                kept.push(element.clone());
+            } else if self.budget == 0 {
+                break;
+            } else {
+                kept.push(self.element(element));
+                return kept;
+            }
+        }
+
+        // The cut falls between elements.
+        match kept.last_mut() {
+            _ if self.ellipsis.is_empty() => {}
+            Some(MarkdownElement::Plain(last)) => last.content_mut().push_str(self.ellipsis),
+            _ => kept.push(Plain::new(self.ellipsis).into()),
+        }
+        kept
+    }
+
+    /// Cuts an element exceeding the budget.
+    fn element(&mut self, element: &MarkdownElement) -> MarkdownElement {
+        match element {
+            MarkdownElement::Plain(x) => Plain::new(self.text(units(x.content()))).into(),
+            MarkdownElement::ItalicsStar(x) => {
+                ItalicsStar::new(self.elements(x.content().get())).into()
+            }
+            MarkdownElement::ItalicsUnderscore(x) => {
+                ItalicsUnderscore::new(self.elements(x.content().get())).into()
+            }
+            MarkdownElement::Bold(x) => Bold::new(self.elements(x.content().get())).into(),
+            MarkdownElement::Underline(x) => {
+                Underline::new(self.elements(x.content().get())).into()
+            }
+            MarkdownElement::Strikethrough(x) => {
+                Strikethrough::new(self.elements(x.content().get())).into()
+            }
+            MarkdownElement::Spoiler(x) => Spoiler::new(self.elements(x.content().get())).into(),
+            MarkdownElement::OneLineCode(x) => OneLineCode::new(self.code(x.content())).into(),
+            MarkdownElement::MultiLineCode(x) => {
+                MultiLineCode::new(self.code(x.content()), x.language().map(String::from)).into()
+            }
+            MarkdownElement::BlockQuote(x) => {
+                BlockQuote::new(self.elements(x.content().get())).into()
+            }
+        }
+    }
+
+    fn code(&mut self, content: &str) -> String {
+        let chars = content
+            .char_indices()
+            .map(|(offset, c)| &content[offset..offset + c.len_utf8()]);
+        self.text(chars)
+    }
+
+    /// Keeps units within the budget, and appends the ellipsis.
+    fn text<'a>(&mut self, units: impl Iterator<Item = &'a str>) -> String {
+        let mut text: String = units.take(self.budget).collect();
+        self.budget = 0;
+        text.push_str(self.ellipsis);
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::parse;
+
+    fn truncate_to_string(text: &str, max_len: usize) -> String {
+        truncate(&parse(text), max_len, &TruncateOption::new()).to_string()
+    }
+
+    #[test]
+    fn test_truncate_fits() {
+        assert_eq!(truncate_to_string("**bold** text", 9), "**bold** text");
+        assert_eq!(truncate_to_string("", 0), "");
+    }
+
+    #[test]
+    fn test_truncate_plain() {
+        assert_eq!(truncate_to_string("abcdef", 4), "abc…");
+        assert_eq!(truncate_to_string("abcdef", 1), "…");
+        assert_eq!(truncate_to_string("abcdef", 0), "");
+        assert_eq!(truncate_to_string("**abc** def", 0), "");
+    }
+
+    #[test]
+    fn test_truncate_inside_style() {
+        assert_eq!(truncate_to_string("**abc** def", 3), "**ab…**");
+        assert_eq!(truncate_to_string("a __b ~~cde~~__", 6), "a __b ~~c…~~__");
+        assert_eq!(truncate_to_string("a __b ~~cde~~__", 5), "a __b …__");
+        assert_eq!(truncate_to_string("||a|| ||b||", 2), "||a||…");
+    }
+
+    #[test]
+    fn test_truncate_between_elements() {
+        assert_eq!(truncate_to_string("**ab**cd", 3), "**ab**…");
+        assert_eq!(truncate_to_string("a**bc**", 2), "a…");
+    }
+
+    #[test]
+    fn test_truncate_code() {
+        assert_eq!(truncate_to_string("`abcdef`", 4), "`abc…`");
+        assert_eq!(
+            truncate_to_string("```js\nlet a = 1;\n```", 6),
+            "```js\nlet …```"
+        );
+    }
+
+    #[test]
+    fn test_truncate_atomic_units() {
+        assert_eq!(truncate_to_string("a<:emoji:123>bc", 3), "a<:emoji:123>…");
+        assert_eq!(truncate_to_string("a<:emoji:123>bc", 2), "a…");
+        assert_eq!(truncate_to_string("👍👍👍", 2), "👍…");
+    }
+
+    #[test]
+    fn test_truncate_block_quote() {
+        let ast = MarkdownDocument::new(vec![block_quote("a\nbcd")]);
+        assert_eq!(
+            truncate(&ast, 3, &TruncateOption::new()),
+            MarkdownDocument::new(vec![block_quote("a\n…")])
+        );
+    }
+
+    #[test]
+    fn test_truncate_ellipsis() {
+        let option = TruncateOption::new().ellipsis(" [more]");
+        assert_eq!(
+            truncate(&parse("||a long spoiler||"), 10, &option).to_string(),
+            "||a l [more]||"
+        );
+        assert_eq!(
+            truncate(&parse("abc"), 2, &TruncateOption::new().ellipsis("")).to_string(),
+            "ab"
+        );
+        assert_eq!(
+            truncate(&parse("abc"), 2, &TruncateOption::new().ellipsis("")),
+            MarkdownDocument::new(vec![plain("ab")])
+        );
+        assert_eq!(
+            truncate(&parse("**ab**cd"), 2, &TruncateOption::new().ellipsis("")),
+            MarkdownDocument::new(vec![bold("ab")])
+        );
+    }
+
+    #[test]
+    fn test_truncate_long_ellipsis() {
+        let option = TruncateOption::new().ellipsis(" [more]");
+        assert_eq!(truncate(&parse("abcdef"), 5, &option).to_string(), " [mor");
+        assert_eq!(
+            truncate(&parse("**abcdef**"), 3, &option).to_string(),
+            " [m"
+        );
+        assert_eq!(truncate(&parse("abcdef"), 0, &option).to_string(), "");
+    }
+
+    #[test]
+    fn test_truncate_visible_len() {
+        let ast = parse(include_str!("../tests/example.md"));
+        let options = [
+            TruncateOption::new(),
+            TruncateOption::new().ellipsis(""),
+            TruncateOption::new().ellipsis(" [read more]"),
+        ];
+        for option in &options {
+            for max_len in 0..ast.visible_len() {
+                let truncated = truncate(&ast, max_len, option);
+                assert!(truncated.visible_len() <= max_len, "{}", max_len);
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use discord_md::generate::{ToMarkdownString, ToMarkdownStringOption};
+use discord_md::generate::{LineEnding, ToMarkdownString, ToMarkdownStringOption};
 use discord_md::parse;
 
 #[test]
@@ -18,3 +18,17 @@ fn test_parse_then_generate_2() {
         message
     );
 }
+
+#[test]
+fn test_parse_then_generate_crlf() {
+    let message = include_str!("example.md").replace('\n', "\r\n");
+    assert_eq!(
+        parse(&message).to_markdown_string(&ToMarkdownStringOption::new()),
+        message
+    );
+    assert_eq!(
+        parse(&message)
+            .to_markdown_string(&ToMarkdownStringOption::new().line_ending(LineEnding::Lf)),
+        include_str!("example.md")
+    );
+}